@@ -6,6 +6,7 @@ pub enum ExitCode {
     RuntimeFailure = 1,
     Usage = 2,
     Internal = 3,
+    Interrupted = 130,
 }
 
 #[derive(Debug)]
@@ -36,6 +37,13 @@ impl AppError {
         }
     }
 
+    pub fn interrupted<T: Into<String>>(message: T) -> Self {
+        Self {
+            code: ExitCode::Interrupted,
+            message: message.into(),
+        }
+    }
+
     pub fn code(&self) -> i32 {
         self.code as i32
     }
@@ -0,0 +1,104 @@
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+pub const DEFAULT_DIR: &str = ".otto/cache";
+
+#[derive(Debug, Clone)]
+pub struct Cache {
+    root: PathBuf,
+}
+
+impl Cache {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn load(&self, task: &str) -> Option<String> {
+        fs::read_to_string(self.entry_path(task))
+            .ok()
+            .map(|text| text.trim().to_string())
+    }
+
+    pub fn store(&self, task: &str, fingerprint: &str) -> Result<(), String> {
+        fs::create_dir_all(&self.root).map_err(|e| format!("create cache directory: {e}"))?;
+        fs::write(self.entry_path(task), fingerprint).map_err(|e| format!("write cache entry: {e}"))
+    }
+
+    fn entry_path(&self, task: &str) -> PathBuf {
+        self.root
+            .join(format!("{}.fingerprint", task.replace(':', "%3A")))
+    }
+}
+
+pub fn fingerprint(dir: &Path, inputs: &[String], salt: &str) -> Result<String, String> {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+
+    for path in expand_files(dir, inputs)? {
+        let contents = fs::read(&path).map_err(|e| format!("read {}: {e}", path.display()))?;
+        let relative = path.strip_prefix(dir).unwrap_or(&path);
+        hasher.update(relative.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(&contents);
+    }
+
+    Ok(hex(&hasher.finalize()))
+}
+
+pub fn stamp(dir: &Path, patterns: &[String]) -> Result<String, String> {
+    let mut hasher = Sha256::new();
+
+    for path in expand_files(dir, patterns)? {
+        let Ok(meta) = fs::metadata(&path) else {
+            continue;
+        };
+        let modified = meta
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        hasher.update(path.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(meta.len().to_le_bytes());
+        hasher.update(modified.as_nanos().to_le_bytes());
+    }
+
+    Ok(hex(&hasher.finalize()))
+}
+
+pub fn outputs_exist(dir: &Path, outputs: &[String]) -> Result<bool, String> {
+    for pattern in outputs {
+        let matched = expand_paths(dir, pattern)?;
+        if matched.is_empty() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn expand_files(dir: &Path, patterns: &[String]) -> Result<BTreeSet<PathBuf>, String> {
+    let mut files = BTreeSet::new();
+    for pattern in patterns {
+        for path in expand_paths(dir, pattern)? {
+            if path.is_file() {
+                files.insert(path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn expand_paths(dir: &Path, pattern: &str) -> Result<Vec<PathBuf>, String> {
+    let full = dir.join(pattern);
+    let paths = glob::glob(&full.to_string_lossy())
+        .map_err(|e| format!("invalid glob {pattern:?}: {e}"))?;
+    Ok(paths.filter_map(Result::ok).collect())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
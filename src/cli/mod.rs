@@ -1,20 +1,42 @@
 use crate::app_error::AppError;
-use crate::config::{self, Config, Defaults, NotificationSettings, ResolvedTask};
-use crate::history::{DEFAULT_PATH, Filter, Store};
-use crate::model::{RunRecord, RunSource, RunStatus};
+use crate::cache::{self, Cache};
+use crate::config::{
+    self, Config, Container, Defaults, GlobalHooks, HistorySettings, NotificationSettings,
+    ResolvedHook, ResolvedStep, ResolvedTask, ResolvedWait, TaskRef,
+};
+use crate::git;
+use crate::history::{self, Filter, Retention, Store};
+use crate::host;
+use crate::include::{self, FetchMode};
+use crate::lock;
+use crate::metrics;
+use crate::model::{AttemptRecord, GitContext, ReplaySettings, RunRecord, RunSource, RunStatus};
 use crate::notify;
-use crate::output::{self, HistoryRow, TaskRow};
-use crate::runner::{self, Request};
+use crate::output::{self, HistoryRow, HistoryView, TaskRow};
+use crate::prompt;
+use crate::ready;
+use crate::runner::{
+    self, CancelToken, EventSink, OutputBuffer, Request, RestartPolicy, RunEvent, StdinMode,
+    Stream, TeeFile,
+};
+use crate::runs::{self, DetachedRun};
+use crate::schedule::Schedule;
+use crate::schema;
+use crate::smtp;
 use crate::version;
 use clap::builder::styling::{AnsiColor, Effects, Styles};
-use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap::{ArgGroup, Args, CommandFactory, Parser, Subcommand, ValueEnum};
 use clap_complete::{Generator, generate};
 use rand::Rng;
 use serde::Serialize;
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ffi::OsString;
 use std::fs;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, mpsc};
 use std::thread;
 use std::time::Duration;
 use std::time::Instant;
@@ -22,7 +44,7 @@ use time::OffsetDateTime;
 
 const DEFAULT_CONFIG_PATH: &str = "./otto.yml";
 
-const DEFAULT_CONFIG_TEMPLATE: &str = r#"version: 1
+const DEFAULT_CONFIG_TEMPLATE: &str = r#"version: 2
 
 defaults:
   timeout: "2m"      # max runtime per attempt
@@ -34,6 +56,10 @@ notifications:
   desktop: true       # desktop notifications (macOS/Linux)
   # webhook_url: "https://example.com/otto-hook"
   # webhook_timeout: "5s"
+  # slack:
+  #   webhook_url: "https://hooks.slack.com/services/..."
+  #   mention_on_failure: "<!here>"
+  # statsd: "127.0.0.1:8125"
 
 tasks:
   test:
@@ -54,6 +80,15 @@ tasks:
   #   run: "rm -rf ./target"
 "#;
 
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(200);
+const SCHEDULE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+const SERVICE_STABLE_AFTER: Duration = Duration::from_secs(10);
+const SERVICE_MAX_BACKOFF: Duration = Duration::from_secs(30);
+const GREP_MATCH_LINES: usize = 3;
+
+static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+
 #[derive(Debug, Parser)]
 #[command(
     name = "otto",
@@ -64,18 +99,31 @@ tasks:
 struct Cli {
     #[arg(long = "no-color", global = true)]
     no_color: bool,
+    #[arg(long, global = true, conflicts_with = "refresh")]
+    offline: bool,
+    #[arg(long, global = true)]
+    refresh: bool,
     #[command(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
 }
 
 #[derive(Debug, Subcommand)]
 enum Commands {
     Init(InitArgs),
-    Run(RunArgs),
-    History(HistoryArgs),
+    Run(Box<RunArgs>),
+    History(Box<HistoryArgs>),
+    Rerun(RerunArgs),
+    Replay(ReplayArgs),
     Tasks(TasksArgs),
     Validate(ValidateArgs),
+    Migrate(MigrateArgs),
+    Watch(WatchArgs),
+    Schedule(ScheduleArgs),
+    Ps(PsArgs),
+    Logs(LogsArgs),
+    Stop(StopArgs),
     Version,
+    Schema(SchemaArgs),
     Completion(CompletionArgs),
 }
 
@@ -87,7 +135,7 @@ struct InitArgs {
     force: bool,
 }
 
-#[derive(Debug, Args)]
+#[derive(Debug, Default, Args)]
 struct RunArgs {
     task: Option<String>,
     #[arg(last = true, allow_hyphen_values = true)]
@@ -105,31 +153,219 @@ struct RunArgs {
     #[arg(long)]
     retries: Option<i32>,
 
+    #[arg(long = "no-retries")]
+    no_retries: bool,
+
     #[arg(long = "notify-on")]
     notify_on: Option<String>,
 
+    #[arg(long)]
+    interactive: bool,
+
+    #[arg(long = "clean-env", visible_alias = "no-inherit-env")]
+    clean_env: bool,
+
+    #[arg(long = "stdin-file", conflicts_with = "interactive")]
+    stdin_file: Option<PathBuf>,
+
+    #[arg(long)]
+    tee: Option<PathBuf>,
+
+    #[arg(long = "var", value_name = "NAME=VALUE")]
+    vars: Vec<String>,
+
     #[arg(long = "env-file")]
     env_file: Option<PathBuf>,
 
     #[arg(long = "no-dotenv")]
     no_dotenv: bool,
 
+    #[arg(long = "no-history")]
+    no_history: bool,
+
+    #[arg(long)]
+    jobs: Option<usize>,
+
+    #[arg(long = "keep-going", short = 'k')]
+    keep_going: bool,
+
+    #[arg(long)]
+    force: bool,
+
+    #[arg(long)]
+    detach: bool,
+
+    #[arg(long = "detached-id", hide = true)]
+    detached_id: Option<String>,
+
+    #[arg(long = "rerun-of", hide = true)]
+    rerun_of: Option<String>,
+
+    #[arg(skip)]
+    expected_env: Option<ExpectedEnv>,
+
+    #[arg(long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
+
     #[arg(long)]
     json: bool,
+
+    #[arg(long = "json-stream", conflicts_with = "json")]
+    json_stream: bool,
+}
+
+#[derive(Debug, Args)]
+struct WatchArgs {
+    task: String,
+    #[arg(long)]
+    config: Option<PathBuf>,
+    #[arg(long, default_value = "300ms")]
+    debounce: String,
+    #[arg(long = "no-history")]
+    no_history: bool,
+}
+
+#[derive(Debug, Args)]
+struct PsArgs {
+    #[arg(long)]
+    config: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct LogsArgs {
+    id: String,
+    #[arg(long, short)]
+    follow: bool,
+    #[arg(long)]
+    config: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct StopArgs {
+    id: String,
+    #[arg(long)]
+    config: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct ScheduleArgs {
+    #[arg(long)]
+    config: Option<PathBuf>,
 }
 
 #[derive(Debug, Args)]
 struct HistoryArgs {
+    #[command(subcommand)]
+    command: Option<HistoryCommand>,
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
     #[arg(long, default_value_t = 20)]
     limit: usize,
+    #[arg(long, default_value_t = 0)]
+    offset: usize,
+    #[arg(long, value_name = "ID")]
+    before: Option<String>,
     #[arg(long)]
     status: Option<String>,
     #[arg(long)]
     source: Option<String>,
+    #[arg(long)]
+    name: Option<String>,
+    #[arg(long)]
+    since: Option<String>,
+    #[arg(long)]
+    until: Option<String>,
+    #[arg(long)]
+    grep: Option<String>,
+    #[arg(long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
+    #[arg(long, value_enum, conflicts_with = "json")]
+    format: Option<HistoryFormat>,
+    #[arg(long, value_delimiter = ',')]
+    columns: Vec<String>,
+    #[arg(long)]
+    relative: bool,
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum HistoryFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Subcommand)]
+enum HistoryCommand {
+    Open(HistoryOpenArgs),
+    Diff(HistoryDiffArgs),
+    Import(HistoryImportArgs),
+    Last(HistoryLastArgs),
+    Prune(HistoryPruneArgs),
+    Stats(HistoryStatsArgs),
+}
+
+#[derive(Debug, Args)]
+struct HistoryOpenArgs {
+    id: String,
+}
+
+#[derive(Debug, Args)]
+struct HistoryDiffArgs {
+    old: String,
+    new: String,
+}
+
+#[derive(Debug, Args)]
+struct HistoryImportArgs {
+    path: PathBuf,
+}
+
+#[derive(Debug, Args)]
+struct HistoryLastArgs {
+    #[arg(long)]
+    name: Option<String>,
+    #[arg(long = "exit-code")]
+    exit_code: bool,
+}
+
+#[derive(Debug, Args)]
+struct HistoryStatsArgs {
     #[arg(long)]
     json: bool,
 }
 
+#[derive(Debug, Args)]
+#[command(group(ArgGroup::new("rule").required(true).multiple(true)))]
+struct HistoryPruneArgs {
+    #[arg(long, group = "rule")]
+    keep: Option<usize>,
+    #[arg(long = "older-than", group = "rule")]
+    older_than: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct RerunArgs {
+    #[arg(required_unless_present = "last", conflicts_with = "last")]
+    id: Option<String>,
+    #[arg(long)]
+    last: bool,
+    #[arg(long, requires = "last")]
+    failed: bool,
+    #[arg(long)]
+    config: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct ReplayArgs {
+    id: String,
+    #[arg(long)]
+    config: Option<PathBuf>,
+    #[arg(long)]
+    strict: bool,
+}
+
 #[derive(Debug, Args)]
 struct TasksArgs {
     #[arg(long)]
@@ -144,6 +380,16 @@ struct ValidateArgs {
     config: Option<PathBuf>,
     #[arg(long)]
     json: bool,
+    #[arg(long)]
+    strict: bool,
+}
+
+#[derive(Debug, Args)]
+struct MigrateArgs {
+    #[arg(long)]
+    config: Option<PathBuf>,
+    #[arg(long)]
+    output: Option<PathBuf>,
 }
 
 #[derive(Debug, Args)]
@@ -152,6 +398,18 @@ struct CompletionArgs {
     shell: Shell,
 }
 
+#[derive(Debug, Args)]
+struct SchemaArgs {
+    #[arg(value_enum)]
+    kind: SchemaKind,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum SchemaKind {
+    Run,
+    History,
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 enum Shell {
     Bash,
@@ -176,17 +434,43 @@ fn clap_styles() -> Styles {
 pub fn run_cli() -> Result<(), AppError> {
     let cli = Cli::parse();
     output::configure(cli.no_color);
+    let _ = ctrlc::set_handler(|| {
+        if SHUTDOWN.swap(true, Ordering::SeqCst) {
+            std::process::exit(130);
+        }
+        runner::interrupt();
+    });
+    include::configure(if cli.offline {
+        FetchMode::Offline
+    } else if cli.refresh {
+        FetchMode::Refresh
+    } else {
+        FetchMode::Cached
+    });
+
+    let Some(command) = cli.command else {
+        return run_run(RunArgs::default());
+    };
 
-    match cli.command {
+    match command {
         Commands::Init(args) => run_init(args),
-        Commands::Run(args) => run_run(args),
-        Commands::History(args) => run_history(args),
+        Commands::Run(args) => run_run(*args),
+        Commands::History(args) => run_history(*args),
+        Commands::Rerun(args) => run_rerun(args),
+        Commands::Replay(args) => run_replay(args),
         Commands::Tasks(args) => run_tasks(args),
         Commands::Validate(args) => run_validate(args),
+        Commands::Migrate(args) => run_migrate(args),
+        Commands::Watch(args) => run_watch(args),
+        Commands::Schedule(args) => run_schedule(args),
+        Commands::Ps(args) => run_ps(args),
+        Commands::Logs(args) => run_logs(args),
+        Commands::Stop(args) => run_stop(args),
         Commands::Version => {
             println!("{}", version::VALUE);
             Ok(())
         }
+        Commands::Schema(args) => run_schema(args),
         Commands::Completion(args) => run_completion(args),
     }
 }
@@ -214,6 +498,208 @@ fn run_init(args: InitArgs) -> Result<(), AppError> {
 }
 
 fn run_run(args: RunArgs) -> Result<(), AppError> {
+    if args.detach {
+        return detach_run(&args);
+    }
+
+    let detached_id = args.detached_id.clone();
+    let store = runs_store(args.config.as_deref());
+    let result = run_attached(args);
+    if let Some(id) = detached_id {
+        let code = match &result {
+            Ok(()) => 0,
+            Err(err) => err.code(),
+        };
+        store.finish(&id, code).map_err(AppError::internal)?;
+    }
+    result
+}
+
+fn detach_run(args: &RunArgs) -> Result<(), AppError> {
+    if args.json || args.json_stream || args.interactive {
+        return Err(AppError::usage(
+            "--detach cannot be combined with --json, --json-stream, or --interactive",
+        ));
+    }
+
+    let store = runs_store(args.config.as_deref());
+    let id = new_record_id();
+    fs::create_dir_all(store.root())
+        .map_err(|e| AppError::internal(format!("create runs directory: {e}")))?;
+    let log_path = std::path::absolute(store.log_path(&id))
+        .map_err(|e| AppError::internal(format!("resolve run log: {e}")))?;
+    let log = fs::File::create(&log_path)
+        .map_err(|e| AppError::internal(format!("create run log: {e}")))?;
+    let log_err = log
+        .try_clone()
+        .map_err(|e| AppError::internal(format!("create run log: {e}")))?;
+
+    let mut forwarded: Vec<OsString> = Vec::new();
+    let mut in_inline = false;
+    for arg in std::env::args_os().skip(1) {
+        if !in_inline && arg == "--detach" {
+            continue;
+        }
+        if !in_inline && arg == "--" {
+            forwarded.push("--detached-id".into());
+            forwarded.push(id.clone().into());
+            in_inline = true;
+        }
+        forwarded.push(arg);
+    }
+    if !in_inline {
+        forwarded.push("--detached-id".into());
+        forwarded.push(id.clone().into());
+    }
+
+    let exe = std::env::current_exe()
+        .map_err(|e| AppError::internal(format!("locate otto executable: {e}")))?;
+    let mut command = std::process::Command::new(exe);
+    command
+        .args(forwarded)
+        .stdin(std::process::Stdio::null())
+        .stdout(log)
+        .stderr(log_err);
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    let child = command
+        .spawn()
+        .map_err(|e| AppError::internal(format!("start detached run: {e}")))?;
+
+    let name = match (&args.task, &args.name) {
+        (Some(task), _) => task.clone(),
+        (None, Some(name)) => name.clone(),
+        (None, None) if !args.inline.is_empty() => args.inline.join(" "),
+        (None, None) => "default".to_string(),
+    };
+    store
+        .save(&DetachedRun {
+            id: id.clone(),
+            name: name.clone(),
+            pid: child.id(),
+            started_at: OffsetDateTime::now_utc(),
+            log_path: log_path.display().to_string(),
+            exit_code: None,
+        })
+        .map_err(AppError::internal)?;
+
+    eprintln!(
+        "{} detached run \"{name}\" (pid {}); follow it with {}",
+        output::info("i"),
+        child.id(),
+        output::command(&format!("otto logs -f {id}"))
+    );
+    println!("{id}");
+    Ok(())
+}
+
+fn runs_store(config: Option<&Path>) -> runs::Store {
+    let config_path = config.unwrap_or(Path::new(DEFAULT_CONFIG_PATH));
+    let config_dir = match config_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    runs::Store::new(config_dir.join(runs::DEFAULT_DIR))
+}
+
+fn run_ps(args: PsArgs) -> Result<(), AppError> {
+    let runs = runs_store(args.config.as_deref())
+        .list()
+        .map_err(AppError::internal)?;
+    if runs.is_empty() {
+        println!("{} No detached runs.", output::info("i"));
+        return Ok(());
+    }
+
+    for run in &runs {
+        let status = match run.status() {
+            "running" => output::info("running"),
+            "success" => output::success("success"),
+            "interrupted" => output::warning("interrupted"),
+            other => output::failure(other),
+        };
+        println!(
+            "{} {} {} {}",
+            output::bold(&run.id),
+            status,
+            run.name,
+            output::muted(&format!("pid {}", run.pid))
+        );
+    }
+    Ok(())
+}
+
+fn run_logs(args: LogsArgs) -> Result<(), AppError> {
+    let store = runs_store(args.config.as_deref());
+    let run = store.find(&args.id).map_err(AppError::usage)?;
+    let mut file = fs::File::open(&run.log_path).map_err(|e| {
+        AppError::usage(format!("read log {}: {e}", output::command(&run.log_path)))
+    })?;
+    let mut stdout = io::stdout();
+
+    loop {
+        io::copy(&mut file, &mut stdout)
+            .map_err(|e| AppError::internal(format!("write output: {e}")))?;
+        if !args.follow || SHUTDOWN.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        let still_running = store
+            .find(&run.id)
+            .map_err(AppError::internal)?
+            .is_running();
+        if !still_running {
+            io::copy(&mut file, &mut stdout)
+                .map_err(|e| AppError::internal(format!("write output: {e}")))?;
+            return Ok(());
+        }
+        thread::sleep(WATCH_POLL_INTERVAL);
+    }
+}
+
+fn run_stop(args: StopArgs) -> Result<(), AppError> {
+    let store = runs_store(args.config.as_deref());
+    let run = store.find(&args.id).map_err(AppError::usage)?;
+    if !run.is_running() {
+        return Err(AppError::usage(format!(
+            "run {} is not running ({})",
+            run.id,
+            run.status()
+        )));
+    }
+
+    #[cfg(unix)]
+    {
+        let pid = libc::pid_t::try_from(run.pid)
+            .map_err(|_| AppError::internal(format!("invalid pid {}", run.pid)))?;
+        unsafe {
+            libc::kill(pid, libc::SIGINT);
+        }
+
+        let deadline = Instant::now() + STOP_TIMEOUT;
+        while Instant::now() < deadline {
+            let current = store.find(&run.id).map_err(AppError::internal)?;
+            if !current.is_running() {
+                eprintln!("{} stopped run {}", output::success("ok"), run.id);
+                return Ok(());
+            }
+            thread::sleep(WATCH_POLL_INTERVAL);
+        }
+
+        unsafe {
+            libc::kill(-pid, libc::SIGKILL);
+        }
+        eprintln!("{} killed run {}", output::warning("warn"), run.id);
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    Err(AppError::usage("otto stop is only supported on Unix"))
+}
+
+fn run_attached(args: RunArgs) -> Result<(), AppError> {
     let config_path = args
         .config
         .clone()
@@ -227,162 +713,830 @@ fn run_run(args: RunArgs) -> Result<(), AppError> {
         args.env_file.is_some(),
     )?;
 
+    if args.jobs == Some(0) {
+        return Err(AppError::usage("--jobs must be at least 1"));
+    }
+
+    let mut vars = HashMap::new();
+    for var in &args.vars {
+        let Some((name, value)) = var.split_once('=') else {
+            return Err(AppError::usage(format!(
+                "--var expects NAME=VALUE, got {var:?}"
+            )));
+        };
+        vars.insert(name.to_string(), value.to_string());
+    }
+
+    let replay = Some(ReplaySettings {
+        env_file: args
+            .env_file
+            .as_ref()
+            .map(|path| path.display().to_string()),
+        no_dotenv: args.no_dotenv,
+        clean_env: args.clean_env,
+        vars: vars.clone().into_iter().collect(),
+    })
+    .filter(|settings| *settings != ReplaySettings::default());
+
+    let mut tags: Vec<String> = Vec::new();
+    for tag in &args.tags {
+        if tag.is_empty() || tag.contains(char::is_whitespace) {
+            return Err(AppError::usage(format!(
+                "--tag expects a single word, got {tag:?}"
+            )));
+        }
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+
+    let tee = args
+        .tee
+        .as_deref()
+        .map(TeeFile::create)
+        .transpose()
+        .map_err(AppError::usage)?;
+
     if !args.inline.is_empty() {
         if args.task.is_some() {
             return Err(AppError::usage(
                 "inline mode requires only command args after --",
             ));
         }
+        if args.jobs.is_some() {
+            return Err(AppError::usage(
+                "--jobs only applies to named tasks; inline commands run alone",
+            ));
+        }
 
+        let maybe_cfg = maybe_load_config(&config_path, args.config.is_some())?;
         let (mut resolved, notifications) = resolve_inline_run(
             &args.inline,
-            &config_path,
-            args.config.is_some(),
+            maybe_cfg.as_ref(),
             args.name.as_deref(),
             args.timeout.as_deref(),
             args.retries,
             args.notify_on.as_deref(),
         )?;
+        let hooks = match &maybe_cfg {
+            Some(cfg) => cfg.resolve_global_hooks().map_err(AppError::usage)?,
+            None => GlobalHooks::default(),
+        };
 
-        apply_runtime_env(&mut resolved, &dotenv_vars);
-        return execute_run(resolved, notifications, args.json, true);
+        let opts = RunOptions {
+            notifications,
+            history: history_settings(maybe_cfg.as_ref(), args.no_history),
+            dotenv_vars,
+            as_json: args.json,
+            json_stream: args.json_stream,
+            clean_env: args.clean_env,
+            stdin_file: args.stdin_file.clone(),
+            vars: vars.clone(),
+            tee: tee.clone(),
+            no_retries: retries_disabled(args.no_retries),
+            force: args.force,
+            rerun_of: args.rerun_of.clone(),
+            tags: tags.clone(),
+            replay: replay.clone(),
+            expected_env: args.expected_env.clone(),
+            ..RunOptions::default()
+        };
+
+        resolved.interactive = args.interactive;
+        apply_runtime_env(&mut resolved, &opts).map_err(AppError::usage)?;
+        let name = resolved.name.clone();
+        return run_with_global_hooks(&hooks, &name, RunSource::Inline, &opts, || {
+            execute_run(resolved, &opts, true)
+        });
     }
 
     if args.name.is_some()
         || args.timeout.is_some()
         || args.retries.is_some()
         || args.notify_on.is_some()
+        || args.interactive
     {
         return Err(AppError::usage(
-            "--name, --timeout, --retries, and --notify-on are inline-only flags; use with 'otto run -- <command>'",
+            "--name, --timeout, --retries, --notify-on, and --interactive are inline-only flags; use with 'otto run -- <command>'",
         ));
     }
 
-    let task_name = args
-        .task
-        .ok_or_else(|| AppError::usage("named task mode requires exactly one task name"))?;
-
     let cfg = load_config_classified(&config_path)?;
+    let task_name = match args.task {
+        Some(task_name) => task_name,
+        None if !cfg.default_task.is_empty() => cfg.default_task.clone(),
+        None => {
+            return Err(AppError::usage(
+                "named task mode requires a task name (or default_task in config)",
+            ));
+        }
+    };
+    let task_names = if is_task_pattern(&task_name) {
+        let matched = cfg.match_tasks(&task_name).map_err(AppError::usage)?;
+        if matched.is_empty() {
+            return Err(AppError::usage(format!("no tasks match {task_name:?}")));
+        }
+        if matched.len() > 1 && args.json {
+            return Err(AppError::usage(
+                "--json requires a pattern that matches exactly one task",
+            ));
+        }
+        matched
+    } else {
+        if cfg
+            .tasks
+            .as_ref()
+            .and_then(|tasks| tasks.get(&task_name))
+            .is_some_and(|task| task.internal)
+        {
+            return Err(AppError::usage(format!(
+                "task {task_name:?} is internal and can only run as part of another task"
+            )));
+        }
+        vec![task_name]
+    };
+    if args.jobs.is_some() && !task_names.iter().any(|name| cfg.has_parallel_group(name)) {
+        return Err(AppError::usage(
+            "--jobs only applies to tasks that run a parallel group",
+        ));
+    }
+
     let notifications = cfg
         .resolve_notification_settings()
         .map_err(AppError::usage)?;
+    let hooks = cfg.resolve_global_hooks().map_err(AppError::usage)?;
+    let opts = RunOptions {
+        notifications,
+        history: history_settings(Some(&cfg), args.no_history),
+        dotenv_vars,
+        as_json: args.json,
+        json_stream: args.json_stream,
+        clean_env: args.clean_env,
+        stdin_file: args.stdin_file.clone(),
+        vars,
+        tee,
+        jobs: args.jobs.map(|jobs| Arc::new(JobSlots::new(jobs))),
+        keep_going: args.keep_going,
+        no_retries: retries_disabled(args.no_retries),
+        force: args.force,
+        rerun_of: args.rerun_of.clone(),
+        tags,
+        replay,
+        expected_env: args.expected_env.clone(),
+        ..RunOptions::default()
+    };
 
-    let mut stack = Vec::new();
-    run_named_task(
-        &cfg,
-        &task_name,
-        &notifications,
-        args.json,
-        &dotenv_vars,
-        true,
-        &mut stack,
-    )
-}
+    let mut failures = Vec::new();
+    for task_name in &task_names {
+        let mut stack = Vec::new();
+        let result = run_with_global_hooks(&hooks, task_name, RunSource::Task, &opts, || {
+            run_named_task(&cfg, task_name, &opts, true, &mut stack)
+        });
+        match result {
+            Err(err) if opts.keep_going && !runner::interrupted() => {
+                failures.push(format!("{task_name}: {err}"));
+            }
+            other => other?,
+        }
+    }
 
-fn run_named_task(
-    cfg: &Config,
-    task_name: &str,
-    notifications: &NotificationSettings,
-    as_json: bool,
-    dotenv_vars: &HashMap<String, String>,
-    emit_notifications: bool,
-    stack: &mut Vec<String>,
-) -> Result<(), AppError> {
-    if let Some(index) = stack.iter().position(|name| name == task_name) {
-        let mut cycle = stack[index..].to_vec();
-        cycle.push(task_name.to_string());
-        return Err(AppError::usage(format!(
-            "task dependency cycle: {}",
-            cycle.join(" -> ")
+    if !failures.is_empty() {
+        return Err(AppError::runtime(format!(
+            "{} of {} tasks failed: {}",
+            failures.len(),
+            task_names.len(),
+            failures.join("; ")
         )));
     }
 
-    stack.push(task_name.to_string());
-    let resolved = cfg.resolve_task(task_name).map_err(AppError::usage)?;
-    let result = if resolved.sub_tasks.is_empty() {
-        let mut runnable = resolved;
-        apply_runtime_env(&mut runnable, dotenv_vars);
-        execute_run(runnable, notifications.clone(), as_json, emit_notifications)
-    } else {
-        execute_task_group(
-            cfg,
-            resolved,
-            notifications,
-            as_json,
-            dotenv_vars,
-            emit_notifications,
-            stack,
-        )
-    };
-    stack.pop();
-    result
+    Ok(())
 }
 
-fn execute_task_group(
+fn run_watch(args: WatchArgs) -> Result<(), AppError> {
+    let config_path = args
+        .config
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH));
+    let debounce = humantime::parse_duration(&args.debounce)
+        .map_err(|e| AppError::usage(format!("invalid --debounce: {e}")))?;
+    let dotenv_vars = load_dotenv(Path::new(".env"), false, false)?;
+
+    let cfg = load_config_classified(&config_path)?;
+    let resolved = cfg.resolve_task(&args.task).map_err(AppError::usage)?;
+    let patterns = if resolved.watch.is_empty() {
+        resolved.inputs.clone()
+    } else {
+        resolved.watch.clone()
+    };
+    if patterns.is_empty() {
+        return Err(AppError::usage(format!(
+            "task {:?} has no watch or inputs patterns to watch",
+            args.task
+        )));
+    }
+    let watch_dir = Path::new(&resolved.base_dir).join(&resolved.dir);
+
+    let notifications = cfg
+        .resolve_notification_settings()
+        .map_err(AppError::usage)?;
+    let hooks = cfg.resolve_global_hooks().map_err(AppError::usage)?;
+    let opts = RunOptions {
+        notifications,
+        history: history_settings(Some(&cfg), args.no_history),
+        dotenv_vars,
+        no_retries: retries_disabled(false),
+        ..RunOptions::default()
+    };
+
+    eprintln!(
+        "{} watching {} for task \"{}\"",
+        output::info("i"),
+        output::command(&patterns.join(", ")),
+        args.task
+    );
+
+    let mut stamp = cache::stamp(&watch_dir, &patterns).map_err(AppError::usage)?;
+    loop {
+        runner::clear_interrupt();
+        let restart = thread::scope(|scope| -> Result<bool, AppError> {
+            let mut run = Some(scope.spawn(|| {
+                let mut stack = Vec::new();
+                run_with_global_hooks(&hooks, &args.task, RunSource::Task, &opts, || {
+                    run_named_task(&cfg, &args.task, &opts, true, &mut stack)
+                })
+            }));
+
+            loop {
+                if SHUTDOWN.load(Ordering::SeqCst) {
+                    if let Some(run) = run.take() {
+                        let _ = run.join();
+                    }
+                    return Ok(false);
+                }
+
+                if run.as_ref().is_some_and(|run| run.is_finished())
+                    && let Some(run) = run.take()
+                {
+                    report_watch_run(run.join());
+                }
+
+                thread::sleep(WATCH_POLL_INTERVAL);
+                let next = cache::stamp(&watch_dir, &patterns).map_err(AppError::usage)?;
+                if next == stamp {
+                    continue;
+                }
+                stamp = settle_stamp(&watch_dir, &patterns, next, debounce)?;
+
+                if let Some(run) = run.take() {
+                    if !run.is_finished() {
+                        eprintln!(
+                            "{} change detected, restarting \"{}\"",
+                            output::info("i"),
+                            args.task
+                        );
+                        runner::interrupt();
+                    }
+                    report_watch_run(run.join());
+                }
+                return Ok(true);
+            }
+        })?;
+
+        if !restart {
+            return Err(AppError::interrupted(format!(
+                "watch \"{}\" stopped",
+                args.task
+            )));
+        }
+    }
+}
+
+fn settle_stamp(
+    dir: &Path,
+    patterns: &[String],
+    mut current: String,
+    debounce: Duration,
+) -> Result<String, AppError> {
+    loop {
+        thread::sleep(debounce);
+        let next = cache::stamp(dir, patterns).map_err(AppError::usage)?;
+        if next == current || SHUTDOWN.load(Ordering::SeqCst) {
+            return Ok(next);
+        }
+        current = next;
+    }
+}
+
+fn report_watch_run(result: thread::Result<Result<(), AppError>>) {
+    match result {
+        Ok(Err(err)) if !runner::interrupted() => eprintln!("{err}"),
+        Err(_) => eprintln!("task thread panicked"),
+        _ => {}
+    }
+}
+
+fn run_schedule(args: ScheduleArgs) -> Result<(), AppError> {
+    let config_path = args
+        .config
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH));
+    let dotenv_vars = load_dotenv(Path::new(".env"), false, false)?;
+    let cfg = load_config_classified(&config_path)?;
+
+    let mut schedules: Vec<(String, Schedule)> = Vec::new();
+    if let Some(tasks) = &cfg.tasks {
+        for (name, task) in tasks {
+            if task.schedule.is_empty() {
+                continue;
+            }
+            let schedule = Schedule::parse(&task.schedule)
+                .map_err(|e| AppError::usage(format!("task {name:?} schedule: {e}")))?;
+            schedules.push((name.clone(), schedule));
+        }
+    }
+    schedules.sort_by(|a, b| a.0.cmp(&b.0));
+    if schedules.is_empty() {
+        return Err(AppError::usage("no tasks define a schedule"));
+    }
+
+    let notifications = cfg
+        .resolve_notification_settings()
+        .map_err(AppError::usage)?;
+    let hooks = cfg.resolve_global_hooks().map_err(AppError::usage)?;
+    let opts = RunOptions {
+        notifications,
+        history: history_settings(Some(&cfg), false),
+        dotenv_vars,
+        scheduled: true,
+        no_retries: retries_disabled(false),
+        ..RunOptions::default()
+    };
+
+    eprintln!(
+        "{} scheduling {} task(s), times in UTC",
+        output::info("i"),
+        output::number(&schedules.len().to_string())
+    );
+    for (name, _) in &schedules {
+        let expr = cfg
+            .tasks
+            .as_ref()
+            .and_then(|tasks| tasks.get(name))
+            .map(|task| task.schedule.as_str())
+            .unwrap_or_default();
+        eprintln!("  {} {}", output::bullet(name), output::muted(expr));
+    }
+
+    let running: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+    let mut last_minute = OffsetDateTime::now_utc().unix_timestamp() / 60;
+
+    thread::scope(|scope| {
+        while !SHUTDOWN.load(Ordering::SeqCst) {
+            thread::sleep(SCHEDULE_POLL_INTERVAL);
+            let now = OffsetDateTime::now_utc();
+            let minute = now.unix_timestamp() / 60;
+            if minute == last_minute {
+                continue;
+            }
+            last_minute = minute;
+
+            for (name, schedule) in &schedules {
+                if !schedule.matches(now) {
+                    continue;
+                }
+                let started = running
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .insert(name.clone());
+                if !started {
+                    eprintln!(
+                        "{} schedule skipped \"{name}\": previous run still in progress",
+                        output::warning("warn")
+                    );
+                    continue;
+                }
+
+                let (cfg, hooks, opts, running) = (&cfg, &hooks, &opts, &running);
+                scope.spawn(move || {
+                    let mut stack = Vec::new();
+                    let result =
+                        run_with_global_hooks(hooks, name, RunSource::Schedule, opts, || {
+                            run_named_task(cfg, name, opts, true, &mut stack)
+                        });
+                    if let Err(err) = result
+                        && !runner::interrupted()
+                    {
+                        eprintln!("{err}");
+                    }
+                    running
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .remove(name);
+                });
+            }
+        }
+    });
+
+    Err(AppError::interrupted("schedule stopped"))
+}
+
+#[derive(Debug, Clone, Default)]
+struct RunOptions {
+    notifications: NotificationSettings,
+    history: HistorySettings,
+    dotenv_vars: HashMap<String, String>,
+    as_json: bool,
+    json_stream: bool,
+    clean_env: bool,
+    stdin_file: Option<PathBuf>,
+    vars: HashMap<String, String>,
+    tee: Option<TeeFile>,
+    cancel: Option<CancelToken>,
+    skipped: Option<Arc<Mutex<Vec<String>>>>,
+    outputs: Arc<Mutex<HashMap<String, String>>>,
+    prefix_output: bool,
+    group_output: bool,
+    jobs: Option<Arc<JobSlots>>,
+    scheduled: bool,
+    keep_going: bool,
+    no_retries: bool,
+    force: bool,
+    rerun_of: Option<String>,
+    tags: Vec<String>,
+    replay: Option<ReplaySettings>,
+    expected_env: Option<ExpectedEnv>,
+    last_status: Arc<Mutex<Option<RunStatus>>>,
+}
+
+impl RunOptions {
+    fn note_status(&self, status: RunStatus) {
+        *self
+            .last_status
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(status);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct ExpectedEnv {
+    run_id: String,
+    names: Vec<String>,
+    hashes: BTreeMap<String, String>,
+    strict: bool,
+}
+
+#[derive(Debug)]
+struct JobSlots {
+    free: Mutex<usize>,
+    released: Condvar,
+}
+
+struct JobSlot(Arc<JobSlots>);
+
+impl JobSlots {
+    fn new(jobs: usize) -> Self {
+        Self {
+            free: Mutex::new(jobs),
+            released: Condvar::new(),
+        }
+    }
+
+    fn acquire(self: &Arc<Self>) -> JobSlot {
+        let mut free = self
+            .free
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        while *free == 0 {
+            free = self
+                .released
+                .wait(free)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+        *free -= 1;
+        JobSlot(Arc::clone(self))
+    }
+}
+
+impl Drop for JobSlot {
+    fn drop(&mut self) {
+        *self
+            .0
+            .free
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) += 1;
+        self.0.released.notify_one();
+    }
+}
+
+fn is_task_pattern(name: &str) -> bool {
+    name.contains(['*', '?', '['])
+}
+
+fn run_with_global_hooks(
+    hooks: &GlobalHooks,
+    task_name: &str,
+    source: RunSource,
+    opts: &RunOptions,
+    run: impl FnOnce() -> Result<(), AppError>,
+) -> Result<(), AppError> {
+    let mut env: HashMap<String, String> = opts
+        .dotenv_vars
+        .iter()
+        .filter(|(key, _)| std::env::var_os(key).is_none())
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    env.insert("OTTO_TASK_NAME".to_string(), task_name.to_string());
+    env.insert(
+        "OTTO_TASK_SOURCE".to_string(),
+        source_to_str(source).to_string(),
+    );
+
+    if let Some(hook) = &hooks.pre_run {
+        let request = global_hook_request(
+            "pre_run",
+            hook,
+            hooks,
+            &env,
+            opts.as_json || opts.json_stream,
+        );
+        runner::execute(&request)
+            .map_err(|err| AppError::runtime(format!("pre_run hook: {}", err.message)))?;
+    }
+
+    let wall = Instant::now();
+    opts.last_status
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .take();
+    let result = run();
+
+    if let Some(hook) = &hooks.post_run {
+        let recorded = opts
+            .last_status
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take();
+        let status = recorded.unwrap_or(match &result {
+            Ok(()) => RunStatus::Success,
+            Err(err) if err.code() == 130 => RunStatus::Interrupted,
+            Err(_) => RunStatus::Failed,
+        });
+        env.insert(
+            "OTTO_RUN_STATUS".to_string(),
+            status_to_str(status).to_string(),
+        );
+        env.insert(
+            "OTTO_RUN_DURATION_MS".to_string(),
+            wall.elapsed().as_millis().to_string(),
+        );
+
+        let request = global_hook_request(
+            "post_run",
+            hook,
+            hooks,
+            &env,
+            opts.as_json || opts.json_stream,
+        );
+        if let Err(err) = runner::execute(&request) {
+            eprintln!("{} post_run hook: {}", output::warning("warn"), err.message);
+        }
+    }
+
+    result
+}
+
+fn global_hook_request(
+    label: &str,
+    hook: &ResolvedHook,
+    hooks: &GlobalHooks,
+    env: &HashMap<String, String>,
+    as_json: bool,
+) -> Request {
+    Request {
+        name: label.to_string(),
+        command_preview: if hook.use_shell {
+            hook.shell.clone()
+        } else {
+            hook.exec.join(" ")
+        },
+        use_shell: hook.use_shell,
+        exec: hook.exec.clone(),
+        shell: hook.shell.clone(),
+        shell_kind: hooks.shell_kind,
+        dir: hooks.dir.clone(),
+        env: env.clone(),
+        inherit_env: true,
+        interactive: false,
+        stdin: StdinMode::Null,
+        pty: false,
+        combine_output: false,
+        nice: None,
+        io_priority: runner::IoPriority::Normal,
+        timeout: hook.timeout,
+        deadline: Duration::ZERO,
+        grace_period: config::DEFAULT_GRACE_PERIOD,
+        retries: 0,
+        retry_backoff: Duration::ZERO,
+        retry_strategy: runner::RetryStrategy::default(),
+        retry_jitter: Duration::ZERO,
+        retry_max_backoff: Duration::ZERO,
+        retry_on_exit_codes: Vec::new(),
+        retry_on_timeout: true,
+        retry_when_output_matches: None,
+        stream_output: !as_json,
+        output_prefix: None,
+        capture: None,
+        max_captured_output: None,
+        tee: None,
+        log_path: None,
+        events: None,
+        cancel: None,
+        container: None,
+    }
+}
+
+fn run_named_task(
+    cfg: &Config,
+    task_name: &str,
+    opts: &RunOptions,
+    emit_notifications: bool,
+    stack: &mut Vec<String>,
+) -> Result<(), AppError> {
+    if let Some(index) = stack.iter().position(|name| name == task_name) {
+        let mut cycle = stack[index..].to_vec();
+        cycle.push(task_name.to_string());
+        return Err(AppError::usage(format!(
+            "task dependency cycle: {}",
+            cycle.join(" -> ")
+        )));
+    }
+
+    stack.push(task_name.to_string());
+    let mut resolved = cfg.resolve_task(task_name).map_err(AppError::usage)?;
+    if opts.scheduled {
+        resolved.source = RunSource::Schedule;
+    }
+    let result = if !resolved.platforms.is_empty()
+        && !resolved
+            .platforms
+            .iter()
+            .any(|platform| platform == std::env::consts::OS)
+    {
+        let reason = format!("not supported on {}", std::env::consts::OS);
+        let git = git_context(&resolved);
+        record_skipped(resolved, opts, &reason, git)
+    } else if resolved.sub_tasks.is_empty() {
+        let mut runnable = resolved;
+        apply_runtime_env(&mut runnable, opts)
+            .map_err(AppError::usage)
+            .and_then(|()| execute_run(runnable, opts, emit_notifications))
+    } else {
+        execute_task_group(cfg, resolved, opts, emit_notifications, stack)
+    };
+    stack.pop();
+    result
+}
+
+fn execute_task_group(
     cfg: &Config,
     resolved: ResolvedTask,
-    notifications: &NotificationSettings,
-    as_json: bool,
-    dotenv_vars: &HashMap<String, String>,
+    opts: &RunOptions,
     emit_notifications: bool,
     stack: &mut Vec<String>,
 ) -> Result<(), AppError> {
-    if as_json {
+    if opts.as_json || opts.json_stream {
         return Err(AppError::usage(
-            "--json is not supported for composed tasks yet",
+            "--json and --json-stream are not supported for composed tasks yet",
         ));
     }
 
     let started_at = OffsetDateTime::now_utc();
     let wall = Instant::now();
+    let git = git_context(&resolved);
     let mut failures: Vec<String> = Vec::new();
-
-    if resolved.parallel {
-        let mut handles = Vec::with_capacity(resolved.sub_tasks.len());
-        for child in &resolved.sub_tasks {
-            let cfg_child = cfg.clone();
-            let notifications_child = notifications.clone();
-            let dotenv_child = dotenv_vars.clone();
-            let mut child_stack = stack.clone();
-            let child_name = child.clone();
-            handles.push(thread::spawn(move || {
-                run_named_task(
-                    &cfg_child,
-                    &child_name,
-                    &notifications_child,
-                    false,
-                    &dotenv_child,
-                    false,
-                    &mut child_stack,
-                )
-                .map_err(|err| format!("{child_name}: {err}"))
-            }));
+    let skipped = Arc::new(Mutex::new(Vec::new()));
+    let group_cancel = if resolved.timeout.is_zero() {
+        opts.cancel.clone()
+    } else {
+        Some(
+            opts.cancel
+                .as_ref()
+                .map_or_else(CancelToken::new, CancelToken::child),
+        )
+    };
+    let (timer_done, timer) = match &group_cancel {
+        Some(cancel) if !resolved.timeout.is_zero() => {
+            let (done, wait) = mpsc::channel::<()>();
+            let cancel = cancel.clone();
+            let timeout = resolved.timeout;
+            let reason = format!(
+                "after group \"{}\" timed out after {}",
+                resolved.name,
+                output::format_duration_ms(timeout.as_millis() as i64)
+            );
+            let timer = thread::spawn(move || {
+                let fired = wait.recv_timeout(timeout) == Err(mpsc::RecvTimeoutError::Timeout);
+                if fired {
+                    cancel.time_out(reason);
+                }
+                fired
+            });
+            (Some(done), Some(timer))
         }
+        _ => (None, None),
+    };
+    let child_opts = RunOptions {
+        as_json: false,
+        rerun_of: None,
+        skipped: Some(skipped.clone()),
+        cancel: group_cancel,
+        ..opts.clone()
+    };
 
-        for handle in handles {
-            match handle.join() {
-                Ok(Ok(())) => {}
-                Ok(Err(err)) => failures.push(err),
-                Err(_) => failures.push("task thread panicked".to_string()),
-            }
-        }
+    if resolved.parallel {
+        let cancel = if resolved.fail_fast {
+            Some(
+                child_opts
+                    .cancel
+                    .as_ref()
+                    .map_or_else(CancelToken::new, CancelToken::child),
+            )
+        } else {
+            child_opts.cancel.clone()
+        };
+        let group_output = resolved.grouped_output || opts.group_output;
+        let parallel_opts = RunOptions {
+            prefix_output: !group_output,
+            group_output,
+            cancel,
+            ..child_opts.clone()
+        };
+        failures = run_parallel(
+            cfg,
+            &resolved.name,
+            &resolved.sub_tasks,
+            &parallel_opts,
+            stack,
+            resolved.max_parallel,
+            resolved.fail_fast,
+        );
     } else {
-        for child in &resolved.sub_tasks {
-            if let Err(err) =
-                run_named_task(cfg, child, notifications, false, dotenv_vars, false, stack)
+        for (idx, child) in resolved.sub_tasks.iter().enumerate() {
+            if runner::interrupted()
+                || child_opts
+                    .cancel
+                    .as_ref()
+                    .is_some_and(CancelToken::is_cancelled)
             {
-                failures.push(format!("{child}: {err}"));
                 break;
             }
+            if let Err(err) = run_task_ref(cfg, &resolved.name, idx, child, &child_opts, stack) {
+                failures.push(format!("{}: {err}", child.label(&resolved.name, idx)));
+                let child_continues = match child {
+                    TaskRef::Name(name) => cfg
+                        .tasks
+                        .as_ref()
+                        .and_then(|tasks| tasks.get(name))
+                        .is_some_and(|task| task.continue_on_error),
+                    TaskRef::Inline(step) => step.continue_on_error,
+                };
+                if !resolved.continue_on_error && !child_continues && !opts.keep_going {
+                    break;
+                }
+            }
         }
     }
 
-    let status = if failures.is_empty() {
+    drop(timer_done);
+    let timed_out = timer.is_some_and(|timer| timer.join().unwrap_or(false));
+    if timed_out {
+        failures.insert(
+            0,
+            format!(
+                "timed out after {}",
+                output::format_duration_ms(resolved.timeout.as_millis() as i64)
+            ),
+        );
+    }
+
+    let interrupted = runner::interrupted();
+    let status = if interrupted {
+        RunStatus::Interrupted
+    } else if timed_out {
+        RunStatus::TimedOut
+    } else if failures.is_empty() {
         RunStatus::Success
     } else {
         RunStatus::Failed
     };
-    let exit_code = if failures.is_empty() { 0 } else { 1 };
+    let exit_code = if interrupted {
+        130
+    } else if timed_out {
+        124
+    } else if failures.is_empty() {
+        0
+    } else {
+        1
+    };
     let stderr_tail = if failures.is_empty() {
         None
     } else {
@@ -398,38 +1552,46 @@ fn execute_task_group(
         duration_ms: wall.elapsed().as_millis() as i64,
         exit_code,
         status,
+        stdout_tail: None,
         stderr_tail: stderr_tail.clone(),
+        signal: None,
+        skip_reason: None,
+        forced: opts.force,
+        rerun_of: opts.rerun_of.clone(),
+        tags: opts.tags.clone(),
+        env: Vec::new(),
+        env_hashes: BTreeMap::new(),
+        replay: opts.replay.clone(),
+        args: Vec::new(),
+        git,
+        host: host::name(),
+        user: host::user(),
+        cwd: host::cwd(),
+        log_path: None,
+        attempts: Vec::new(),
     };
 
-    let store = Store::new(DEFAULT_PATH);
-    store
-        .append(&record)
-        .map_err(|err| AppError::internal(err.to_string()))?;
-
-    if emit_notifications && should_notify(&resolved.notify_on, status) {
-        let manager = notify::Manager {
-            desktop_enabled: notifications.desktop_enabled,
-            webhook_url: notifications.webhook_url.clone(),
-            webhook_timeout: notifications.webhook_timeout,
-        };
+    if resolved.history {
+        append_history(&opts.history, &record)?;
+    }
+    opts.note_status(record.status);
 
-        let event = notify::Event {
-            name: record.name.clone(),
-            source: source_to_str(record.source).to_string(),
-            status: status_to_str(record.status).to_string(),
-            exit_code: record.exit_code,
-            duration: Duration::from_millis(record.duration_ms as u64),
-            started_at: record.started_at,
-            command_preview: record.command_preview.clone(),
-            stderr_tail: record.stderr_tail.clone(),
-        };
+    send_metrics(opts, &record);
 
-        if let Err(err) = manager.notify(&event) {
-            eprintln!(
-                "{} failed to send notification: {err}",
-                output::warning("warn")
-            );
-        }
+    if emit_notifications {
+        send_notification(
+            &resolved.notify_on,
+            resolved.notify_min_duration,
+            opts,
+            &record,
+        );
+    }
+
+    if interrupted {
+        return Err(AppError::interrupted(format!(
+            "run \"{}\" interrupted",
+            resolved.name
+        )));
     }
 
     if failures.is_empty() {
@@ -438,13 +1600,22 @@ fn execute_task_group(
         } else {
             "sequentially"
         };
+        let skipped = skipped
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let skipped = if skipped.is_empty() {
+            String::new()
+        } else {
+            format!(", {} skipped: {}", skipped.len(), skipped.join(", "))
+        };
         println!(
-            "{} run \"{}\" finished in {} ({} sub-tasks {})",
+            "{} run \"{}\" finished in {} ({} sub-tasks {}{})",
             output::success("ok"),
             resolved.name,
             output::number(&output::format_duration_ms(record.duration_ms)),
             resolved.sub_tasks.len(),
-            mode
+            mode,
+            skipped
         );
         Ok(())
     } else {
@@ -452,22 +1623,112 @@ fn execute_task_group(
     }
 }
 
+fn run_task_ref(
+    cfg: &Config,
+    parent: &str,
+    idx: usize,
+    entry: &TaskRef,
+    opts: &RunOptions,
+    stack: &mut Vec<String>,
+) -> Result<(), AppError> {
+    match entry {
+        TaskRef::Name(name) => run_named_task(cfg, name, opts, false, stack),
+        TaskRef::Inline(step) => {
+            let mut resolved = cfg
+                .resolve_inline_ref(&entry.label(parent, idx), step)
+                .map_err(AppError::usage)?;
+            apply_runtime_env(&mut resolved, opts).map_err(AppError::usage)?;
+            execute_run(resolved, opts, false)
+        }
+    }
+}
+
+fn run_parallel(
+    cfg: &Config,
+    parent: &str,
+    sub_tasks: &[TaskRef],
+    opts: &RunOptions,
+    stack: &[String],
+    max_parallel: usize,
+    fail_fast: bool,
+) -> Vec<String> {
+    let workers = if max_parallel == 0 {
+        sub_tasks.len()
+    } else {
+        max_parallel.min(sub_tasks.len())
+    };
+    let next = AtomicUsize::new(0);
+    let failures: Mutex<Vec<(usize, String)>> = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    while !runner::interrupted()
+                        && !opts.cancel.as_ref().is_some_and(CancelToken::is_cancelled)
+                    {
+                        let idx = next.fetch_add(1, Ordering::SeqCst);
+                        let Some(child) = sub_tasks.get(idx) else {
+                            break;
+                        };
+
+                        let mut child_stack = stack.to_vec();
+                        if let Err(err) =
+                            run_task_ref(cfg, parent, idx, child, opts, &mut child_stack)
+                        {
+                            failures
+                                .lock()
+                                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                                .push((idx, format!("{}: {err}", child.label(parent, idx))));
+                            if fail_fast && let Some(cancel) = &opts.cancel {
+                                cancel.cancel();
+                            }
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            if handle.join().is_err() {
+                failures
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .push((usize::MAX, "task thread panicked".to_string()));
+            }
+        }
+    });
+
+    let mut failures = failures
+        .into_inner()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    failures.sort_by_key(|(idx, _)| *idx);
+    failures.into_iter().map(|(_, err)| err).collect()
+}
+
 fn resolve_inline_run(
     inline: &[String],
-    config_path: &Path,
-    explicit_config: bool,
+    maybe_cfg: Option<&Config>,
     inline_name: Option<&str>,
     inline_timeout: Option<&str>,
     inline_retries: Option<i32>,
     inline_notify_on: Option<&str>,
 ) -> Result<(ResolvedTask, NotificationSettings), AppError> {
-    let maybe_cfg = maybe_load_config_for_inline(config_path, explicit_config)?;
-
     let mut defaults = Defaults::default();
     let mut notifications = NotificationSettings {
         desktop_enabled: true,
         webhook_url: String::new(),
         webhook_timeout: Duration::from_secs(5),
+        statsd: String::new(),
+        pushgateway_url: String::new(),
+        metrics_prefix: "otto".to_string(),
+        slack: None,
+        discord: None,
+        teams: None,
+        telegram: None,
+        email: None,
+        ntfy: None,
+        pushover: None,
     };
 
     if let Some(cfg) = maybe_cfg {
@@ -477,7 +1738,7 @@ fn resolve_inline_run(
             .map_err(AppError::usage)?;
     }
 
-    let resolved = config::resolve_inline(
+    let mut resolved = config::resolve_inline(
         inline,
         inline_name.unwrap_or_default(),
         inline_timeout.unwrap_or_default(),
@@ -486,11 +1747,14 @@ fn resolve_inline_run(
         &defaults,
     )
     .map_err(AppError::usage)?;
+    if let Some(cfg) = maybe_cfg {
+        resolved.config_dir = cfg.config_dir.display().to_string();
+    }
 
     Ok((resolved, notifications))
 }
 
-fn maybe_load_config_for_inline(path: &Path, explicit: bool) -> Result<Option<Config>, AppError> {
+fn maybe_load_config(path: &Path, explicit: bool) -> Result<Option<Config>, AppError> {
     if !path.exists() {
         if explicit {
             return Err(AppError::usage(format!(
@@ -507,78 +1771,286 @@ fn maybe_load_config_for_inline(path: &Path, explicit: bool) -> Result<Option<Co
 
 fn execute_run(
     resolved: ResolvedTask,
-    notifications: NotificationSettings,
-    as_json: bool,
+    opts: &RunOptions,
     emit_notifications: bool,
 ) -> Result<(), AppError> {
+    if let Some(expected) = &opts.expected_env {
+        check_replay_env(expected, &resolved.env)?;
+    }
+    let as_json = opts.as_json;
+    let task_dir = if resolved.dir.is_empty() {
+        PathBuf::from(".")
+    } else {
+        PathBuf::from(&resolved.dir)
+    };
+    let cache_store = Cache::new(Path::new(&resolved.config_dir).join(cache::DEFAULT_DIR));
+    let fingerprint = if resolved.inputs.is_empty() {
+        None
+    } else {
+        Some(
+            cache::fingerprint(&task_dir, &resolved.inputs, &fingerprint_salt(&resolved))
+                .map_err(AppError::usage)?,
+        )
+    };
+
+    if !opts.force
+        && let Some(current) = &fingerprint
+        && cache_store.load(&resolved.name).as_ref() == Some(current)
+        && cache::outputs_exist(&task_dir, &resolved.outputs).map_err(AppError::usage)?
+    {
+        publish_outputs(&resolved, &task_dir, opts);
+        let git = git::context(&task_dir);
+        return record_skipped(resolved, opts, "inputs unchanged", git);
+    }
+
+    let _job_slot = opts.jobs.as_ref().map(JobSlots::acquire);
+    let _mutex_guard = if resolved.mutex.is_empty() {
+        None
+    } else {
+        let lock_dir = Path::new(&resolved.config_dir).join(lock::DEFAULT_DIR);
+        Some(
+            lock::acquire(&lock_dir, &resolved.mutex, || {
+                eprintln!(
+                    "{} run \"{}\" waiting for mutex {}",
+                    output::info("i"),
+                    resolved.name,
+                    output::command(&resolved.mutex)
+                );
+            })
+            .map_err(AppError::internal)?,
+        )
+    };
+
+    let record_id = if resolved.run_id.is_empty() {
+        new_record_id()
+    } else {
+        resolved.run_id.clone()
+    };
+    let log_path = if resolved.history && opts.history.enabled && !resolved.interactive {
+        let log_dir = std::path::absolute(Store::new(&opts.history.path).log_dir())
+            .map_err(|e| AppError::internal(format!("resolve log directory: {e}")))?;
+        fs::create_dir_all(&log_dir)
+            .map_err(|e| AppError::internal(format!("create log directory: {e}")))?;
+        Some(log_dir.join(format!("{record_id}.log")))
+    } else {
+        None
+    };
+
+    let tmpdir = run_tmpdir(&record_id);
+    fs::create_dir_all(&tmpdir)
+        .map_err(|e| AppError::internal(format!("create temp directory: {e}")))?;
+    let mut tmpdir_guard = TempDirGuard {
+        path: tmpdir.clone(),
+        keep: false,
+    };
+
+    let git = git::context(&task_dir);
+    let started_at = OffsetDateTime::now_utc();
+    if opts.json_stream {
+        print_stream_event(&StreamEvent::RunStarted {
+            id: &record_id,
+            name: &resolved.name,
+            source: source_to_str(resolved.source),
+            command_preview: &resolved.command_preview,
+            started_at,
+        })
+        .map_err(|e| AppError::internal(format!("encode json: {e}")))?;
+    }
+
+    let mut env = resolved.env.clone();
+    env.extend(run_context_env(&resolved, &record_id, started_at));
+    env.insert("OTTO_TMPDIR".to_string(), tmpdir.display().to_string());
+
     let request = Request {
         name: resolved.name.clone(),
         command_preview: resolved.command_preview.clone(),
         use_shell: resolved.use_shell,
         exec: resolved.exec.clone(),
         shell: resolved.shell.clone(),
+        shell_kind: resolved.shell_kind,
         dir: resolved.dir.clone(),
-        env: resolved.env.clone(),
+        env,
+        inherit_env: resolved.inherit_env,
+        interactive: resolved.interactive,
+        stdin: resolved.stdin.clone(),
+        pty: resolved.pty,
+        combine_output: resolved.combine_output,
+        nice: resolved.nice,
+        io_priority: resolved.io_priority,
         timeout: resolved.timeout,
+        deadline: resolved.deadline,
+        grace_period: resolved.grace_period,
         retries: resolved.retries,
         retry_backoff: resolved.retry_backoff,
-        stream_output: !as_json,
+        retry_strategy: resolved.retry_strategy,
+        retry_jitter: resolved.retry_jitter,
+        retry_max_backoff: resolved.retry_max_backoff,
+        retry_on_exit_codes: resolved.retry_on_exit_codes.clone(),
+        retry_on_timeout: resolved.retry_on_timeout,
+        retry_when_output_matches: resolved.retry_when_output_matches.clone(),
+        stream_output: !as_json && !opts.json_stream && !resolved.quiet,
+        output_prefix: opts
+            .prefix_output
+            .then(|| format!("{} ", output::tag(&resolved.name))),
+        capture: opts.group_output.then(OutputBuffer::new),
+        max_captured_output: resolved.max_captured_output,
+        tee: opts.tee.clone(),
+        log_path: log_path.clone(),
+        events: opts.json_stream.then(|| stream_sink(record_id.clone())),
+        cancel: opts.cancel.clone(),
+        container: resolved
+            .container
+            .is_some()
+            .then(|| container_name(&record_id)),
     };
 
-    let execution = runner::execute(&request);
-    let (result, run_err) = match execution {
-        Ok(ok) => (ok, None),
-        Err(err) => (err.result, Some(err.message)),
+    let spinner = if (as_json || resolved.quiet) && !opts.json_stream && !opts.prefix_output {
+        output::Spinner::start(&resolved.name)
+    } else {
+        None
     };
 
-    let record = RunRecord {
-        id: new_record_id(),
-        name: resolved.name,
+    let ready = match &resolved.wait_for {
+        Some(wait) => wait_until_ready(wait, &request),
+        None => Ok(()),
+    };
+    let execution =
+        match ready.and_then(|()| run_hook("before", resolved.before.as_ref(), &request)) {
+            Ok(()) if resolved.steps.is_empty() => match resolved.restart {
+                Some(policy) => supervise(&request, policy),
+                None => runner::execute(&request),
+            },
+            Ok(()) => run_steps(&resolved.steps, &request),
+            Err(failure) => Err(failure),
+        };
+
+    if execution.is_err()
+        && let Err(err) = run_hook("on_failure", resolved.on_failure.as_ref(), &request)
+    {
+        eprintln!("{} {}", output::warning("warn"), err.message);
+    }
+
+    if let Err(err) = run_hook("after", resolved.after.as_ref(), &request) {
+        eprintln!("{} {}", output::warning("warn"), err.message);
+    }
+    drop(spinner);
+
+    let keep_tmpdir = match resolved.keep_tmpdir.as_str() {
+        "always" => true,
+        "on-failure" => execution.is_err(),
+        _ => false,
+    };
+    if keep_tmpdir {
+        tmpdir_guard.keep = true;
+        eprintln!(
+            "{} run \"{}\" kept temp directory {}",
+            output::info("i"),
+            resolved.name,
+            output::command(&tmpdir.display().to_string())
+        );
+    }
+    drop(tmpdir_guard);
+
+    if execution.is_ok() {
+        publish_outputs(&resolved, &task_dir, opts);
+    }
+
+    let (result, run_err) = match execution {
+        Ok(ok) => (ok, None),
+        Err(err) => (*err.result, Some(err.message)),
+    };
+
+    let notify_on = resolved.notify_on.clone();
+    let notify_min_duration = resolved.notify_min_duration;
+    let record_history = resolved.history;
+    let mut env_names: Vec<String> = resolved.env.keys().cloned().collect();
+    env_names.sort();
+    let env_hashes: BTreeMap<String, String> = if opts.history.hash_env {
+        resolved
+            .env
+            .iter()
+            .map(|(name, value)| (name.clone(), env_hash(value)))
+            .collect()
+    } else {
+        BTreeMap::new()
+    };
+    let record = RunRecord {
+        id: record_id,
+        name: resolved.name,
         source: resolved.source,
         command_preview: resolved.command_preview,
         started_at: result.started_at,
         duration_ms: result.duration.as_millis() as i64,
         exit_code: result.exit_code,
         status: result.status,
+        stdout_tail: result.stdout_tail,
         stderr_tail: result.stderr_tail,
+        signal: result.signal,
+        skip_reason: None,
+        forced: opts.force,
+        rerun_of: opts.rerun_of.clone(),
+        tags: opts.tags.clone(),
+        env: env_names,
+        env_hashes,
+        replay: opts.replay.clone(),
+        args: if resolved.source == RunSource::Inline {
+            resolved.exec
+        } else {
+            Vec::new()
+        },
+        git,
+        host: host::name(),
+        user: host::user(),
+        cwd: host::cwd(),
+        log_path: log_path.map(|path| path.display().to_string()),
+        attempts: result.attempts,
     };
 
-    let store = Store::new(DEFAULT_PATH);
-    store
-        .append(&record)
-        .map_err(|err| AppError::internal(err.to_string()))?;
-
-    if emit_notifications && should_notify(&resolved.notify_on, record.status) {
-        let manager = notify::Manager {
-            desktop_enabled: notifications.desktop_enabled,
-            webhook_url: notifications.webhook_url,
-            webhook_timeout: notifications.webhook_timeout,
-        };
+    if let Some(capture) = &request.capture {
+        print_output_block(&record, &capture.take());
+    }
 
-        let event = notify::Event {
-            name: record.name.clone(),
-            source: source_to_str(record.source).to_string(),
-            status: status_to_str(record.status).to_string(),
-            exit_code: record.exit_code,
-            duration: result.duration,
-            started_at: record.started_at,
-            command_preview: record.command_preview.clone(),
-            stderr_tail: record.stderr_tail.clone(),
-        };
+    if record_history {
+        append_history(&opts.history, &record)?;
+    }
+    opts.note_status(record.status);
 
-        if let Err(err) = manager.notify(&event) {
-            eprintln!(
-                "{} failed to send notification: {err}",
-                output::warning("warn")
-            );
-        }
+    send_metrics(opts, &record);
+
+    if emit_notifications {
+        send_notification(&notify_on, notify_min_duration, opts, &record);
+    }
+
+    if run_err.is_none()
+        && let Some(current) = &fingerprint
+        && let Err(err) = cache_store.store(&record.name, current)
+    {
+        eprintln!("{} {err}", output::warning("warn"));
     }
 
     if let Some(run_err) = run_err {
+        if resolved.quiet && !as_json && !opts.json_stream {
+            for tail in [&record.stdout_tail, &record.stderr_tail]
+                .into_iter()
+                .flatten()
+            {
+                eprintln!("{tail}");
+            }
+        }
         if as_json {
             print_run_json(&record, Some(run_err.clone()))
                 .map_err(|e| AppError::internal(format!("encode json: {e}")))?;
         }
+        if opts.json_stream {
+            print_stream_event(&StreamEvent::RunFinished(Box::new(run_json_payload(
+                &record,
+                Some(&run_err),
+            ))))
+            .map_err(|e| AppError::internal(format!("encode json: {e}")))?;
+        }
+        if record.status == RunStatus::Interrupted {
+            return Err(AppError::interrupted(run_err));
+        }
         return Err(AppError::runtime(run_err));
     }
 
@@ -588,6 +2060,17 @@ fn execute_run(
         return Ok(());
     }
 
+    if opts.json_stream {
+        return print_stream_event(&StreamEvent::RunFinished(Box::new(run_json_payload(
+            &record, None,
+        ))))
+        .map_err(|e| AppError::internal(format!("encode json: {e}")));
+    }
+
+    if opts.group_output {
+        return Ok(());
+    }
+
     println!(
         "{} run \"{}\" finished in {}",
         output::success("ok"),
@@ -598,6 +2081,392 @@ fn execute_run(
     Ok(())
 }
 
+fn print_output_block(record: &RunRecord, captured: &[u8]) {
+    let status = match record.status {
+        RunStatus::Success => output::success("ok"),
+        RunStatus::Interrupted => output::warning("interrupted"),
+        RunStatus::TimedOut => output::failure("timed out"),
+        _ => output::failure("failed"),
+    };
+
+    let mut stdout = io::stdout().lock();
+    let _ = writeln!(
+        stdout,
+        "{} {status} in {}",
+        output::tag(&record.name),
+        output::number(&output::format_duration_ms(record.duration_ms))
+    );
+    let _ = stdout.write_all(captured);
+    if !captured.is_empty() && !captured.ends_with(b"\n") {
+        let _ = writeln!(stdout);
+    }
+    let _ = stdout.flush();
+}
+
+fn publish_outputs(resolved: &ResolvedTask, task_dir: &Path, opts: &RunOptions) {
+    if resolved.named_outputs.is_empty() {
+        return;
+    }
+
+    let dir = std::env::current_dir().unwrap_or_default().join(task_dir);
+    let mut outputs = opts
+        .outputs
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    for (key, path) in &resolved.named_outputs {
+        let path = dir.join(path).components().collect::<PathBuf>();
+        outputs.insert(
+            format!("needs.{}.outputs.{key}", resolved.name),
+            path.display().to_string(),
+        );
+    }
+}
+
+fn run_context_env(
+    resolved: &ResolvedTask,
+    record_id: &str,
+    started_at: OffsetDateTime,
+) -> HashMap<String, String> {
+    let config_dir = std::env::current_dir()
+        .unwrap_or_default()
+        .join(&resolved.config_dir)
+        .components()
+        .collect::<PathBuf>();
+    HashMap::from([
+        ("OTTO_TASK_NAME".to_string(), resolved.name.clone()),
+        ("OTTO_RUN_ID".to_string(), record_id.to_string()),
+        (
+            "OTTO_CONFIG_DIR".to_string(),
+            config_dir.display().to_string(),
+        ),
+        (
+            "OTTO_STARTED_AT".to_string(),
+            started_at
+                .format(&time::format_description::well_known::Rfc3339)
+                .unwrap_or_default(),
+        ),
+    ])
+}
+
+fn fingerprint_salt(resolved: &ResolvedTask) -> String {
+    let mut env: Vec<String> = resolved
+        .env
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect();
+    env.sort();
+    let task_dir = canonical_dir(&resolved.dir);
+    let config_dir = canonical_dir(&resolved.config_dir);
+    let mut salt = format!(
+        "{}\n{}\n{}",
+        resolved.command_preview,
+        task_dir
+            .strip_prefix(&config_dir)
+            .unwrap_or(&task_dir)
+            .display(),
+        env.join("\n")
+    );
+    if let Some(container) = &resolved.container {
+        salt.push_str("\ncontainer ");
+        salt.push_str(&container.image);
+        for volume in &container.volumes {
+            salt.push_str("\nvolume ");
+            salt.push_str(volume);
+        }
+    }
+    salt
+}
+
+fn canonical_dir(dir: &str) -> PathBuf {
+    let dir = if dir.is_empty() { "." } else { dir };
+    fs::canonicalize(dir).unwrap_or_else(|_| PathBuf::from(dir))
+}
+
+fn record_skipped(
+    resolved: ResolvedTask,
+    opts: &RunOptions,
+    reason: &str,
+    git: Option<GitContext>,
+) -> Result<(), AppError> {
+    let as_json = opts.as_json;
+    let record_history = resolved.history;
+    let record = RunRecord {
+        id: new_record_id(),
+        name: resolved.name,
+        source: resolved.source,
+        command_preview: resolved.command_preview,
+        started_at: OffsetDateTime::now_utc(),
+        duration_ms: 0,
+        exit_code: 0,
+        status: RunStatus::Skipped,
+        stdout_tail: None,
+        stderr_tail: None,
+        signal: None,
+        skip_reason: Some(reason.to_string()),
+        forced: false,
+        rerun_of: opts.rerun_of.clone(),
+        tags: opts.tags.clone(),
+        env: Vec::new(),
+        env_hashes: BTreeMap::new(),
+        replay: opts.replay.clone(),
+        args: Vec::new(),
+        git,
+        host: host::name(),
+        user: host::user(),
+        cwd: host::cwd(),
+        log_path: None,
+        attempts: Vec::new(),
+    };
+
+    if record_history {
+        append_history(&opts.history, &record)?;
+    }
+    opts.note_status(record.status);
+
+    if let Some(skipped) = &opts.skipped {
+        skipped
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(record.name.clone());
+    }
+
+    if as_json {
+        return print_run_json(&record, None)
+            .map_err(|e| AppError::internal(format!("encode json: {e}")));
+    }
+
+    if opts.json_stream {
+        return print_stream_event(&StreamEvent::RunFinished(Box::new(run_json_payload(
+            &record, None,
+        ))))
+        .map_err(|e| AppError::internal(format!("encode json: {e}")));
+    }
+
+    println!(
+        "{} run \"{}\" skipped ({reason})",
+        output::muted("skip"),
+        record.name
+    );
+    Ok(())
+}
+
+fn run_hook(
+    label: &str,
+    hook: Option<&ResolvedHook>,
+    base: &Request,
+) -> Result<(), runner::RunFailure> {
+    let Some(hook) = hook else {
+        return Ok(());
+    };
+
+    let request = Request {
+        name: format!("{}:{label}", base.name),
+        command_preview: if hook.use_shell {
+            hook.shell.clone()
+        } else {
+            hook.exec.join(" ")
+        },
+        use_shell: hook.use_shell,
+        exec: hook.exec.clone(),
+        shell: hook.shell.clone(),
+        stdin: StdinMode::Null,
+        timeout: hook.timeout,
+        deadline: Duration::ZERO,
+        retries: 0,
+        events: None,
+        container: None,
+        ..base.clone()
+    };
+
+    runner::execute(&request)
+        .map(|_| ())
+        .map_err(|mut failure| {
+            failure.message = format!("{label} hook: {}", failure.message);
+            failure
+        })
+}
+
+fn wait_until_ready(wait: &ResolvedWait, request: &Request) -> Result<(), runner::RunFailure> {
+    if request.stream_output {
+        eprintln!(
+            "{} run \"{}\" waiting for {}",
+            output::info("i"),
+            request.name,
+            output::command(&wait.target.describe())
+        );
+    }
+
+    let started_at = OffsetDateTime::now_utc();
+    let wall = Instant::now();
+    let stopped = || {
+        runner::interrupted()
+            || request
+                .cancel
+                .as_ref()
+                .is_some_and(CancelToken::is_cancelled)
+    };
+    ready::wait(&wait.target, wait.timeout, stopped).map_err(|message| {
+        let (exit_code, status) = if stopped() {
+            (130, RunStatus::Interrupted)
+        } else {
+            (1, RunStatus::Failed)
+        };
+        runner::RunFailure {
+            result: Box::new(runner::RunResult {
+                started_at,
+                duration: wall.elapsed(),
+                exit_code,
+                status,
+                stdout_tail: None,
+                stderr_tail: None,
+                signal: None,
+                attempts: Vec::new(),
+            }),
+            message: format!("wait_for: {message}"),
+        }
+    })
+}
+
+fn supervise(
+    request: &Request,
+    policy: RestartPolicy,
+) -> Result<runner::RunResult, runner::RunFailure> {
+    let base = if request.retry_backoff.is_zero() {
+        Duration::from_secs(1)
+    } else {
+        request.retry_backoff
+    };
+    let max_backoff = if request.retry_max_backoff.is_zero() {
+        SERVICE_MAX_BACKOFF
+    } else {
+        request.retry_max_backoff
+    };
+    let stopped = || {
+        runner::interrupted()
+            || request
+                .cancel
+                .as_ref()
+                .is_some_and(CancelToken::is_cancelled)
+    };
+
+    let mut restarts = 0;
+    loop {
+        let wall = Instant::now();
+        let outcome = runner::execute(request);
+        if stopped() || (policy == RestartPolicy::OnFailure && outcome.is_ok()) {
+            return outcome;
+        }
+
+        if wall.elapsed() >= SERVICE_STABLE_AFTER {
+            restarts = 0;
+        }
+        let wait = runner::retry_delay(request.retry_strategy, base, restarts, max_backoff);
+        restarts += 1;
+        let reason = match &outcome {
+            Ok(result) => format!("exited with code {}", result.exit_code),
+            Err(failure) => failure.message.clone(),
+        };
+        eprintln!(
+            "{} service \"{}\" {reason}; restarting in {}",
+            output::warning("warn"),
+            request.name,
+            output::number(&output::format_duration_ms(wait.as_millis() as i64))
+        );
+
+        let resume_at = Instant::now() + wait;
+        while Instant::now() < resume_at {
+            if stopped() {
+                let mut result = match outcome {
+                    Ok(result) => result,
+                    Err(failure) => *failure.result,
+                };
+                result.exit_code = 130;
+                result.status = RunStatus::Interrupted;
+                return Err(runner::RunFailure {
+                    result: Box::new(result),
+                    message: format!("service \"{}\" stopped", request.name),
+                });
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+
+fn run_steps(
+    steps: &[ResolvedStep],
+    base: &Request,
+) -> Result<runner::RunResult, runner::RunFailure> {
+    let started_at = OffsetDateTime::now_utc();
+    let wall = Instant::now();
+    let mut first_failure: Option<runner::RunFailure> = None;
+    let mut stdout_tail = None;
+    let mut stderr_tail = None;
+    let mut attempts = Vec::new();
+
+    for step in steps {
+        if base.stream_output {
+            eprintln!(
+                "{} run \"{}\" step {}",
+                output::info("i"),
+                base.name,
+                output::command(&step.name)
+            );
+        }
+
+        let request = Request {
+            name: format!("{}:{}", base.name, step.name),
+            command_preview: if step.use_shell {
+                step.shell.clone()
+            } else {
+                step.exec.join(" ")
+            },
+            use_shell: step.use_shell,
+            exec: step.exec.clone(),
+            shell: step.shell.clone(),
+            ..base.clone()
+        };
+
+        match runner::execute(&request) {
+            Ok(result) => {
+                stdout_tail = result.stdout_tail;
+                stderr_tail = result.stderr_tail;
+                attempts.extend(result.attempts);
+            }
+            Err(mut failure) => {
+                attempts.append(&mut failure.result.attempts);
+                failure.message = format!("step {:?}: {}", step.name, failure.message);
+                let interrupted = failure.result.status == RunStatus::Interrupted;
+                let stop = interrupted || !step.continue_on_error;
+                if first_failure.is_none() || interrupted {
+                    first_failure = Some(failure);
+                }
+                if stop {
+                    break;
+                }
+            }
+        }
+    }
+
+    match first_failure {
+        Some(mut failure) => {
+            failure.result.started_at = started_at;
+            failure.result.duration = wall.elapsed();
+            failure.result.attempts = attempts;
+            Err(failure)
+        }
+        None => Ok(runner::RunResult {
+            started_at,
+            duration: wall.elapsed(),
+            exit_code: 0,
+            status: RunStatus::Success,
+            stdout_tail,
+            stderr_tail,
+            signal: None,
+            attempts,
+        }),
+    }
+}
+
 #[derive(Serialize)]
 struct RunJsonPayload<'a> {
     id: &'a str,
@@ -610,13 +2479,110 @@ struct RunJsonPayload<'a> {
     exit_code: i32,
     status: &'a str,
     #[serde(skip_serializing_if = "Option::is_none")]
+    stdout_tail: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     stderr_tail: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    signal: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skip_reason: Option<&'a str>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    forced: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rerun_of: Option<&'a str>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    tags: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    git: Option<&'a GitContext>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    host: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cwd: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<&'a str>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    attempts: &'a [AttemptRecord],
 }
 
-fn print_run_json(record: &RunRecord, error: Option<String>) -> Result<(), io::Error> {
-    let payload = RunJsonPayload {
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum StreamEvent<'a> {
+    RunStarted {
+        id: &'a str,
+        name: &'a str,
+        source: &'a str,
+        command_preview: &'a str,
+        #[serde(with = "time::serde::rfc3339")]
+        started_at: OffsetDateTime,
+    },
+    AttemptStarted {
+        id: &'a str,
+        attempt: i32,
+        max_attempts: i32,
+    },
+    OutputChunk {
+        id: &'a str,
+        stream: &'a str,
+        data: &'a str,
+    },
+    AttemptFinished {
+        id: &'a str,
+        attempt: i32,
+        exit_code: i32,
+        duration_ms: i64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<&'a str>,
+    },
+    RunFinished(Box<RunJsonPayload<'a>>),
+}
+
+fn print_stream_event(event: &StreamEvent) -> Result<(), io::Error> {
+    let line = serde_json::to_string(event)?;
+    let mut stdout = io::stdout().lock();
+    writeln!(stdout, "{line}")?;
+    stdout.flush()
+}
+
+fn stream_sink(id: String) -> EventSink {
+    EventSink::new(move |event| {
+        let event = match &event {
+            RunEvent::AttemptStarted {
+                attempt,
+                max_attempts,
+            } => StreamEvent::AttemptStarted {
+                id: &id,
+                attempt: *attempt,
+                max_attempts: *max_attempts,
+            },
+            RunEvent::Output { stream, data } => StreamEvent::OutputChunk {
+                id: &id,
+                stream: match stream {
+                    Stream::Stdout => "stdout",
+                    Stream::Stderr => "stderr",
+                },
+                data,
+            },
+            RunEvent::AttemptFinished {
+                attempt,
+                exit_code,
+                duration,
+                error,
+            } => StreamEvent::AttemptFinished {
+                id: &id,
+                attempt: *attempt,
+                exit_code: *exit_code,
+                duration_ms: duration.as_millis() as i64,
+                error: error.as_deref(),
+            },
+        };
+        let _ = print_stream_event(&event);
+    })
+}
+
+fn run_json_payload<'a>(record: &'a RunRecord, error: Option<&'a str>) -> RunJsonPayload<'a> {
+    RunJsonPayload {
         id: &record.id,
         name: &record.name,
         source: source_to_str(record.source),
@@ -625,9 +2591,24 @@ fn print_run_json(record: &RunRecord, error: Option<String>) -> Result<(), io::E
         duration_ms: record.duration_ms,
         exit_code: record.exit_code,
         status: status_to_str(record.status),
+        stdout_tail: record.stdout_tail.as_deref(),
         stderr_tail: record.stderr_tail.as_deref(),
-        error: error.as_deref(),
-    };
+        signal: record.signal.as_deref(),
+        skip_reason: record.skip_reason.as_deref(),
+        forced: record.forced,
+        rerun_of: record.rerun_of.as_deref(),
+        tags: &record.tags,
+        git: record.git.as_ref(),
+        host: record.host.as_deref(),
+        user: record.user.as_deref(),
+        cwd: record.cwd.as_deref(),
+        error,
+        attempts: &record.attempts,
+    }
+}
+
+fn print_run_json(record: &RunRecord, error: Option<String>) -> Result<(), io::Error> {
+    let payload = run_json_payload(record, error.as_deref());
 
     let mut stdout = io::stdout().lock();
     serde_json::to_writer_pretty(&mut stdout, &payload)?;
@@ -663,132 +2644,639 @@ fn load_dotenv(
     }
 }
 
-fn apply_runtime_env(resolved: &mut ResolvedTask, dotenv_vars: &HashMap<String, String>) {
+fn expand_globs(dir: &str, args: Vec<String>) -> Result<Vec<String>, String> {
+    let dir = if dir.is_empty() {
+        Path::new(".")
+    } else {
+        Path::new(dir)
+    };
+    let mut expanded = Vec::with_capacity(args.len());
+    let mut args = args.into_iter();
+    expanded.extend(args.next());
+
+    for arg in args {
+        if !arg.contains(['*', '?', '[']) {
+            expanded.push(arg);
+            continue;
+        }
+
+        let pattern = dir.join(&arg);
+        let mut matches: Vec<String> = glob::glob(&pattern.to_string_lossy())
+            .map_err(|e| format!("invalid glob {arg:?}: {e}"))?
+            .filter_map(Result::ok)
+            .map(|path| {
+                path.strip_prefix(dir)
+                    .unwrap_or(&path)
+                    .display()
+                    .to_string()
+            })
+            .collect();
+        if matches.is_empty() {
+            expanded.push(arg);
+            continue;
+        }
+        matches.sort();
+        expanded.extend(matches);
+    }
+
+    Ok(expanded)
+}
+
+fn retries_disabled(flag: bool) -> bool {
+    flag || std::env::var("OTTO_NO_RETRY").is_ok_and(|value| value == "1")
+}
+
+fn apply_runtime_env(resolved: &mut ResolvedTask, opts: &RunOptions) -> Result<(), String> {
+    if resolved.run_id.is_empty() {
+        resolved.run_id = new_record_id();
+    }
+    if opts.clean_env {
+        resolved.inherit_env = false;
+    }
+    if opts.no_retries {
+        resolved.retries = 0;
+    }
+
     let mut lookup: HashMap<String, String> = std::env::vars().collect();
+    lookup.extend(
+        opts.outputs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone(),
+    );
     let mut runtime_env: HashMap<String, String> = HashMap::new();
 
-    for (key, value) in dotenv_vars {
-        if lookup.contains_key(key) {
+    for (key, value) in &opts.dotenv_vars {
+        if let Some(existing) = lookup.get(key) {
+            if !resolved.inherit_env {
+                runtime_env.insert(key.clone(), existing.clone());
+            }
             continue;
         }
         runtime_env.insert(key.clone(), value.clone());
         lookup.insert(key.clone(), value.clone());
     }
 
+    if !resolved.prompts.is_empty() {
+        for (key, value) in prompt::answer(&resolved.prompts, &opts.vars)? {
+            runtime_env.insert(key.clone(), value.clone());
+            lookup.insert(key, value);
+        }
+    }
+
     if !resolved.env.is_empty() {
         let mut keys: Vec<String> = resolved.env.keys().cloned().collect();
         keys.sort();
 
-        for key in keys {
-            if let Some(value) = resolved.env.get(&key) {
-                let expanded = expand_variables(value, &lookup);
-                runtime_env.insert(key.clone(), expanded.clone());
-                lookup.insert(key, expanded);
+        for key in keys {
+            if let Some(value) = resolved.env.get(&key) {
+                let expanded = expand_variables(value, &lookup)?;
+                runtime_env.insert(key.clone(), expanded.clone());
+                lookup.insert(key, expanded);
+            }
+        }
+    }
+
+    if !resolved.dir.is_empty() {
+        resolved.dir = expand_variables(&resolved.dir, &lookup)?;
+    }
+
+    for path in resolved.named_outputs.values_mut() {
+        *path = expand_variables(path, &lookup)?;
+    }
+
+    if !resolved.base_dir.is_empty() {
+        resolved.dir = Path::new(&resolved.base_dir)
+            .join(&resolved.dir)
+            .display()
+            .to_string();
+    }
+
+    if let StdinMode::File(path) = &resolved.stdin {
+        let expanded = expand_variables(&path.display().to_string(), &lookup)?;
+        resolved.stdin = StdinMode::File(Path::new(&resolved.dir).join(expanded));
+    }
+
+    if let Some(path) = &opts.stdin_file {
+        if resolved.interactive {
+            return Err(format!(
+                "--stdin-file cannot be used with interactive task {:?}",
+                resolved.name
+            ));
+        }
+        let cwd = std::env::current_dir().map_err(|e| format!("resolve working directory: {e}"))?;
+        resolved.stdin = StdinMode::File(cwd.join(path));
+    }
+
+    if resolved.use_shell {
+        resolved.shell = expand_variables(&resolved.shell, &lookup)?;
+        resolved.command_preview = resolved.shell.clone();
+    } else if !resolved.exec.is_empty() {
+        let expanded: Vec<String> = resolved
+            .exec
+            .iter()
+            .map(|token| expand_variables(token, &lookup))
+            .collect::<Result<_, _>>()?;
+        let expanded = if resolved.glob {
+            expand_globs(&resolved.dir, expanded)?
+        } else {
+            expanded
+        };
+        resolved.command_preview = expanded.join(" ");
+        resolved.exec = expanded;
+    }
+
+    for step in &mut resolved.steps {
+        if step.use_shell {
+            step.shell = expand_variables(&step.shell, &lookup)?;
+        } else {
+            step.exec = step
+                .exec
+                .iter()
+                .map(|token| expand_variables(token, &lookup))
+                .collect::<Result<_, _>>()?;
+            if resolved.glob {
+                step.exec = expand_globs(&resolved.dir, std::mem::take(&mut step.exec))?;
+            }
+        }
+    }
+
+    for hook in [
+        &mut resolved.before,
+        &mut resolved.after,
+        &mut resolved.on_failure,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if hook.use_shell {
+            hook.shell = expand_variables(&hook.shell, &lookup)?;
+        } else {
+            hook.exec = hook
+                .exec
+                .iter()
+                .map(|token| expand_variables(token, &lookup))
+                .collect::<Result<_, _>>()?;
+        }
+    }
+
+    if !resolved.inherit_env
+        && !runtime_env.contains_key("PATH")
+        && let Some(path) = lookup.get("PATH")
+    {
+        runtime_env.insert("PATH".to_string(), path.clone());
+    }
+
+    if let Some(container) = resolved.container.clone() {
+        wrap_in_container(resolved, &container, &runtime_env, &lookup)?;
+    }
+
+    resolved.env = runtime_env;
+    Ok(())
+}
+
+fn wrap_in_container(
+    resolved: &mut ResolvedTask,
+    container: &Container,
+    env: &HashMap<String, String>,
+    lookup: &HashMap<String, String>,
+) -> Result<(), String> {
+    let cwd = std::env::current_dir().map_err(|e| format!("resolve working directory: {e}"))?;
+    let absolute = |path: &str| -> String {
+        cwd.join(path)
+            .components()
+            .collect::<PathBuf>()
+            .display()
+            .to_string()
+    };
+    let project = absolute(&resolved.base_dir);
+    let workdir = absolute(&resolved.dir);
+
+    let mut prefix: Vec<String> = ["docker", "run", "--rm", "--init", "-i"]
+        .map(String::from)
+        .to_vec();
+    if resolved.interactive || resolved.pty {
+        prefix.push("-t".to_string());
+    }
+    prefix.extend(["--name".to_string(), container_name(&resolved.run_id)]);
+    prefix.extend(["-v".to_string(), format!("{project}:{project}")]);
+    for volume in &container.volumes {
+        let volume = expand_variables(volume, lookup)?;
+        let volume = match volume.split_once(':') {
+            Some((host, rest)) if host.starts_with('.') => {
+                format!("{}:{rest}", absolute(&format!("{project}/{host}")))
+            }
+            _ => volume,
+        };
+        prefix.extend(["-v".to_string(), volume]);
+    }
+    let tmpdir = run_tmpdir(&resolved.run_id).display().to_string();
+    prefix.extend(["-v".to_string(), format!("{tmpdir}:{tmpdir}")]);
+    prefix.extend(["-w".to_string(), workdir]);
+
+    let mut keys: Vec<&str> = env.keys().map(String::as_str).collect();
+    keys.extend([
+        "OTTO_ATTEMPT",
+        "OTTO_MAX_ATTEMPTS",
+        "OTTO_TASK_NAME",
+        "OTTO_RUN_ID",
+        "OTTO_CONFIG_DIR",
+        "OTTO_STARTED_AT",
+        "OTTO_TMPDIR",
+    ]);
+    keys.sort_unstable();
+    keys.dedup();
+    for key in keys {
+        if key != "PATH" {
+            prefix.extend(["-e".to_string(), key.to_string()]);
+        }
+    }
+    prefix.push(expand_variables(&container.image, lookup)?);
+
+    let shell_program = if resolved.shell_kind == runner::ShellKind::Bash {
+        "bash"
+    } else {
+        "sh"
+    };
+    let wrap = |use_shell: bool, shell: &str, exec: &[String]| -> Vec<String> {
+        let mut args = prefix.clone();
+        if use_shell {
+            args.extend([
+                shell_program.to_string(),
+                "-c".to_string(),
+                shell.to_string(),
+            ]);
+        } else {
+            args.extend(exec.iter().cloned());
+        }
+        args
+    };
+
+    if resolved.steps.is_empty() {
+        resolved.exec = wrap(resolved.use_shell, &resolved.shell, &resolved.exec);
+        resolved.use_shell = false;
+    }
+    for step in &mut resolved.steps {
+        step.exec = wrap(step.use_shell, &step.shell, &step.exec);
+        step.use_shell = false;
+    }
+    Ok(())
+}
+
+struct TempDirGuard {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl Drop for TempDirGuard {
+    fn drop(&mut self) {
+        if !self.keep
+            && let Err(err) = fs::remove_dir_all(&self.path)
+        {
+            eprintln!(
+                "{} remove temp directory {}: {err}",
+                output::warning("warn"),
+                self.path.display()
+            );
+        }
+    }
+}
+
+fn run_tmpdir(run_id: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("otto-{run_id}"))
+}
+
+fn container_name(run_id: &str) -> String {
+    format!("otto-{run_id}")
+}
+
+fn expand_variables(value: &str, lookup: &HashMap<String, String>) -> Result<String, String> {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+
+        if let Some(after) = rest.strip_prefix('$') {
+            out.push('$');
+            rest = after;
+            continue;
+        }
+
+        if let Some(body) = rest.strip_prefix('{') {
+            let Some(end) = closing_brace(body) else {
+                out.push('$');
+                continue;
+            };
+            out.push_str(&expand_braced(&body[..end], lookup)?);
+            rest = &body[end + 1..];
+            continue;
+        }
+
+        let len = rest
+            .char_indices()
+            .take_while(|(idx, ch)| {
+                ch.is_ascii_alphabetic() || *ch == '_' || (*idx > 0 && ch.is_ascii_digit())
+            })
+            .count();
+        if len == 0 {
+            out.push('$');
+            continue;
+        }
+
+        let key = &rest[..len];
+        match lookup.get(key) {
+            Some(found) => out.push_str(found),
+            None => out.push_str(&format!("${{{key}}}")),
+        }
+        rest = &rest[len..];
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn closing_brace(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (idx, ch) in text.char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some(idx),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn expand_braced(expr: &str, lookup: &HashMap<String, String>) -> Result<String, String> {
+    if let Some((key, fallback)) = expr.split_once(":-") {
+        return match lookup.get(key).filter(|found| !found.is_empty()) {
+            Some(found) => Ok(found.clone()),
+            None => expand_variables(fallback, lookup),
+        };
+    }
+
+    if let Some((key, message)) = expr.split_once(":?") {
+        return match lookup.get(key).filter(|found| !found.is_empty()) {
+            Some(found) => Ok(found.clone()),
+            None if message.is_empty() => Err(format!("{key}: parameter null or not set")),
+            None => Err(format!("{key}: {message}")),
+        };
+    }
+
+    if let Some(reference) = expr.strip_prefix("needs.")
+        && !lookup.contains_key(expr)
+    {
+        let (task, key) = reference
+            .split_once(".outputs.")
+            .unwrap_or((reference, "?"));
+        return Err(format!(
+            "output {key:?} of task {task:?} is not available; run {task:?} earlier in the same group"
+        ));
+    }
+
+    Ok(match lookup.get(expr) {
+        Some(found) => found.clone(),
+        None => format!("${{{expr}}}"),
+    })
+}
+
+fn load_config_classified(path: &Path) -> Result<Config, AppError> {
+    config::load(path).map_err(|err| {
+        if err.starts_with("read config:") && !err.contains("No such file") {
+            AppError::internal(err)
+        } else {
+            AppError::usage(err)
+        }
+    })
+}
+
+fn send_notification(
+    notify_on: &str,
+    min_duration: Duration,
+    opts: &RunOptions,
+    record: &RunRecord,
+) {
+    let duration = Duration::from_millis(record.duration_ms as u64);
+    if !should_notify(notify_on, min_duration, record.status, duration) {
+        return;
+    }
+
+    let manager = notify::Manager {
+        desktop_enabled: opts.notifications.desktop_enabled,
+        webhook_url: opts.notifications.webhook_url.clone(),
+        webhook_timeout: opts.notifications.webhook_timeout,
+        slack: opts
+            .notifications
+            .slack
+            .as_ref()
+            .map(|slack| notify::Slack {
+                webhook_url: slack.webhook_url.clone(),
+                channel: slack.channel.clone(),
+                mention_on_failure: slack.mention_on_failure.clone(),
+            }),
+        discord: opts
+            .notifications
+            .discord
+            .as_ref()
+            .map(|discord| notify::Discord {
+                webhook_url: discord.webhook_url.clone(),
+                username: discord.username.clone(),
+            }),
+        teams_webhook_url: opts
+            .notifications
+            .teams
+            .as_ref()
+            .map(|teams| teams.webhook_url.clone())
+            .unwrap_or_default(),
+        telegram: opts
+            .notifications
+            .telegram
+            .as_ref()
+            .map(|telegram| notify::Telegram {
+                bot_token_env: telegram.bot_token_env.clone(),
+                chat_id: telegram.chat_id.clone(),
+            }),
+        email: opts.notifications.email.as_ref().and_then(|email| {
+            let (host, port) = email.server.rsplit_once(':')?;
+            Some(notify::Email {
+                host: host.to_string(),
+                port: port.parse().ok()?,
+                security: smtp::Security::parse(&email.security)?,
+                username_env: email.username_env.clone(),
+                password_env: email.password_env.clone(),
+                from: email.from.clone(),
+                to: email.to.clone(),
+            })
+        }),
+        ntfy: opts.notifications.ntfy.as_ref().map(|ntfy| notify::Ntfy {
+            url: ntfy.url.clone(),
+            token_env: ntfy.token_env.clone(),
+            priorities: ntfy.priorities.clone(),
+        }),
+        pushover: opts
+            .notifications
+            .pushover
+            .as_ref()
+            .map(|pushover| notify::Pushover {
+                user_key: pushover.user_key.clone(),
+                token_env: pushover.token_env.clone(),
+                failure_priority: pushover.failure_priority,
+                retry: pushover.retry,
+                expire: pushover.expire,
+            }),
+    };
+
+    let event = notify::Event {
+        name: record.name.clone(),
+        source: source_to_str(record.source).to_string(),
+        status: status_to_str(record.status).to_string(),
+        exit_code: record.exit_code,
+        duration,
+        started_at: record.started_at,
+        command_preview: record.command_preview.clone(),
+        stdout_tail: record.stdout_tail.clone(),
+        stderr_tail: record.stderr_tail.clone(),
+        signal: record.signal.clone(),
+        git: record.git.clone(),
+    };
+
+    if let Err(err) = manager.notify(&event) {
+        eprintln!(
+            "{} failed to send notification: {err}",
+            output::warning("warn")
+        );
+    }
+}
+
+fn check_replay_env(expected: &ExpectedEnv, env: &HashMap<String, String>) -> Result<(), AppError> {
+    if expected.names.is_empty() {
+        return Ok(());
+    }
+
+    let mut changes = Vec::new();
+    for name in &expected.names {
+        match env.get(name) {
+            None => changes.push(format!("{name} is no longer set")),
+            Some(value) => {
+                if expected
+                    .hashes
+                    .get(name)
+                    .is_some_and(|hash| *hash != env_hash(value))
+                {
+                    changes.push(format!("{name} changed"));
+                }
             }
         }
     }
-
-    if !resolved.dir.is_empty() {
-        resolved.dir = expand_variables(&resolved.dir, &lookup);
+    let mut added: Vec<&String> = env
+        .keys()
+        .filter(|name| !expected.names.contains(name))
+        .collect();
+    added.sort();
+    changes.extend(added.into_iter().map(|name| format!("{name} is new")));
+    if changes.is_empty() {
+        return Ok(());
     }
 
-    if resolved.use_shell {
-        resolved.shell = expand_variables(&resolved.shell, &lookup);
-        resolved.command_preview = resolved.shell.clone();
-    } else if !resolved.exec.is_empty() {
-        let expanded: Vec<String> = resolved
-            .exec
-            .iter()
-            .map(|token| expand_variables(token, &lookup))
-            .collect();
-        resolved.command_preview = expanded.join(" ");
-        resolved.exec = expanded;
+    let message = format!(
+        "environment differs from run {}: {}",
+        expected.run_id,
+        changes.join(", ")
+    );
+    if expected.strict {
+        return Err(AppError::runtime(message));
     }
-
-    resolved.env = runtime_env;
+    eprintln!("{} {message}", output::warning("warn"));
+    Ok(())
 }
 
-fn expand_variables(value: &str, lookup: &HashMap<String, String>) -> String {
-    let mut out = String::with_capacity(value.len());
-    let bytes = value.as_bytes();
-    let mut i = 0;
+fn env_hash(value: &str) -> String {
+    let digest = Sha256::digest(value.as_bytes());
+    digest[..8].iter().map(|b| format!("{b:02x}")).collect()
+}
 
-    while i < bytes.len() {
-        if bytes[i] != b'$' {
-            out.push(bytes[i] as char);
-            i += 1;
-            continue;
-        }
+fn git_context(resolved: &ResolvedTask) -> Option<GitContext> {
+    let base = if resolved.base_dir.is_empty() {
+        Path::new(".")
+    } else {
+        Path::new(&resolved.base_dir)
+    };
+    git::context(&base.join(&resolved.dir))
+}
 
-        if i + 1 >= bytes.len() {
-            out.push('$');
-            break;
-        }
+fn send_metrics(opts: &RunOptions, record: &RunRecord) {
+    let emitter = metrics::Emitter {
+        statsd_addr: opts.notifications.statsd.clone(),
+        pushgateway_url: opts.notifications.pushgateway_url.clone(),
+        prefix: opts.notifications.metrics_prefix.clone(),
+        timeout: opts.notifications.webhook_timeout,
+    };
+    if !emitter.is_enabled() {
+        return;
+    }
 
-        if bytes[i + 1] == b'{' {
-            if let Some(end_rel) = value[i + 2..].find('}') {
-                let end = i + 2 + end_rel;
-                let key = &value[i + 2..end];
-                if let Some(found) = lookup.get(key) {
-                    out.push_str(found);
-                } else {
-                    out.push_str(&format!("${{{key}}}"));
-                }
-                i = end + 1;
-                continue;
-            }
+    let sample = metrics::Sample {
+        name: record.name.clone(),
+        status: status_to_str(record.status).to_string(),
+        exit_code: record.exit_code,
+        duration: Duration::from_millis(record.duration_ms as u64),
+    };
 
-            out.push('$');
-            i += 1;
-            continue;
-        }
+    if let Err(err) = emitter.emit(&sample) {
+        eprintln!("{} failed to send metrics: {err}", output::warning("warn"));
+    }
+}
 
-        let mut j = i + 1;
-        while j < bytes.len() {
-            let ch = bytes[j] as char;
-            if j == i + 1 {
-                if !(ch.is_ascii_alphabetic() || ch == '_') {
-                    break;
-                }
-            } else if !(ch.is_ascii_alphanumeric() || ch == '_') {
-                break;
+fn history_settings(cfg: Option<&Config>, no_history: bool) -> HistorySettings {
+    let mut settings = cfg
+        .map(Config::resolve_history_settings)
+        .unwrap_or_default();
+    match std::env::var(history::PATH_ENV).as_deref() {
+        Ok("") | Err(_) => {}
+        Ok("global") => {
+            if let Some(path) = history::global_path() {
+                settings.path = path;
             }
-            j += 1;
-        }
-
-        if j == i + 1 {
-            out.push('$');
-            i += 1;
-            continue;
         }
+        Ok(path) => settings.path = PathBuf::from(path),
+    }
+    if no_history {
+        settings.enabled = false;
+    }
+    settings
+}
 
-        let key = &value[i + 1..j];
-        if let Some(found) = lookup.get(key) {
-            out.push_str(found);
-        } else {
-            out.push_str(&format!("${{{key}}}"));
-        }
-        i = j;
+fn append_history(settings: &HistorySettings, record: &RunRecord) -> Result<(), AppError> {
+    if !settings.enabled {
+        return Ok(());
     }
 
-    out
+    history_store(settings)
+        .append(record)
+        .map_err(AppError::internal)
 }
 
-fn load_config_classified(path: &Path) -> Result<Config, AppError> {
-    config::load(path).map_err(|err| {
-        if err.starts_with("read config:") && !err.contains("No such file") {
-            AppError::internal(err)
-        } else {
-            AppError::usage(err)
-        }
-    })
+fn history_store(settings: &HistorySettings) -> Store {
+    Store::new(&settings.path)
+        .with_max_entries(settings.max_entries)
+        .with_rotation(settings.max_size, settings.max_archives)
+        .with_compression(settings.compress)
 }
 
-fn should_notify(policy: &str, status: RunStatus) -> bool {
+fn should_notify(
+    policy: &str,
+    min_duration: Duration,
+    status: RunStatus,
+    duration: Duration,
+) -> bool {
+    let failed = matches!(status, RunStatus::Failed | RunStatus::TimedOut);
     match policy {
         "never" => false,
-        "always" => true,
-        _ => status == RunStatus::Failed,
+        "always" => failed || duration >= min_duration,
+        _ => failed,
     }
 }
 
@@ -811,6 +3299,7 @@ fn source_to_str(source: RunSource) -> &'static str {
     match source {
         RunSource::Task => "task",
         RunSource::Inline => "inline",
+        RunSource::Schedule => "schedule",
     }
 }
 
@@ -818,34 +3307,117 @@ fn status_to_str(status: RunStatus) -> &'static str {
     match status {
         RunStatus::Success => "success",
         RunStatus::Failed => "failed",
+        RunStatus::Skipped => "skipped",
+        RunStatus::Interrupted => "interrupted",
+        RunStatus::TimedOut => "timedout",
     }
 }
 
 fn run_history(args: HistoryArgs) -> Result<(), AppError> {
     if let Some(status) = &args.status
-        && status != "success"
-        && status != "failed"
+        && !["success", "failed", "skipped", "interrupted", "timedout"].contains(&status.as_str())
     {
-        return Err(AppError::usage("--status must be success or failed"));
+        return Err(AppError::usage(
+            "--status must be success, failed, skipped, interrupted, or timedout",
+        ));
     }
 
     if let Some(source) = &args.source
-        && source != "task"
-        && source != "inline"
+        && !["task", "inline", "schedule"].contains(&source.as_str())
+    {
+        return Err(AppError::usage(
+            "--source must be task, inline, or schedule",
+        ));
+    }
+
+    if let Some(name) = &args.name
+        && let Err(err) = glob::Pattern::new(name)
     {
-        return Err(AppError::usage("--source must be task or inline"));
+        return Err(AppError::usage(format!("--name: {err}")));
+    }
+
+    let config_path = args
+        .config
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH));
+    let maybe_cfg = maybe_load_config(&config_path, args.config.is_some())?;
+    let settings = history_settings(maybe_cfg.as_ref(), false);
+    let store = history_store(&settings);
+
+    match &args.command {
+        Some(HistoryCommand::Open(open)) => return run_history_open(&store, &open.id),
+        Some(HistoryCommand::Diff(diff)) => return run_history_diff(&store, diff),
+        Some(HistoryCommand::Import(import)) => return run_history_import(&store, import),
+        Some(HistoryCommand::Last(last)) => return run_history_last(&store, last),
+        Some(HistoryCommand::Prune(prune)) => return run_history_prune(&store, prune),
+        Some(HistoryCommand::Stats(stats)) => return run_history_stats(&store, stats),
+        None => {}
     }
 
-    let store = Store::new(DEFAULT_PATH);
     let rows = store.list(&Filter {
         limit: Some(args.limit),
+        offset: args.offset,
+        before: args.before.clone(),
         status: args.status.clone(),
         source: args.source.clone(),
+        name: args.name.clone(),
+        since: args
+            .since
+            .as_deref()
+            .map(|value| parse_time_bound("--since", value, false))
+            .transpose()?,
+        until: args
+            .until
+            .as_deref()
+            .map(|value| parse_time_bound("--until", value, true))
+            .transpose()?,
+        grep: args.grep.clone(),
+        tags: args.tags.clone(),
     });
 
-    let rows = rows.map_err(AppError::internal)?;
+    let rows = rows.map_err(|err| {
+        if args.before.is_some() && err.starts_with("no run with id") {
+            AppError::usage(err)
+        } else {
+            AppError::internal(err)
+        }
+    })?;
+    let format = match args.format {
+        Some(format) => format,
+        None if args.json => HistoryFormat::Json,
+        None => HistoryFormat::Text,
+    };
+
+    if format == HistoryFormat::Csv {
+        if args.relative {
+            return Err(AppError::usage("--relative requires text output"));
+        }
+        let columns: Vec<&str> = if args.columns.is_empty() {
+            output::DEFAULT_CSV_COLUMNS.to_vec()
+        } else {
+            args.columns
+                .iter()
+                .map(|column| {
+                    output::csv_column(column).ok_or_else(|| {
+                        AppError::usage(format!(
+                            "unknown column {column:?}; use {}",
+                            output::CSV_COLUMNS.join(", ")
+                        ))
+                    })
+                })
+                .collect::<Result<_, _>>()?
+        };
+        return output::print_history_csv(io::stdout().lock(), &rows, &columns)
+            .map_err(|e| AppError::internal(format!("print history: {e}")));
+    }
 
-    if args.json {
+    if format == HistoryFormat::Json {
+        if !args.columns.is_empty() {
+            return Err(AppError::usage("--columns requires text or csv output"));
+        }
+        if args.relative {
+            return Err(AppError::usage("--relative requires text output"));
+        }
         let mut stdout = io::stdout().lock();
         serde_json::to_writer_pretty(&mut stdout, &rows)
             .map_err(|e| AppError::internal(format!("encode history json: {e}")))?;
@@ -853,22 +3425,379 @@ fn run_history(args: HistoryArgs) -> Result<(), AppError> {
         return Ok(());
     }
 
+    let columns = args
+        .columns
+        .iter()
+        .map(|column| {
+            output::text_column(column)
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    AppError::usage(format!(
+                        "unknown column {column:?}; use {}",
+                        output::HISTORY_COLUMNS.join(", ")
+                    ))
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
     let display_rows: Vec<HistoryRow> = rows
         .into_iter()
         .map(|row| HistoryRow {
+            matches: match &args.grep {
+                Some(term) => history::matching_lines(&row, term)
+                    .iter()
+                    .take(GREP_MATCH_LINES)
+                    .map(|line| output::highlight_term(line, term))
+                    .collect(),
+                None => Vec::new(),
+            },
+            id: row.id,
+            log_path: row.log_path,
             name: row.name,
             source: row.source,
             status: row.status,
             exit_code: row.exit_code,
+            signal: row.signal,
+            skip_reason: row.skip_reason,
+            forced: row.forced,
+            rerun_of: row.rerun_of,
+            tags: row.tags,
+            git: row.git,
+            host: row.host,
+            user: row.user,
+            cwd: row.cwd,
             started_at: row.started_at,
             duration_ms: row.duration_ms,
         })
         .collect();
 
-    output::print_history(io::stdout().lock(), &display_rows)
+    let view = HistoryView {
+        columns,
+        relative_to: args.relative.then(OffsetDateTime::now_utc),
+    };
+    output::print_history(io::stdout().lock(), &display_rows, &view)
         .map_err(|e| AppError::internal(format!("print history: {e}")))
 }
 
+fn parse_time_bound(flag: &str, value: &str, end_of_day: bool) -> Result<OffsetDateTime, AppError> {
+    if let Ok(age) = humantime::parse_duration(value) {
+        return time_ago(flag, age);
+    }
+    if let Ok(at) = OffsetDateTime::parse(value, &time::format_description::well_known::Rfc3339) {
+        return Ok(at);
+    }
+    time::Date::parse(
+        value,
+        time::macros::format_description!("[year]-[month]-[day]"),
+    )
+    .map(|date| {
+        let time = if end_of_day {
+            time::Time::MAX
+        } else {
+            time::Time::MIDNIGHT
+        };
+        date.with_time(time).assume_utc()
+    })
+    .map_err(|_| {
+        AppError::usage(format!(
+            "{flag} expects a duration (2h), a date (2024-06-01), or an RFC 3339 time"
+        ))
+    })
+}
+
+fn time_ago(flag: &str, age: Duration) -> Result<OffsetDateTime, AppError> {
+    time::Duration::try_from(age)
+        .ok()
+        .and_then(|age| OffsetDateTime::now_utc().checked_sub(age))
+        .ok_or_else(|| AppError::usage(format!("{flag} reaches too far into the past")))
+}
+
+fn run_history_last(store: &Store, args: &HistoryLastArgs) -> Result<(), AppError> {
+    if let Some(name) = &args.name
+        && let Err(err) = glob::Pattern::new(name)
+    {
+        return Err(AppError::usage(format!("--name: {err}")));
+    }
+
+    let record = store
+        .list(&Filter {
+            limit: Some(1),
+            name: args.name.clone(),
+            ..Filter::default()
+        })
+        .map_err(AppError::internal)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| match &args.name {
+            Some(name) => AppError::usage(format!("no runs of {name:?} in history")),
+            None => AppError::usage("no runs in history"),
+        })?;
+
+    let mut stdout = io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, &record)
+        .map_err(|e| AppError::internal(format!("encode json: {e}")))?;
+    writeln!(stdout).map_err(|e| AppError::internal(format!("write output: {e}")))?;
+
+    if !args.exit_code {
+        return Ok(());
+    }
+
+    let message = format!(
+        "last run of \"{}\" {}",
+        record.name,
+        status_to_str(record.status)
+    );
+    match record.status {
+        RunStatus::Success | RunStatus::Skipped => Ok(()),
+        RunStatus::Interrupted => Err(AppError::interrupted(message)),
+        RunStatus::Failed | RunStatus::TimedOut => Err(AppError::runtime(message)),
+    }
+}
+
+fn run_history_import(store: &Store, args: &HistoryImportArgs) -> Result<(), AppError> {
+    if !args.path.is_file() {
+        return Err(AppError::usage(format!(
+            "history file {} not found",
+            output::command(&args.path.display().to_string())
+        )));
+    }
+
+    let summary = store.import(&args.path).map_err(AppError::internal)?;
+    let runs = match summary.imported {
+        1 => "1 run".to_string(),
+        n => format!("{n} runs"),
+    };
+    if summary.duplicates == 0 {
+        println!(
+            "{} imported {} into history",
+            output::success("ok"),
+            output::number(&runs)
+        );
+    } else {
+        println!(
+            "{} imported {} into history ({} already present)",
+            output::success("ok"),
+            output::number(&runs),
+            summary.duplicates
+        );
+    }
+    Ok(())
+}
+
+fn run_history_prune(store: &Store, args: &HistoryPruneArgs) -> Result<(), AppError> {
+    let newer_than = args
+        .older_than
+        .as_deref()
+        .map(|age| {
+            humantime::parse_duration(age)
+                .map_err(|e| AppError::usage(format!("--older-than: {e}")))
+                .and_then(|age| time_ago("--older-than", age))
+        })
+        .transpose()?;
+
+    let removed = store
+        .prune(&Retention {
+            keep: args.keep,
+            newer_than,
+        })
+        .map_err(AppError::internal)?;
+
+    for err in store.remove_logs(&removed) {
+        eprintln!("{} {err}", output::warning("warn"));
+    }
+
+    println!(
+        "{} pruned {} from history",
+        output::success("ok"),
+        output::number(&match removed.len() {
+            1 => "1 run".to_string(),
+            n => format!("{n} runs"),
+        })
+    );
+    Ok(())
+}
+
+fn run_history_stats(store: &Store, args: &HistoryStatsArgs) -> Result<(), AppError> {
+    let records = store.list(&Filter::default()).map_err(AppError::internal)?;
+    let stats = history::stats(&records);
+
+    if args.json {
+        let mut stdout = io::stdout().lock();
+        serde_json::to_writer_pretty(&mut stdout, &stats)
+            .map_err(|e| AppError::internal(format!("encode stats json: {e}")))?;
+        writeln!(stdout).map_err(|e| AppError::internal(format!("write output: {e}")))?;
+        return Ok(());
+    }
+
+    output::print_stats(io::stdout().lock(), &stats)
+        .map_err(|e| AppError::internal(format!("print stats: {e}")))
+}
+
+fn find_record(store: &Store, id: &str) -> Result<RunRecord, AppError> {
+    let mut rows = store.list(&Filter::default()).map_err(AppError::internal)?;
+    if let Some(index) = rows.iter().position(|row| row.id == id) {
+        return Ok(rows.swap_remove(index));
+    }
+
+    let mut matches: Vec<RunRecord> = rows
+        .into_iter()
+        .filter(|row| row.id.starts_with(id))
+        .collect();
+    match matches.len() {
+        1 => Ok(matches.remove(0)),
+        0 => Err(AppError::usage(format!("no run with id {id:?}"))),
+        n => Err(AppError::usage(format!(
+            "id {id:?} matches {n} runs; use more characters"
+        ))),
+    }
+}
+
+fn run_rerun(args: RerunArgs) -> Result<(), AppError> {
+    let config_path = args
+        .config
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH));
+    let maybe_cfg = maybe_load_config(&config_path, args.config.is_some())?;
+    let settings = history_settings(maybe_cfg.as_ref(), false);
+    let store = Store::new(&settings.path);
+
+    let record = match &args.id {
+        Some(id) => find_record(&store, id)?,
+        None => store
+            .list(&Filter::default())
+            .map_err(AppError::internal)?
+            .into_iter()
+            .find(|row| {
+                !args.failed || matches!(row.status, RunStatus::Failed | RunStatus::TimedOut)
+            })
+            .ok_or_else(|| {
+                AppError::usage(if args.failed {
+                    "no failed runs in history"
+                } else {
+                    "no runs in history"
+                })
+            })?,
+    };
+
+    eprintln!(
+        "{} rerunning \"{}\" from run {}",
+        output::info("i"),
+        record.name,
+        output::muted(&record.id)
+    );
+
+    run_run(replay_args(record, args.config)?)
+}
+
+fn run_replay(args: ReplayArgs) -> Result<(), AppError> {
+    let config_path = args
+        .config
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH));
+    let maybe_cfg = maybe_load_config(&config_path, args.config.is_some())?;
+    let settings = history_settings(maybe_cfg.as_ref(), false);
+    let record = find_record(&Store::new(&settings.path), &args.id)?;
+
+    eprintln!(
+        "{} replaying \"{}\" from run {}",
+        output::info("i"),
+        record.name,
+        output::muted(&record.id)
+    );
+    if !record.env.is_empty() {
+        eprintln!(
+            "{} recorded environment: {}",
+            output::info("i"),
+            record.env.join(", ")
+        );
+    }
+
+    let replay = record.replay.clone().unwrap_or_default();
+    let expected_env = ExpectedEnv {
+        run_id: record.id.clone(),
+        names: record.env.clone(),
+        hashes: record.env_hashes.clone(),
+        strict: args.strict,
+    };
+    let mut run_args = replay_args(record, args.config)?;
+    run_args.expected_env = Some(expected_env);
+    run_args.env_file = replay.env_file.map(PathBuf::from);
+    run_args.no_dotenv = replay.no_dotenv;
+    run_args.clean_env = replay.clean_env;
+    run_args.vars = replay
+        .vars
+        .into_iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect();
+
+    run_run(run_args)
+}
+
+fn replay_args(record: RunRecord, config: Option<PathBuf>) -> Result<RunArgs, AppError> {
+    let mut run_args = RunArgs {
+        config,
+        rerun_of: Some(record.id.clone()),
+        tags: record.tags.clone(),
+        ..RunArgs::default()
+    };
+    if record.source == RunSource::Inline {
+        let inline = if record.args.is_empty() {
+            shlex::split(&record.command_preview).ok_or_else(|| {
+                AppError::usage(format!("cannot replay command of run {}", record.id))
+            })?
+        } else {
+            record.args
+        };
+        run_args.inline = inline.iter().map(|arg| arg.replace('$', "$$")).collect();
+        run_args.name = Some(record.name);
+    } else {
+        run_args.task = Some(record.name);
+    }
+
+    Ok(run_args)
+}
+
+fn run_history_open(store: &Store, id: &str) -> Result<(), AppError> {
+    let record = find_record(store, id)?;
+
+    let Some(log_path) = &record.log_path else {
+        return Err(AppError::usage(format!(
+            "run {} has no saved log",
+            record.id
+        )));
+    };
+
+    let contents = fs::read(log_path)
+        .map_err(|e| AppError::usage(format!("read log {}: {e}", output::command(log_path))))?;
+    io::stdout()
+        .write_all(&contents)
+        .map_err(|e| AppError::internal(format!("write output: {e}")))
+}
+
+fn run_history_diff(store: &Store, args: &HistoryDiffArgs) -> Result<(), AppError> {
+    let old = find_record(store, &args.old)?;
+    let new = find_record(store, &args.new)?;
+
+    let read_log = |record: &RunRecord| {
+        record
+            .log_path
+            .as_ref()
+            .and_then(|path| fs::read(path).ok())
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+    };
+    let (label, old_text, new_text) = match (read_log(&old), read_log(&new)) {
+        (Some(old_log), Some(new_log)) => ("log", old_log, new_log),
+        _ => (
+            "stderr tail",
+            old.stderr_tail.clone().unwrap_or_default(),
+            new.stderr_tail.clone().unwrap_or_default(),
+        ),
+    };
+
+    output::print_run_diff(io::stdout().lock(), &old, &new, label, &old_text, &new_text)
+        .map_err(|e| AppError::internal(format!("print diff: {e}")))
+}
+
 fn run_tasks(args: TasksArgs) -> Result<(), AppError> {
     let config_path = args
         .config
@@ -879,13 +3808,19 @@ fn run_tasks(args: TasksArgs) -> Result<(), AppError> {
         .as_ref()
         .ok_or_else(|| AppError::usage("tasks: is required"))?;
 
-    let mut names: Vec<&String> = tasks.keys().collect();
+    let mut names: Vec<&String> = tasks
+        .iter()
+        .filter(|(_, task)| !task.internal)
+        .map(|(name, _)| name)
+        .collect();
     names.sort();
 
     #[derive(Serialize)]
     struct TaskJson {
         name: String,
         #[serde(skip_serializing_if = "String::is_empty")]
+        namespace: String,
+        #[serde(skip_serializing_if = "String::is_empty")]
         description: String,
         command: String,
     }
@@ -896,18 +3831,19 @@ fn run_tasks(args: TasksArgs) -> Result<(), AppError> {
         let command = if !task.exec.is_empty() {
             task.exec.join(" ")
         } else if !task.tasks.is_empty() {
-            let mode = if task.parallel {
-                "parallel"
-            } else {
-                "sequential"
-            };
-            format!("tasks ({mode}): {}", task.tasks.join(", "))
+            config::join_task_preview(&task.tasks, task.parallel)
+        } else if !task.steps.is_empty() {
+            config::steps_preview(&task.steps)
         } else {
             task.run.clone()
         };
 
         items.push(TaskJson {
             name: name.clone(),
+            namespace: name
+                .rsplit_once(':')
+                .map(|(namespace, _)| namespace.to_string())
+                .unwrap_or_default(),
             description: task.description.clone(),
             command,
         });
@@ -948,6 +3884,8 @@ fn run_validate(args: ValidateArgs) -> Result<(), AppError> {
         #[serde(skip_serializing_if = "Option::is_none")]
         issues: Option<Vec<Issue<'a>>>,
         #[serde(skip_serializing_if = "Option::is_none")]
+        warnings: Option<Vec<Issue<'a>>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         error: Option<&'a str>,
     }
 
@@ -964,6 +3902,7 @@ fn run_validate(args: ValidateArgs) -> Result<(), AppError> {
                     valid: false,
                     config: &config_path_text,
                     issues: None,
+                    warnings: None,
                     error: Some(&err),
                 };
                 let mut stdout = io::stdout().lock();
@@ -977,21 +3916,58 @@ fn run_validate(args: ValidateArgs) -> Result<(), AppError> {
 
     match config::validate(&cfg) {
         Ok(()) => {
+            let warnings = config::lint(&cfg);
+            let strict_error = if args.strict && !warnings.is_empty() {
+                Some(format!(
+                    "configuration has {} warning(s) (--strict)",
+                    warnings.len()
+                ))
+            } else {
+                None
+            };
+
             if args.json {
                 let output = ValidateOutput {
-                    valid: true,
+                    valid: strict_error.is_none(),
                     config: &config_path_text,
                     issues: None,
-                    error: None,
+                    warnings: if warnings.is_empty() {
+                        None
+                    } else {
+                        Some(
+                            warnings
+                                .iter()
+                                .map(|warning| Issue {
+                                    field: &warning.field,
+                                    message: &warning.message,
+                                })
+                                .collect(),
+                        )
+                    },
+                    error: strict_error.as_deref(),
                 };
                 let mut stdout = io::stdout().lock();
                 serde_json::to_writer_pretty(&mut stdout, &output)
                     .map_err(|e| AppError::internal(format!("encode validate json: {e}")))?;
                 writeln!(stdout).map_err(|e| AppError::internal(format!("write output: {e}")))?;
             } else {
-                println!("valid {}", output::command(&config_path_text));
+                for warning in &warnings {
+                    eprintln!(
+                        "{} {}: {}",
+                        output::warning("warn"),
+                        warning.field,
+                        warning.message
+                    );
+                }
+                if strict_error.is_none() {
+                    println!("valid {}", output::command(&config_path_text));
+                }
+            }
+
+            match strict_error {
+                Some(err) => Err(AppError::usage(err)),
+                None => Ok(()),
             }
-            Ok(())
         }
         Err(err) => {
             if args.json {
@@ -1007,6 +3983,7 @@ fn run_validate(args: ValidateArgs) -> Result<(), AppError> {
                     valid: false,
                     config: &config_path_text,
                     issues: Some(issues),
+                    warnings: None,
                     error: Some(&err.to_string()),
                 };
                 let mut stdout = io::stdout().lock();
@@ -1019,6 +3996,40 @@ fn run_validate(args: ValidateArgs) -> Result<(), AppError> {
     }
 }
 
+fn run_migrate(args: MigrateArgs) -> Result<(), AppError> {
+    let config_path = args
+        .config
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH));
+    let output_path = args.output.unwrap_or_else(|| config_path.clone());
+
+    let text = fs::read_to_string(&config_path).map_err(|e| {
+        AppError::usage(format!(
+            "read config {}: {e}",
+            output::command(&config_path.display().to_string())
+        ))
+    })?;
+
+    let Some(migrated) = config::migrate(&text).map_err(AppError::usage)? else {
+        println!(
+            "{} {} is already at version {}",
+            output::info("i"),
+            output::command(&config_path.display().to_string()),
+            config::CURRENT_VERSION
+        );
+        return Ok(());
+    };
+
+    fs::write(&output_path, migrated)
+        .map_err(|e| AppError::internal(format!("write {}: {e}", output_path.display())))?;
+
+    println!(
+        "migrated {} to version {}",
+        output::command(&output_path.display().to_string()),
+        config::CURRENT_VERSION
+    );
+    Ok(())
+}
+
 fn compact_command(command: &str, max_chars: usize) -> String {
     let compact = command.split_whitespace().collect::<Vec<_>>().join(" ");
 
@@ -1030,6 +4041,17 @@ fn compact_command(command: &str, max_chars: usize) -> String {
     format!("{}...", compact.chars().take(limit).collect::<String>())
 }
 
+fn run_schema(args: SchemaArgs) -> Result<(), AppError> {
+    let value = match args.kind {
+        SchemaKind::Run => schema::run(),
+        SchemaKind::History => schema::history(),
+    };
+    let text = serde_json::to_string_pretty(&value)
+        .map_err(|e| AppError::internal(format!("encode schema: {e}")))?;
+    println!("{text}");
+    Ok(())
+}
+
 fn run_completion(args: CompletionArgs) -> Result<(), AppError> {
     let mut cmd = Cli::command();
     let mut stdout = io::stdout().lock();
@@ -1,18 +1,48 @@
+use crate::include;
 use crate::model::RunSource;
+use crate::ready;
+use crate::runner::{IoPriority, RestartPolicy, RetryStrategy, ShellKind, StdinMode};
+use crate::schedule::Schedule;
+use crate::smtp;
 use regex::Regex;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::LazyLock;
 use std::time::Duration;
 
-pub const CURRENT_VERSION: i32 = 1;
+pub const CURRENT_VERSION: i32 = 2;
+pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(5);
+const DEFAULT_WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_TELEGRAM_TOKEN_ENV: &str = "OTTO_TELEGRAM_TOKEN";
+const DEFAULT_SMTP_USERNAME_ENV: &str = "OTTO_SMTP_USERNAME";
+const DEFAULT_SMTP_PASSWORD_ENV: &str = "OTTO_SMTP_PASSWORD";
+const DEFAULT_NTFY_TOKEN_ENV: &str = "OTTO_NTFY_TOKEN";
+const DEFAULT_PUSHOVER_TOKEN_ENV: &str = "OTTO_PUSHOVER_TOKEN";
+const DEFAULT_PUSHOVER_RETRY: Duration = Duration::from_secs(60);
+const DEFAULT_PUSHOVER_EXPIRE: Duration = Duration::from_secs(3600);
+const DEFAULT_NTFY_PRIORITIES: &[(&str, u8)] = &[
+    ("success", 3),
+    ("failed", 4),
+    ("skipped", 2),
+    ("interrupted", 3),
+    ("timedout", 4),
+];
+const SUPPORTED_VERSIONS: &[i32] = &[1, 2];
+
+static VERSION_LINE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^version:\s*1\b").expect("valid regex"));
 
-static TASK_NAME_RE: LazyLock<Regex> =
+static MUTEX_NAME_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^[a-z0-9][a-z0-9_-]{0,62}$").expect("valid regex"));
 
+static TASK_NAME_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[a-z0-9][a-z0-9_-]*(:[a-z0-9][a-z0-9_-]*)*$").expect("valid regex")
+});
+const MAX_TASK_NAME_LEN: usize = 63;
+
 const RESERVED_NAMES: &[&str] = &[
     "init",
     "run",
@@ -21,25 +51,67 @@ const RESERVED_NAMES: &[&str] = &[
     "validate",
     "version",
     "completion",
+    "migrate",
+    "watch",
+    "schedule",
+    "ps",
+    "logs",
+    "stop",
+    "rerun",
+    "replay",
+    "schema",
 ];
 const VALID_NOTIFY_ON: &[&str] = &["never", "failure", "always"];
+const VALID_RETRY_STRATEGIES: &[&str] = &["fixed", "linear", "exponential"];
+const VALID_SHELLS: &[&str] = &["sh", "bash", "cmd", "pwsh", "powershell", "$SHELL"];
+const VALID_STDIN: &[&str] = &["inherit", "null"];
+const VALID_IO_PRIORITIES: &[&str] = &["normal", "low", "idle"];
+const VALID_OUTPUTS: &[&str] = &["stream", "quiet"];
+const VALID_GROUP_OUTPUTS: &[&str] = &["prefixed", "grouped"];
+const VALID_RESTARTS: &[&str] = &["on-failure", "always"];
+const VALID_KEEP_TMPDIR: &[&str] = &["never", "on-failure", "always"];
+const VALID_PLATFORMS: &[&str] = &["linux", "macos", "windows", "freebsd", "openbsd", "netbsd"];
+const VALID_SMTP_SECURITY: &[&str] = &["starttls", "tls", "none"];
+const VALID_HISTORY_SCOPES: &[&str] = &["project", "global"];
+const VALID_DIR_RELATIVE_TO: &[&str] = &["cwd", "config"];
+const SHADOWED_ENV_NAMES: &[&str] = &["PATH", "HOME", "PWD", "SHELL", "USER"];
+const LONG_PREVIEW_CHARS: usize = 300;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(default, deny_unknown_fields)]
 pub struct Config {
     pub version: i32,
+    pub dir_relative_to: String,
+    pub default_task: String,
+    pub include: Vec<String>,
     pub defaults: Defaults,
     pub notifications: Notifications,
+    pub history: History,
+    pub hooks: Hooks,
     pub tasks: Option<HashMap<String, Task>>,
+    #[serde(skip)]
+    pub config_dir: PathBuf,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(default, deny_unknown_fields)]
 pub struct Defaults {
+    pub shell: String,
     pub timeout: String,
+    pub deadline: String,
+    pub grace_period: String,
+    pub max_captured_output: String,
+    pub keep_tmpdir: String,
     pub retries: Option<i32>,
     pub retry_backoff: String,
+    pub retry_strategy: String,
+    pub retry_jitter: String,
+    pub retry_max_backoff: String,
+    pub retry_on_exit_codes: Vec<i32>,
+    pub retry_on_timeout: Option<bool>,
+    pub retry_when_output_matches: String,
     pub notify_on: String,
+    pub notify_min_duration: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -48,47 +120,452 @@ pub struct Notifications {
     pub desktop: Option<bool>,
     pub webhook_url: String,
     pub webhook_timeout: String,
+    pub statsd: String,
+    pub pushgateway_url: String,
+    pub metrics_prefix: String,
+    pub slack: Option<Slack>,
+    pub discord: Option<Discord>,
+    pub teams: Option<Teams>,
+    pub telegram: Option<Telegram>,
+    pub email: Option<Email>,
+    pub ntfy: Option<Ntfy>,
+    pub pushover: Option<Pushover>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Slack {
+    pub webhook_url: String,
+    pub channel: String,
+    pub mention_on_failure: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Discord {
+    pub webhook_url: String,
+    pub username: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Teams {
+    pub webhook_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Telegram {
+    pub bot_token_env: String,
+    pub chat_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Email {
+    pub server: String,
+    pub security: String,
+    pub username_env: String,
+    pub password_env: String,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Ntfy {
+    pub url: String,
+    pub token_env: String,
+    pub priorities: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Pushover {
+    pub user_key: String,
+    pub token_env: String,
+    pub failure_priority: Option<i8>,
+    pub retry: String,
+    pub expire: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Step {
+    pub name: String,
+    pub exec: Vec<String>,
+    pub run: String,
+    pub continue_on_error: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TaskRef {
+    Name(String),
+    Inline(Step),
+}
+
+impl TaskRef {
+    pub fn label(&self, parent: &str, idx: usize) -> String {
+        match self {
+            TaskRef::Name(name) => name.clone(),
+            TaskRef::Inline(step) if !step.name.is_empty() => step.name.clone(),
+            TaskRef::Inline(_) => format!("{parent}[{idx}]"),
+        }
+    }
+
+    pub fn preview(&self) -> String {
+        match self {
+            TaskRef::Name(name) => name.clone(),
+            TaskRef::Inline(step) if !step.name.is_empty() => step.name.clone(),
+            TaskRef::Inline(step) if step.exec.is_empty() => step.run.clone(),
+            TaskRef::Inline(step) => join_command_preview(&step.exec),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ShellSetting {
+    #[default]
+    Default,
+    Named(String),
+    Disabled,
+}
+
+impl ShellSetting {
+    pub fn is_default(&self) -> bool {
+        *self == ShellSetting::Default
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            ShellSetting::Named(name) => name,
+            ShellSetting::Default | ShellSetting::Disabled => "",
+        }
+    }
+}
+
+impl Serialize for ShellSetting {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ShellSetting::Default => serializer.serialize_bool(true),
+            ShellSetting::Named(name) => serializer.serialize_str(name),
+            ShellSetting::Disabled => serializer.serialize_bool(false),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ShellSetting {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Setting {
+            Enabled(bool),
+            Named(String),
+        }
+
+        Ok(match Setting::deserialize(deserializer)? {
+            Setting::Enabled(true) => ShellSetting::Default,
+            Setting::Enabled(false) => ShellSetting::Disabled,
+            Setting::Named(name) if name.is_empty() => ShellSetting::Default,
+            Setting::Named(name) => ShellSetting::Named(name),
+        })
+    }
+}
+
+impl From<&str> for TaskRef {
+    fn from(name: &str) -> Self {
+        TaskRef::Name(name.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Outputs {
+    Paths(Vec<String>),
+    Named(HashMap<String, String>),
+}
+
+impl Default for Outputs {
+    fn default() -> Self {
+        Outputs::Paths(Vec::new())
+    }
+}
+
+impl Outputs {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Outputs::Paths(paths) => paths.is_empty(),
+            Outputs::Named(named) => named.is_empty(),
+        }
+    }
+
+    pub fn patterns(&self) -> Vec<String> {
+        match self {
+            Outputs::Paths(paths) => paths.clone(),
+            Outputs::Named(named) => named.values().cloned().collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct History {
+    pub enabled: Option<bool>,
+    pub path: String,
+    pub scope: String,
+    pub max_entries: Option<usize>,
+    pub max_size: String,
+    pub max_archives: Option<usize>,
+    pub compress: Option<bool>,
+    pub hash_env: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Hooks {
+    pub pre_run: Option<Hook>,
+    pub post_run: Option<Hook>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(default, deny_unknown_fields)]
 pub struct Task {
     pub description: String,
+    pub internal: bool,
     pub exec: Vec<String>,
     pub run: String,
-    pub tasks: Vec<String>,
+    pub shell: ShellSetting,
+    pub glob: bool,
+    pub tasks: Vec<TaskRef>,
+    pub steps: Vec<Step>,
     pub parallel: bool,
+    pub max_parallel: Option<usize>,
+    pub fail_fast: bool,
+    pub continue_on_error: bool,
     pub dir: String,
     pub env: HashMap<String, String>,
+    pub inherit_env: Option<bool>,
+    pub interactive: bool,
+    pub stdin: String,
+    pub stdin_file: String,
+    pub pty: bool,
+    pub combine_output: bool,
+    pub nice: Option<i32>,
+    pub io_priority: String,
+    pub output: String,
+    pub service: bool,
+    pub restart: String,
+    pub history: Option<bool>,
+    pub platforms: Vec<String>,
+    pub inputs: Vec<String>,
+    pub outputs: Outputs,
+    pub watch: Vec<String>,
+    pub schedule: String,
+    pub mutex: String,
     pub timeout: String,
+    pub deadline: String,
+    pub grace_period: String,
+    pub max_captured_output: String,
+    pub keep_tmpdir: String,
     pub retries: Option<i32>,
     pub retry_backoff: String,
+    pub retry_strategy: String,
+    pub retry_jitter: String,
+    pub retry_max_backoff: String,
+    pub retry_on_exit_codes: Vec<i32>,
+    pub retry_on_timeout: Option<bool>,
+    pub retry_when_output_matches: String,
     pub notify_on: String,
+    pub notify_min_duration: String,
+    pub before: Option<Hook>,
+    pub after: Option<Hook>,
+    pub on_failure: Option<Hook>,
+    pub container: Option<Container>,
+    pub wait_for: Option<WaitFor>,
+    pub prompts: Vec<Prompt>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Prompt {
+    pub name: String,
+    pub message: String,
+    pub default: String,
+    pub choices: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct WaitFor {
+    pub tcp: String,
+    pub http: String,
+    pub timeout: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Container {
+    pub image: String,
+    pub volumes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Hook {
+    pub exec: Vec<String>,
+    pub run: String,
+    pub timeout: String,
 }
 
 #[derive(Debug, Clone)]
 pub struct ResolvedTask {
     pub name: String,
+    pub run_id: String,
     pub source: RunSource,
     pub command_preview: String,
-    pub sub_tasks: Vec<String>,
+    pub sub_tasks: Vec<TaskRef>,
+    pub steps: Vec<ResolvedStep>,
     pub parallel: bool,
+    pub max_parallel: usize,
+    pub fail_fast: bool,
+    pub continue_on_error: bool,
     pub use_shell: bool,
     pub exec: Vec<String>,
     pub shell: String,
+    pub shell_kind: ShellKind,
     pub dir: String,
+    pub glob: bool,
+    pub base_dir: String,
+    pub config_dir: String,
     pub env: HashMap<String, String>,
+    pub inherit_env: bool,
+    pub interactive: bool,
+    pub stdin: StdinMode,
+    pub pty: bool,
+    pub combine_output: bool,
+    pub nice: Option<i32>,
+    pub io_priority: IoPriority,
+    pub quiet: bool,
+    pub grouped_output: bool,
+    pub restart: Option<RestartPolicy>,
+    pub history: bool,
+    pub platforms: Vec<String>,
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+    pub named_outputs: HashMap<String, String>,
+    pub watch: Vec<String>,
+    pub mutex: String,
     pub timeout: Duration,
+    pub deadline: Duration,
+    pub grace_period: Duration,
+    pub max_captured_output: Option<u64>,
+    pub keep_tmpdir: String,
     pub retries: i32,
     pub retry_backoff: Duration,
+    pub retry_strategy: RetryStrategy,
+    pub retry_jitter: Duration,
+    pub retry_max_backoff: Duration,
+    pub retry_on_exit_codes: Vec<i32>,
+    pub retry_on_timeout: bool,
+    pub retry_when_output_matches: Option<Regex>,
     pub notify_on: String,
+    pub notify_min_duration: Duration,
+    pub before: Option<ResolvedHook>,
+    pub after: Option<ResolvedHook>,
+    pub on_failure: Option<ResolvedHook>,
+    pub container: Option<Container>,
+    pub wait_for: Option<ResolvedWait>,
+    pub prompts: Vec<Prompt>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolvedWait {
+    pub target: ready::Target,
+    pub timeout: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolvedStep {
+    pub name: String,
+    pub use_shell: bool,
+    pub exec: Vec<String>,
+    pub shell: String,
+    pub continue_on_error: bool,
 }
 
 #[derive(Debug, Clone)]
+pub struct ResolvedHook {
+    pub use_shell: bool,
+    pub exec: Vec<String>,
+    pub shell: String,
+    pub timeout: Duration,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GlobalHooks {
+    pub pre_run: Option<ResolvedHook>,
+    pub post_run: Option<ResolvedHook>,
+    pub shell_kind: ShellKind,
+    pub dir: String,
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct NotificationSettings {
     pub desktop_enabled: bool,
     pub webhook_url: String,
     pub webhook_timeout: Duration,
+    pub statsd: String,
+    pub pushgateway_url: String,
+    pub metrics_prefix: String,
+    pub slack: Option<Slack>,
+    pub discord: Option<Discord>,
+    pub teams: Option<Teams>,
+    pub telegram: Option<Telegram>,
+    pub email: Option<Email>,
+    pub ntfy: Option<NtfySettings>,
+    pub pushover: Option<PushoverSettings>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NtfySettings {
+    pub url: String,
+    pub token_env: String,
+    pub priorities: BTreeMap<String, u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PushoverSettings {
+    pub user_key: String,
+    pub token_env: String,
+    pub failure_priority: i8,
+    pub retry: Duration,
+    pub expire: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct HistorySettings {
+    pub enabled: bool,
+    pub path: PathBuf,
+    pub max_entries: usize,
+    pub max_size: Option<u64>,
+    pub max_archives: usize,
+    pub compress: bool,
+    pub hash_env: bool,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: PathBuf::from(crate::history::DEFAULT_PATH),
+            max_entries: 0,
+            max_size: None,
+            max_archives: crate::history::DEFAULT_MAX_ARCHIVES,
+            compress: false,
+            hash_env: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -143,19 +620,96 @@ pub fn load(path: &Path) -> Result<Config, String> {
 
 pub fn parse(path: &Path) -> Result<Config, String> {
     let text = fs::read_to_string(path).map_err(|e| format!("read config: {e}"))?;
-    let cfg: Config = serde_yaml::from_str(&text).map_err(|e| format!("parse config yaml: {e}"))?;
+    let mut value: serde_yaml::Value =
+        serde_yaml::from_str(&text).map_err(|e| format!("parse config yaml: {e}"))?;
+
+    let local_path = local_override_path(path);
+    if let Ok(local_text) = fs::read_to_string(&local_path) {
+        let overlay: serde_yaml::Value = serde_yaml::from_str(&local_text)
+            .map_err(|e| format!("parse {}: {e}", local_path.display()))?;
+        merge_yaml(&mut value, overlay);
+    }
+
+    let mut cfg: Config =
+        serde_yaml::from_value(value).map_err(|e| format!("parse config yaml: {e}"))?;
+    cfg.config_dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    for entry in cfg.include.clone() {
+        merge_include(&mut cfg, &entry).map_err(|e| format!("include {entry:?}: {e}"))?;
+    }
+
     Ok(cfg)
 }
 
+pub fn local_override_path(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{stem}.local.{}", ext.to_string_lossy()),
+        None => format!("{stem}.local"),
+    };
+    path.with_file_name(name)
+}
+
+fn merge_yaml(base: &mut serde_yaml::Value, overlay: serde_yaml::Value) {
+    match (base, overlay) {
+        (_, serde_yaml::Value::Null) => {}
+        (serde_yaml::Value::Mapping(base), serde_yaml::Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_yaml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn merge_include(cfg: &mut Config, entry: &str) -> Result<(), String> {
+    let text = include::read(entry, &cfg.config_dir)?;
+    let included: Config =
+        serde_yaml::from_str(&text).map_err(|e| format!("parse config yaml: {e}"))?;
+    if !included.include.is_empty() {
+        return Err("nested includes are not supported".to_string());
+    }
+
+    let tasks = cfg.tasks.get_or_insert_with(HashMap::new);
+    for (name, task) in included.tasks.unwrap_or_default() {
+        tasks.entry(name).or_insert(task);
+    }
+    Ok(())
+}
+
 pub fn validate(cfg: &Config) -> Result<(), ValidationErrors> {
     let mut issues = ValidationErrors::new();
 
-    if cfg.version != CURRENT_VERSION {
-        issues.add("version", format!("must be {CURRENT_VERSION}"));
+    if !SUPPORTED_VERSIONS.contains(&cfg.version) {
+        issues.add("version", "must be 1 or 2");
+    }
+
+    if !cfg.dir_relative_to.is_empty()
+        && !VALID_DIR_RELATIVE_TO.contains(&cfg.dir_relative_to.as_str())
+    {
+        issues.add("dir_relative_to", "must be one of cwd, config");
     }
 
     validate_defaults(&mut issues, &cfg.defaults);
     validate_notifications(&mut issues, &cfg.notifications);
+    validate_history(&mut issues, &cfg.history);
+    if let Some(hook) = &cfg.hooks.pre_run {
+        validate_hook(&mut issues, "hooks.pre_run", hook);
+    }
+    if let Some(hook) = &cfg.hooks.post_run {
+        validate_hook(&mut issues, "hooks.post_run", hook);
+    }
 
     match &cfg.tasks {
         None => issues.add("tasks", "is required"),
@@ -168,6 +722,13 @@ pub fn validate(cfg: &Config) -> Result<(), ValidationErrors> {
                 validate_task(&mut issues, name, task);
             }
             validate_task_dependencies(&mut issues, tasks);
+
+            if !cfg.default_task.is_empty() && !tasks.contains_key(&cfg.default_task) {
+                issues.add(
+                    "default_task",
+                    format!("references unknown task {:?}", cfg.default_task),
+                );
+            }
         }
     }
 
@@ -179,6 +740,27 @@ pub fn validate(cfg: &Config) -> Result<(), ValidationErrors> {
 }
 
 impl Config {
+    pub fn has_parallel_group(&self, name: &str) -> bool {
+        let mut pending = vec![name];
+        let mut seen = HashSet::new();
+        while let Some(name) = pending.pop() {
+            if !seen.insert(name) {
+                continue;
+            }
+            let Some(task) = self.tasks.as_ref().and_then(|tasks| tasks.get(name)) else {
+                continue;
+            };
+            if task.parallel && task.tasks.len() > 1 {
+                return true;
+            }
+            pending.extend(task.tasks.iter().filter_map(|child| match child {
+                TaskRef::Name(child) => Some(child.as_str()),
+                TaskRef::Inline(_) => None,
+            }));
+        }
+        false
+    }
+
     pub fn resolve_task(&self, name: &str) -> Result<ResolvedTask, String> {
         let tasks = self
             .tasks
@@ -189,8 +771,29 @@ impl Config {
             .get(name)
             .ok_or_else(|| format!("task {name:?} not found"))?;
 
-        let timeout = resolve_duration(&task.timeout, &self.defaults.timeout, Duration::ZERO)
+        let (default_timeout, default_deadline) = if task.service || !task.tasks.is_empty() {
+            ("", "")
+        } else {
+            (
+                self.defaults.timeout.as_str(),
+                self.defaults.deadline.as_str(),
+            )
+        };
+        let timeout = resolve_duration(&task.timeout, default_timeout, Duration::ZERO)
             .map_err(|e| format!("task {name:?} timeout: {e}"))?;
+        let deadline = resolve_duration(&task.deadline, default_deadline, Duration::ZERO)
+            .map_err(|e| format!("task {name:?} deadline: {e}"))?;
+        let grace_period = resolve_duration(
+            &task.grace_period,
+            &self.defaults.grace_period,
+            DEFAULT_GRACE_PERIOD,
+        )
+        .map_err(|e| format!("task {name:?} grace_period: {e}"))?;
+        let max_captured_output = resolve_size(
+            &task.max_captured_output,
+            &self.defaults.max_captured_output,
+        )
+        .map_err(|e| format!("task {name:?} max_captured_output: {e}"))?;
         let retries = resolve_retries(task.retries, self.defaults.retries, 0);
         let retry_backoff = resolve_duration(
             &task.retry_backoff,
@@ -198,23 +801,118 @@ impl Config {
             Duration::from_secs(1),
         )
         .map_err(|e| format!("task {name:?} retry_backoff: {e}"))?;
-        let notify_on = resolve_notify_on(&task.notify_on, &self.defaults.notify_on, "failure");
+        let retry_strategy =
+            resolve_retry_strategy(&task.retry_strategy, &self.defaults.retry_strategy);
+        let retry_jitter = resolve_duration(
+            &task.retry_jitter,
+            &self.defaults.retry_jitter,
+            Duration::ZERO,
+        )
+        .map_err(|e| format!("task {name:?} retry_jitter: {e}"))?;
+        let retry_max_backoff = resolve_duration(
+            &task.retry_max_backoff,
+            &self.defaults.retry_max_backoff,
+            Duration::ZERO,
+        )
+        .map_err(|e| format!("task {name:?} retry_max_backoff: {e}"))?;
+        let retry_on_exit_codes = if !task.retry_on_exit_codes.is_empty() {
+            task.retry_on_exit_codes.clone()
+        } else {
+            self.defaults.retry_on_exit_codes.clone()
+        };
+        let retry_on_timeout = task
+            .retry_on_timeout
+            .or(self.defaults.retry_on_timeout)
+            .unwrap_or(true);
+        let retry_when_output_matches = resolve_output_pattern(
+            &task.retry_when_output_matches,
+            &self.defaults.retry_when_output_matches,
+        )
+        .map_err(|e| format!("task {name:?} retry_when_output_matches: {e}"))?;
+        let keep_tmpdir = resolve_choice(&task.keep_tmpdir, &self.defaults.keep_tmpdir, "never");
+        let notify_on = resolve_choice(&task.notify_on, &self.defaults.notify_on, "failure");
+        let notify_min_duration = resolve_duration(
+            &task.notify_min_duration,
+            &self.defaults.notify_min_duration,
+            Duration::ZERO,
+        )
+        .map_err(|e| format!("task {name:?} notify_min_duration: {e}"))?;
+        let before = resolve_hook(task.before.as_ref(), timeout)
+            .map_err(|e| format!("task {name:?} before: {e}"))?;
+        let after = resolve_hook(task.after.as_ref(), timeout)
+            .map_err(|e| format!("task {name:?} after: {e}"))?;
+        let on_failure = resolve_hook(task.on_failure.as_ref(), timeout)
+            .map_err(|e| format!("task {name:?} on_failure: {e}"))?;
 
         let mut resolved = ResolvedTask {
             name: name.to_string(),
+            run_id: String::new(),
             source: RunSource::Task,
             command_preview: String::new(),
             sub_tasks: Vec::new(),
+            steps: Vec::new(),
             parallel: task.parallel,
+            max_parallel: task.max_parallel.unwrap_or(0),
+            fail_fast: task.fail_fast,
+            continue_on_error: task.continue_on_error,
             use_shell: false,
             exec: Vec::new(),
             shell: String::new(),
+            shell_kind: resolve_shell_kind(task.shell.name(), &self.defaults.shell),
             dir: task.dir.clone(),
+            glob: task.glob,
+            base_dir: self.task_base_dir(),
+            config_dir: self.config_dir.display().to_string(),
             env: task.env.clone(),
+            inherit_env: task.inherit_env.unwrap_or(true),
+            interactive: task.interactive,
+            stdin: resolve_stdin(&task.stdin, &task.stdin_file),
+            pty: task.pty,
+            combine_output: task.combine_output,
+            nice: task.nice,
+            io_priority: resolve_io_priority(&task.io_priority),
+            quiet: task.output == "quiet",
+            grouped_output: task.output == "grouped",
+            restart: task.service.then_some(match task.restart.as_str() {
+                "always" => RestartPolicy::Always,
+                _ => RestartPolicy::OnFailure,
+            }),
+            history: task.history.unwrap_or(true),
+            platforms: task.platforms.clone(),
+            inputs: task.inputs.clone(),
+            outputs: task.outputs.patterns(),
+            named_outputs: match &task.outputs {
+                Outputs::Named(named) => named.clone(),
+                Outputs::Paths(_) => HashMap::new(),
+            },
+            watch: task.watch.clone(),
+            mutex: task.mutex.clone(),
             timeout,
+            deadline,
+            grace_period,
+            max_captured_output,
+            keep_tmpdir,
             retries,
             retry_backoff,
+            retry_strategy,
+            retry_jitter,
+            retry_max_backoff,
+            retry_on_exit_codes,
+            retry_on_timeout,
+            retry_when_output_matches,
             notify_on,
+            notify_min_duration,
+            before,
+            after,
+            on_failure,
+            container: task.container.clone(),
+            wait_for: task
+                .wait_for
+                .as_ref()
+                .map(resolve_wait)
+                .transpose()
+                .map_err(|e| format!("task {name:?} wait_for: {e}"))?,
+            prompts: task.prompts.clone(),
         };
 
         if !task.exec.is_empty() {
@@ -224,6 +922,34 @@ impl Config {
         } else if !task.tasks.is_empty() {
             resolved.sub_tasks = task.tasks.clone();
             resolved.command_preview = join_task_preview(&task.tasks, task.parallel);
+        } else if !task.steps.is_empty() {
+            resolved.steps = task
+                .steps
+                .iter()
+                .enumerate()
+                .map(|(idx, step)| {
+                    Ok(ResolvedStep {
+                        name: if step.name.is_empty() {
+                            format!("step-{}", idx + 1)
+                        } else {
+                            step.name.clone()
+                        },
+                        use_shell: step.exec.is_empty() && task.shell != ShellSetting::Disabled,
+                        exec: if step.exec.is_empty() && task.shell == ShellSetting::Disabled {
+                            split_run(&step.run)?
+                        } else {
+                            step.exec.clone()
+                        },
+                        shell: step.run.clone(),
+                        continue_on_error: step.continue_on_error,
+                    })
+                })
+                .collect::<Result<_, String>>()
+                .map_err(|e| format!("task {name:?} steps: {e}"))?;
+            resolved.command_preview = steps_preview(&task.steps);
+        } else if task.shell == ShellSetting::Disabled {
+            resolved.exec = split_run(&task.run).map_err(|e| format!("task {name:?} run: {e}"))?;
+            resolved.command_preview = task.run.clone();
         } else {
             resolved.use_shell = true;
             resolved.shell = task.run.clone();
@@ -241,13 +967,153 @@ impl Config {
             Duration::from_secs(5),
         )
         .map_err(|e| format!("notifications.webhook_timeout: {e}"))?;
+        let pushover = match self.notifications.pushover.clone() {
+            Some(pushover) => Some(PushoverSettings {
+                retry: resolve_duration(&pushover.retry, "", DEFAULT_PUSHOVER_RETRY)
+                    .map_err(|e| format!("notifications.pushover.retry: {e}"))?,
+                expire: resolve_duration(&pushover.expire, "", DEFAULT_PUSHOVER_EXPIRE)
+                    .map_err(|e| format!("notifications.pushover.expire: {e}"))?,
+                user_key: pushover.user_key,
+                token_env: or_default(pushover.token_env, DEFAULT_PUSHOVER_TOKEN_ENV),
+                failure_priority: pushover.failure_priority.unwrap_or(1),
+            }),
+            None => None,
+        };
 
         Ok(NotificationSettings {
             desktop_enabled,
             webhook_url: self.notifications.webhook_url.clone(),
             webhook_timeout,
+            statsd: self.notifications.statsd.clone(),
+            pushgateway_url: self.notifications.pushgateway_url.clone(),
+            metrics_prefix: if self.notifications.metrics_prefix.is_empty() {
+                "otto".to_string()
+            } else {
+                self.notifications.metrics_prefix.clone()
+            },
+            slack: self.notifications.slack.clone(),
+            discord: self.notifications.discord.clone(),
+            teams: self.notifications.teams.clone(),
+            telegram: self
+                .notifications
+                .telegram
+                .clone()
+                .map(|telegram| Telegram {
+                    bot_token_env: or_default(telegram.bot_token_env, DEFAULT_TELEGRAM_TOKEN_ENV),
+                    chat_id: telegram.chat_id,
+                }),
+            email: self.notifications.email.clone().map(|email| Email {
+                security: match email.security.as_str() {
+                    "" if email.server.ends_with(":465") => "tls".to_string(),
+                    "" => "starttls".to_string(),
+                    _ => email.security,
+                },
+                username_env: or_default(email.username_env, DEFAULT_SMTP_USERNAME_ENV),
+                password_env: or_default(email.password_env, DEFAULT_SMTP_PASSWORD_ENV),
+                ..email
+            }),
+            ntfy: self.notifications.ntfy.clone().map(|ntfy| {
+                let mut priorities: BTreeMap<String, u8> = DEFAULT_NTFY_PRIORITIES
+                    .iter()
+                    .map(|(status, priority)| (status.to_string(), *priority))
+                    .collect();
+                for (status, priority) in &ntfy.priorities {
+                    if let Some(priority) = ntfy_priority(priority) {
+                        priorities.insert(status.clone(), priority);
+                    }
+                }
+                NtfySettings {
+                    url: ntfy.url,
+                    token_env: or_default(ntfy.token_env, DEFAULT_NTFY_TOKEN_ENV),
+                    priorities,
+                }
+            }),
+            pushover,
+        })
+    }
+
+    pub fn resolve_history_settings(&self) -> HistorySettings {
+        let mut settings = HistorySettings {
+            enabled: self.history.enabled.unwrap_or(true),
+            max_entries: self.history.max_entries.unwrap_or(0),
+            max_size: resolve_size(&self.history.max_size, "").unwrap_or_default(),
+            max_archives: self
+                .history
+                .max_archives
+                .unwrap_or(crate::history::DEFAULT_MAX_ARCHIVES),
+            compress: self.history.compress.unwrap_or(false),
+            hash_env: self.history.hash_env.unwrap_or(false),
+            ..HistorySettings::default()
+        };
+        if !self.history.path.is_empty() {
+            settings.path = PathBuf::from(&self.history.path);
+        } else if self.history.scope == "global"
+            && let Some(path) = crate::history::global_path()
+        {
+            settings.path = path;
+        }
+        settings
+    }
+
+    pub fn task_base_dir(&self) -> String {
+        let relative_to = if self.dir_relative_to.is_empty() {
+            if self.version >= 2 { "config" } else { "cwd" }
+        } else {
+            self.dir_relative_to.as_str()
+        };
+
+        if relative_to == "config" {
+            self.config_dir.display().to_string()
+        } else {
+            String::new()
+        }
+    }
+
+    pub fn match_tasks(&self, pattern: &str) -> Result<Vec<String>, String> {
+        let matcher =
+            glob::Pattern::new(pattern).map_err(|e| format!("invalid task pattern: {e}"))?;
+        let mut names: Vec<String> = self
+            .tasks
+            .iter()
+            .flatten()
+            .filter(|(name, task)| !task.internal && matcher.matches(name))
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    pub fn resolve_global_hooks(&self) -> Result<GlobalHooks, String> {
+        let timeout = resolve_duration(&self.defaults.timeout, "", Duration::ZERO)
+            .map_err(|e| format!("defaults.timeout: {e}"))?;
+
+        Ok(GlobalHooks {
+            pre_run: resolve_hook(self.hooks.pre_run.as_ref(), timeout)
+                .map_err(|e| format!("hooks.pre_run: {e}"))?,
+            post_run: resolve_hook(self.hooks.post_run.as_ref(), timeout)
+                .map_err(|e| format!("hooks.post_run: {e}"))?,
+            shell_kind: resolve_shell_kind("", &self.defaults.shell),
+            dir: self.task_base_dir(),
         })
     }
+
+    pub fn resolve_inline_ref(&self, label: &str, step: &Step) -> Result<ResolvedTask, String> {
+        let command = if step.exec.is_empty() {
+            vec![step.run.clone()]
+        } else {
+            step.exec.clone()
+        };
+        let mut resolved = resolve_inline(&command, label, "", None, "", &self.defaults)?;
+        resolved.source = RunSource::Task;
+        resolved.base_dir = self.task_base_dir();
+        resolved.config_dir = self.config_dir.display().to_string();
+        if step.exec.is_empty() {
+            resolved.use_shell = true;
+            resolved.exec.clear();
+            resolved.shell = step.run.clone();
+        }
+        Ok(resolved)
+    }
 }
 
 pub fn resolve_inline(
@@ -264,6 +1130,12 @@ pub fn resolve_inline(
 
     let timeout = resolve_duration(timeout_flag, &defaults.timeout, Duration::ZERO)
         .map_err(|e| format!("inline timeout: {e}"))?;
+    let deadline = resolve_duration("", &defaults.deadline, Duration::ZERO)
+        .map_err(|e| format!("inline deadline: {e}"))?;
+    let grace_period = resolve_duration("", &defaults.grace_period, DEFAULT_GRACE_PERIOD)
+        .map_err(|e| format!("inline grace_period: {e}"))?;
+    let max_captured_output = resolve_size("", &defaults.max_captured_output)
+        .map_err(|e| format!("inline max_captured_output: {e}"))?;
 
     let retries = match retries_flag {
         Some(v) => v,
@@ -276,8 +1148,15 @@ pub fn resolve_inline(
 
     let retry_backoff = resolve_duration("", &defaults.retry_backoff, Duration::from_secs(1))
         .map_err(|e| format!("inline retry_backoff: {e}"))?;
-
-    let notify_on = resolve_notify_on(notify_on_flag, &defaults.notify_on, "failure");
+    let retry_strategy = resolve_retry_strategy("", &defaults.retry_strategy);
+    let retry_jitter = resolve_duration("", &defaults.retry_jitter, Duration::ZERO)
+        .map_err(|e| format!("inline retry_jitter: {e}"))?;
+    let retry_max_backoff = resolve_duration("", &defaults.retry_max_backoff, Duration::ZERO)
+        .map_err(|e| format!("inline retry_max_backoff: {e}"))?;
+
+    let notify_on = resolve_choice(notify_on_flag, &defaults.notify_on, "failure");
+    let notify_min_duration = resolve_duration("", &defaults.notify_min_duration, Duration::ZERO)
+        .map_err(|e| format!("inline notify_min_duration: {e}"))?;
     let task_name = if name.trim().is_empty() {
         "inline".to_string()
     } else {
@@ -286,60 +1165,422 @@ pub fn resolve_inline(
 
     Ok(ResolvedTask {
         name: task_name,
+        run_id: String::new(),
         source: RunSource::Inline,
         command_preview: join_command_preview(args),
         sub_tasks: Vec::new(),
+        steps: Vec::new(),
         parallel: false,
+        max_parallel: 0,
+        fail_fast: false,
+        continue_on_error: false,
         use_shell: false,
         exec: args.to_vec(),
         shell: String::new(),
+        shell_kind: resolve_shell_kind("", &defaults.shell),
         dir: String::new(),
+        glob: false,
+        base_dir: String::new(),
+        config_dir: String::new(),
         env: HashMap::new(),
+        inherit_env: true,
+        interactive: false,
+        stdin: StdinMode::Inherit,
+        pty: false,
+        combine_output: false,
+        nice: None,
+        io_priority: IoPriority::Normal,
+        quiet: false,
+        grouped_output: false,
+        restart: None,
+        history: true,
+        platforms: Vec::new(),
+        inputs: Vec::new(),
+        outputs: Vec::new(),
+        named_outputs: HashMap::new(),
+        watch: Vec::new(),
+        mutex: String::new(),
         timeout,
+        deadline,
+        grace_period,
+        max_captured_output,
+        keep_tmpdir: resolve_choice("", &defaults.keep_tmpdir, "never"),
         retries,
         retry_backoff,
+        retry_strategy,
+        retry_jitter,
+        retry_max_backoff,
+        retry_on_exit_codes: defaults.retry_on_exit_codes.clone(),
+        retry_on_timeout: defaults.retry_on_timeout.unwrap_or(true),
+        retry_when_output_matches: resolve_output_pattern("", &defaults.retry_when_output_matches)
+            .map_err(|e| format!("inline retry_when_output_matches: {e}"))?,
         notify_on,
+        notify_min_duration,
+        before: None,
+        after: None,
+        on_failure: None,
+        container: None,
+        wait_for: None,
+        prompts: Vec::new(),
     })
 }
 
-fn validate_defaults(issues: &mut ValidationErrors, d: &Defaults) {
-    if !d.timeout.is_empty() && parse_duration(&d.timeout).is_err() {
-        issues.add("defaults.timeout", "must be a valid duration");
-    }
+pub fn lint(cfg: &Config) -> Vec<ValidationError> {
+    let mut warnings = ValidationErrors::new();
+    let Some(tasks) = &cfg.tasks else {
+        return warnings.issues;
+    };
 
-    if let Some(retries) = d.retries
-        && !(0..=10).contains(&retries)
-    {
-        issues.add("defaults.retries", "must be between 0 and 10");
-    }
+    let mut names: Vec<&String> = tasks.keys().collect();
+    names.sort();
+
+    for name in names {
+        let task = &tasks[name];
+        let field = format!("tasks.{name}");
+
+        if task.internal
+            && !tasks.values().any(|other| {
+                other
+                    .tasks
+                    .iter()
+                    .any(|entry| matches!(entry, TaskRef::Name(dep) if dep == name))
+            })
+        {
+            warnings.add(
+                field.clone(),
+                "internal task is not referenced by any other task",
+            );
+        }
 
-    if !d.retry_backoff.is_empty() && parse_duration(&d.retry_backoff).is_err() {
-        issues.add("defaults.retry_backoff", "must be a valid duration");
-    }
+        if !task.internal && task.description.trim().is_empty() {
+            warnings.add(format!("{field}.description"), "is missing");
+        }
 
-    if !d.notify_on.is_empty() && !VALID_NOTIFY_ON.contains(&d.notify_on.as_str()) {
-        issues.add(
-            "defaults.notify_on",
-            "must be one of never, failure, always",
-        );
+        let mut env_keys: Vec<&String> = task.env.keys().collect();
+        env_keys.sort();
+        for key in env_keys {
+            if SHADOWED_ENV_NAMES.contains(&key.as_str()) || key.starts_with("OTTO_") {
+                warnings.add(
+                    format!("{field}.env.{key}"),
+                    "shadows a variable provided by the system or otto",
+                );
+            }
+        }
+
+        let preview = if !task.exec.is_empty() {
+            join_command_preview(&task.exec)
+        } else {
+            task.run.clone()
+        };
+        if preview.chars().count() > LONG_PREVIEW_CHARS {
+            warnings.add(
+                field,
+                format!(
+                    "command is longer than {LONG_PREVIEW_CHARS} characters; consider moving it to a script"
+                ),
+            );
+        }
     }
+
+    warnings.issues
 }
 
-fn validate_notifications(issues: &mut ValidationErrors, n: &Notifications) {
-    if !n.webhook_url.is_empty() && reqwest::Url::parse(&n.webhook_url).is_err() {
-        issues.add("notifications.webhook_url", "must be a valid URL");
+pub fn migrate(text: &str) -> Result<Option<String>, String> {
+    let cfg: Config = serde_yaml::from_str(text).map_err(|e| format!("parse config yaml: {e}"))?;
+
+    if cfg.version == CURRENT_VERSION {
+        return Ok(None);
+    }
+
+    if cfg.version != 1 {
+        return Err(format!("cannot migrate config version {}", cfg.version));
+    }
+
+    let mut out = String::with_capacity(text.len() + 32);
+    let mut replaced = false;
+
+    for line in text.split_inclusive('\n') {
+        if !replaced && VERSION_LINE_RE.is_match(line) {
+            let rest = VERSION_LINE_RE.replace(line, "version: 2");
+            out.push_str(&rest);
+            if !rest.ends_with('\n') {
+                out.push('\n');
+            }
+            if cfg.dir_relative_to.is_empty() {
+                out.push_str("dir_relative_to: cwd # keeps v1 dir resolution\n");
+            }
+            replaced = true;
+            continue;
+        }
+        out.push_str(line);
+    }
+
+    if !replaced {
+        return Err("could not find top-level version: 1 line".to_string());
+    }
+
+    let migrated: Config =
+        serde_yaml::from_str(&out).map_err(|e| format!("parse migrated config: {e}"))?;
+    validate(&migrated).map_err(|e| e.to_string())?;
+
+    Ok(Some(out))
+}
+
+fn validate_defaults(issues: &mut ValidationErrors, d: &Defaults) {
+    if !d.timeout.is_empty() && parse_duration(&d.timeout).is_err() {
+        issues.add("defaults.timeout", "must be a valid duration");
+    }
+
+    if !d.deadline.is_empty() && parse_duration(&d.deadline).is_err() {
+        issues.add("defaults.deadline", "must be a valid duration");
+    }
+
+    if !d.grace_period.is_empty() && parse_duration(&d.grace_period).is_err() {
+        issues.add("defaults.grace_period", "must be a valid duration");
+    }
+
+    if !d.max_captured_output.is_empty() && parse_size(&d.max_captured_output).is_err() {
+        issues.add("defaults.max_captured_output", "must be a valid size");
+    }
+
+    if let Some(retries) = d.retries
+        && !(0..=10).contains(&retries)
+    {
+        issues.add("defaults.retries", "must be between 0 and 10");
+    }
+
+    if !d.retry_backoff.is_empty() && parse_duration(&d.retry_backoff).is_err() {
+        issues.add("defaults.retry_backoff", "must be a valid duration");
+    }
+
+    validate_retry_policy(
+        issues,
+        "defaults",
+        &d.retry_strategy,
+        &d.retry_jitter,
+        &d.retry_max_backoff,
+        &d.retry_on_exit_codes,
+        &d.retry_when_output_matches,
+    );
+
+    if !d.shell.is_empty() && !VALID_SHELLS.contains(&d.shell.as_str()) {
+        issues.add(
+            "defaults.shell",
+            format!("must be one of {}", VALID_SHELLS.join(", ")),
+        );
+    }
+
+    if !d.keep_tmpdir.is_empty() && !VALID_KEEP_TMPDIR.contains(&d.keep_tmpdir.as_str()) {
+        issues.add(
+            "defaults.keep_tmpdir",
+            "must be one of never, on-failure, always",
+        );
+    }
+
+    if !d.notify_on.is_empty() && !VALID_NOTIFY_ON.contains(&d.notify_on.as_str()) {
+        issues.add(
+            "defaults.notify_on",
+            "must be one of never, failure, always",
+        );
+    }
+
+    if !d.notify_min_duration.is_empty() && parse_duration(&d.notify_min_duration).is_err() {
+        issues.add("defaults.notify_min_duration", "must be a valid duration");
+    }
+}
+
+fn validate_notifications(issues: &mut ValidationErrors, n: &Notifications) {
+    if !n.webhook_url.is_empty() && reqwest::Url::parse(&n.webhook_url).is_err() {
+        issues.add("notifications.webhook_url", "must be a valid URL");
     }
 
     if !n.webhook_timeout.is_empty() && parse_duration(&n.webhook_timeout).is_err() {
         issues.add("notifications.webhook_timeout", "must be a valid duration");
     }
+
+    if !n.statsd.is_empty()
+        && !n
+            .statsd
+            .rsplit_once(':')
+            .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok())
+    {
+        issues.add("notifications.statsd", "must be host:port");
+    }
+
+    if !n.pushgateway_url.is_empty() && reqwest::Url::parse(&n.pushgateway_url).is_err() {
+        issues.add("notifications.pushgateway_url", "must be a valid URL");
+    }
+
+    if let Some(slack) = &n.slack {
+        if slack.webhook_url.is_empty() {
+            issues.add("notifications.slack.webhook_url", "is required");
+        } else if reqwest::Url::parse(&slack.webhook_url).is_err() {
+            issues.add("notifications.slack.webhook_url", "must be a valid URL");
+        }
+    }
+
+    if let Some(discord) = &n.discord {
+        if discord.webhook_url.is_empty() {
+            issues.add("notifications.discord.webhook_url", "is required");
+        } else if reqwest::Url::parse(&discord.webhook_url).is_err() {
+            issues.add("notifications.discord.webhook_url", "must be a valid URL");
+        }
+    }
+
+    if let Some(teams) = &n.teams {
+        if teams.webhook_url.is_empty() {
+            issues.add("notifications.teams.webhook_url", "is required");
+        } else if reqwest::Url::parse(&teams.webhook_url).is_err() {
+            issues.add("notifications.teams.webhook_url", "must be a valid URL");
+        }
+    }
+
+    if let Some(telegram) = &n.telegram {
+        if telegram.chat_id.trim().is_empty() {
+            issues.add("notifications.telegram.chat_id", "is required");
+        }
+        if !telegram.bot_token_env.is_empty() && !is_env_name(&telegram.bot_token_env) {
+            issues.add(
+                "notifications.telegram.bot_token_env",
+                "must be an environment variable name",
+            );
+        }
+    }
+
+    if let Some(email) = &n.email {
+        if !email
+            .server
+            .rsplit_once(':')
+            .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok())
+        {
+            issues.add("notifications.email.server", "must be host:port");
+        }
+        if !email.security.is_empty() && !VALID_SMTP_SECURITY.contains(&email.security.as_str()) {
+            issues.add(
+                "notifications.email.security",
+                "must be one of starttls, tls, none",
+            );
+        }
+        for (field, name) in [
+            ("notifications.email.username_env", &email.username_env),
+            ("notifications.email.password_env", &email.password_env),
+        ] {
+            if !name.is_empty() && !is_env_name(name) {
+                issues.add(field, "must be an environment variable name");
+            }
+        }
+        if let Err(err) = smtp::address(&email.from) {
+            issues.add("notifications.email.from", err);
+        }
+        if email.to.is_empty() {
+            issues.add("notifications.email.to", "must list at least one recipient");
+        }
+        for to in &email.to {
+            if let Err(err) = smtp::recipient(to) {
+                issues.add("notifications.email.to", err);
+            }
+        }
+    }
+
+    if let Some(ntfy) = &n.ntfy {
+        match reqwest::Url::parse(&ntfy.url) {
+            Ok(url) if url.path().trim_matches('/').is_empty() => {
+                issues.add("notifications.ntfy.url", "must include the topic");
+            }
+            Ok(_) => {}
+            Err(_) => issues.add("notifications.ntfy.url", "must be a valid URL"),
+        }
+        if !ntfy.token_env.is_empty() && !is_env_name(&ntfy.token_env) {
+            issues.add(
+                "notifications.ntfy.token_env",
+                "must be an environment variable name",
+            );
+        }
+        for (status, priority) in &ntfy.priorities {
+            let field = format!("notifications.ntfy.priorities.{status}");
+            if !DEFAULT_NTFY_PRIORITIES
+                .iter()
+                .any(|(known, _)| known == status)
+            {
+                issues.add(
+                    field,
+                    "must be one of success, failed, skipped, interrupted, timedout",
+                );
+            } else if ntfy_priority(priority).is_none() {
+                issues.add(field, "must be min, low, default, high, urgent, or 1-5");
+            }
+        }
+    }
+
+    if let Some(pushover) = &n.pushover {
+        if pushover.user_key.trim().is_empty() {
+            issues.add("notifications.pushover.user_key", "is required");
+        }
+        if !pushover.token_env.is_empty() && !is_env_name(&pushover.token_env) {
+            issues.add(
+                "notifications.pushover.token_env",
+                "must be an environment variable name",
+            );
+        }
+        if pushover
+            .failure_priority
+            .is_some_and(|priority| !(-2..=2).contains(&priority))
+        {
+            issues.add(
+                "notifications.pushover.failure_priority",
+                "must be between -2 and 2",
+            );
+        }
+        if !pushover.retry.is_empty() {
+            match parse_duration(&pushover.retry) {
+                Ok(retry) if retry < Duration::from_secs(30) => {
+                    issues.add("notifications.pushover.retry", "must be at least 30s");
+                }
+                Ok(_) => {}
+                Err(_) => issues.add("notifications.pushover.retry", "must be a valid duration"),
+            }
+        }
+        if !pushover.expire.is_empty() {
+            match parse_duration(&pushover.expire) {
+                Ok(expire) if expire > Duration::from_secs(3 * 3600) => {
+                    issues.add("notifications.pushover.expire", "must be at most 3h");
+                }
+                Ok(_) => {}
+                Err(_) => issues.add("notifications.pushover.expire", "must be a valid duration"),
+            }
+        }
+    }
+}
+
+fn validate_history(issues: &mut ValidationErrors, h: &History) {
+    if !h.path.is_empty() && h.path.trim().is_empty() {
+        issues.add("history.path", "must not be blank");
+    }
+
+    if !h.scope.is_empty() && !VALID_HISTORY_SCOPES.contains(&h.scope.as_str()) {
+        issues.add("history.scope", "must be one of project, global");
+    } else if h.scope == "global" && !h.path.is_empty() {
+        issues.add("history.scope", "cannot be combined with history.path");
+    }
+
+    if h.max_entries == Some(0) {
+        issues.add("history.max_entries", "must be greater than 0");
+    }
+
+    if !h.max_size.is_empty() && parse_size(&h.max_size).is_err() {
+        issues.add("history.max_size", "must be a valid size");
+    }
+
+    if h.max_archives == Some(0) {
+        issues.add("history.max_archives", "must be greater than 0");
+    }
 }
 
 fn validate_task_name(issues: &mut ValidationErrors, name: &str) {
-    if !TASK_NAME_RE.is_match(name) {
+    if !TASK_NAME_RE.is_match(name) || name.len() > MAX_TASK_NAME_LEN {
         issues.add(
             format!("tasks.{name}"),
-            "name must match ^[a-z0-9][a-z0-9_-]{0,62}$",
+            "name must be up to 63 chars of [a-z0-9_-] segments separated by ':'",
         );
     }
 
@@ -353,7 +1594,8 @@ fn validate_task(issues: &mut ValidationErrors, name: &str, task: &Task) {
     let has_exec = !task.exec.is_empty();
     let has_run = !task.run.is_empty();
     let has_tasks = !task.tasks.is_empty();
-    let mode_count = [has_exec, has_run, has_tasks]
+    let has_steps = !task.steps.is_empty();
+    let mode_count = [has_exec, has_run, has_tasks, has_steps]
         .into_iter()
         .filter(|mode| *mode)
         .count();
@@ -361,10 +1603,19 @@ fn validate_task(issues: &mut ValidationErrors, name: &str, task: &Task) {
     if mode_count != 1 {
         issues.add(
             field.clone(),
-            "must define exactly one of exec, run, or tasks",
+            "must define exactly one of exec, run, tasks, or steps",
         );
     }
 
+    for (idx, step) in task.steps.iter().enumerate() {
+        if step.exec.is_empty() == step.run.is_empty() {
+            issues.add(
+                format!("{field}.steps[{idx}]"),
+                "must define exactly one of exec or run",
+            );
+        }
+    }
+
     if has_exec {
         for (idx, tok) in task.exec.iter().enumerate() {
             if tok.is_empty() {
@@ -373,10 +1624,295 @@ fn validate_task(issues: &mut ValidationErrors, name: &str, task: &Task) {
         }
     }
 
+    for (idx, platform) in task.platforms.iter().enumerate() {
+        if !VALID_PLATFORMS.contains(&platform.as_str()) {
+            issues.add(
+                format!("{field}.platforms[{idx}]"),
+                format!("must be one of {}", VALID_PLATFORMS.join(", ")),
+            );
+        }
+    }
+
+    if !task.stdin.is_empty() {
+        if !VALID_STDIN.contains(&task.stdin.as_str()) {
+            issues.add(
+                format!("{field}.stdin"),
+                format!(
+                    "must be one of {}; use stdin_file to read a file",
+                    VALID_STDIN.join(", ")
+                ),
+            );
+        } else if task.interactive && task.stdin != "inherit" {
+            issues.add(
+                format!("{field}.stdin"),
+                "must be inherit for interactive tasks",
+            );
+        }
+    }
+
+    if !task.stdin_file.is_empty() {
+        if !task.stdin.is_empty() {
+            issues.add(
+                format!("{field}.stdin_file"),
+                "cannot be combined with stdin",
+            );
+        } else if task.stdin_file.trim().is_empty() {
+            issues.add(format!("{field}.stdin_file"), "must not be blank");
+        } else if task.interactive {
+            issues.add(
+                format!("{field}.stdin_file"),
+                "is not supported for interactive tasks",
+            );
+        }
+    }
+
+    if !task.shell.is_default() {
+        if let ShellSetting::Named(name) = &task.shell
+            && !VALID_SHELLS.contains(&name.as_str())
+        {
+            issues.add(
+                format!("{field}.shell"),
+                format!("must be false or one of {}", VALID_SHELLS.join(", ")),
+            );
+        } else if has_exec {
+            issues.add(format!("{field}.shell"), "requires run or steps");
+        } else if task.shell == ShellSetting::Disabled {
+            let runs = std::iter::once(&task.run).chain(task.steps.iter().map(|step| &step.run));
+            if runs
+                .filter(|run| !run.is_empty())
+                .any(|run| split_run(run).is_err())
+            {
+                issues.add(
+                    format!("{field}.run"),
+                    "cannot be split into arguments without a shell; check quoting",
+                );
+            }
+        }
+    }
+
+    if task.glob
+        && !has_exec
+        && task.shell != ShellSetting::Disabled
+        && task.steps.iter().all(|step| step.exec.is_empty())
+    {
+        issues.add(format!("{field}.glob"), "requires exec or shell: false");
+    }
+
+    if let Some(nice) = task.nice
+        && !(-20..=19).contains(&nice)
+    {
+        issues.add(format!("{field}.nice"), "must be between -20 and 19");
+    }
+
+    if !task.io_priority.is_empty() && !VALID_IO_PRIORITIES.contains(&task.io_priority.as_str()) {
+        issues.add(
+            format!("{field}.io_priority"),
+            "must be one of normal, low, idle",
+        );
+    }
+
+    if !task.output.is_empty() && has_tasks {
+        if !VALID_GROUP_OUTPUTS.contains(&task.output.as_str()) {
+            issues.add(
+                format!("{field}.output"),
+                "must be one of prefixed, grouped for task groups",
+            );
+        } else if !task.parallel {
+            issues.add(
+                format!("{field}.output"),
+                "requires tasks with parallel: true",
+            );
+        }
+    } else if !task.output.is_empty() {
+        if !VALID_OUTPUTS.contains(&task.output.as_str()) {
+            issues.add(format!("{field}.output"), "must be one of stream, quiet");
+        } else if task.output == "quiet" && task.interactive {
+            issues.add(
+                format!("{field}.output"),
+                "quiet is not supported for interactive tasks",
+            );
+        }
+    }
+
+    if let Some(container) = &task.container {
+        if container.image.trim().is_empty() {
+            issues.add(format!("{field}.container.image"), "is required");
+        }
+        for (idx, volume) in container.volumes.iter().enumerate() {
+            if volume.trim().is_empty() {
+                issues.add(
+                    format!("{field}.container.volumes[{idx}]"),
+                    "must not be empty",
+                );
+            }
+        }
+    }
+
+    if task.pty && task.interactive {
+        issues.add(
+            format!("{field}.pty"),
+            "is not supported for interactive tasks",
+        );
+    }
+
+    if task.combine_output && task.interactive {
+        issues.add(
+            format!("{field}.combine_output"),
+            "is not supported for interactive tasks",
+        );
+    }
+
+    match &task.outputs {
+        Outputs::Paths(patterns) => {
+            for (idx, pattern) in patterns.iter().enumerate() {
+                if pattern.trim().is_empty() {
+                    issues.add(format!("{field}.outputs[{idx}]"), "must not be empty");
+                } else if glob::Pattern::new(pattern).is_err() {
+                    issues.add(format!("{field}.outputs[{idx}]"), "must be a valid glob");
+                }
+            }
+        }
+        Outputs::Named(named) => {
+            for (key, path) in named {
+                if !is_env_name(key) {
+                    issues.add(
+                        format!("{field}.outputs.{key}"),
+                        "name must contain only letters, digits, and underscores",
+                    );
+                } else if path.trim().is_empty() {
+                    issues.add(format!("{field}.outputs.{key}"), "must not be empty");
+                }
+            }
+        }
+    }
+
+    for (key, patterns) in [("inputs", &task.inputs), ("watch", &task.watch)] {
+        for (idx, pattern) in patterns.iter().enumerate() {
+            if pattern.trim().is_empty() {
+                issues.add(format!("{field}.{key}[{idx}]"), "must not be empty");
+            } else if glob::Pattern::new(pattern).is_err() {
+                issues.add(format!("{field}.{key}[{idx}]"), "must be a valid glob");
+            }
+        }
+    }
+
+    if let Some(max_parallel) = task.max_parallel {
+        if max_parallel == 0 {
+            issues.add(format!("{field}.max_parallel"), "must be at least 1");
+        }
+        if !(has_tasks && task.parallel) {
+            issues.add(
+                format!("{field}.max_parallel"),
+                "requires tasks with parallel: true",
+            );
+        }
+    }
+
+    if let Some(wait) = &task.wait_for {
+        let field = format!("{field}.wait_for");
+        if wait.tcp.is_empty() == wait.http.is_empty() {
+            issues.add(field.clone(), "must set exactly one of tcp or http");
+        }
+        if !wait.tcp.is_empty()
+            && wait
+                .tcp
+                .rsplit_once(':')
+                .is_none_or(|(_, port)| port.parse::<u16>().is_err())
+        {
+            issues.add(format!("{field}.tcp"), "must be host:port");
+        }
+        if !wait.http.is_empty()
+            && !reqwest::Url::parse(&wait.http)
+                .is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+        {
+            issues.add(format!("{field}.http"), "must be an http or https URL");
+        }
+        if !wait.timeout.is_empty() && parse_duration(&wait.timeout).is_err() {
+            issues.add(format!("{field}.timeout"), "must be a valid duration");
+        }
+    }
+
+    let mut prompt_names = HashSet::new();
+    for (idx, prompt) in task.prompts.iter().enumerate() {
+        let field = format!("{field}.prompts[{idx}]");
+        if !is_env_name(&prompt.name) {
+            issues.add(
+                format!("{field}.name"),
+                "must be a valid environment variable name",
+            );
+        } else if !prompt_names.insert(prompt.name.as_str()) {
+            issues.add(format!("{field}.name"), "must be unique within the task");
+        }
+        if prompt.choices.iter().any(String::is_empty) {
+            issues.add(format!("{field}.choices"), "must not contain empty values");
+        }
+        if !prompt.default.is_empty()
+            && !prompt.choices.is_empty()
+            && !prompt.choices.contains(&prompt.default)
+        {
+            issues.add(format!("{field}.default"), "must be one of choices");
+        }
+    }
+
+    if task.service && has_steps {
+        issues.add(format!("{field}.service"), "requires exec or run");
+    }
+
+    if !task.restart.is_empty() {
+        if !task.service {
+            issues.add(format!("{field}.restart"), "requires service: true");
+        } else if !VALID_RESTARTS.contains(&task.restart.as_str()) {
+            issues.add(
+                format!("{field}.restart"),
+                "must be one of on-failure, always",
+            );
+        }
+    }
+
+    if task.fail_fast && !(has_tasks && task.parallel) {
+        issues.add(
+            format!("{field}.fail_fast"),
+            "requires tasks with parallel: true",
+        );
+    }
+
+    if task.inputs.is_empty() && matches!(&task.outputs, Outputs::Paths(paths) if !paths.is_empty())
+    {
+        issues.add(format!("{field}.outputs"), "requires inputs");
+    }
+
+    if !task.schedule.is_empty()
+        && let Err(err) = Schedule::parse(&task.schedule)
+    {
+        issues.add(format!("{field}.schedule"), err);
+    }
+
+    if !task.mutex.is_empty() && !MUTEX_NAME_RE.is_match(&task.mutex) {
+        issues.add(
+            format!("{field}.mutex"),
+            "must match ^[a-z0-9][a-z0-9_-]{0,62}$",
+        );
+    }
+
     if !task.timeout.is_empty() && parse_duration(&task.timeout).is_err() {
         issues.add(format!("{field}.timeout"), "must be a valid duration");
     }
 
+    if !task.deadline.is_empty() && parse_duration(&task.deadline).is_err() {
+        issues.add(format!("{field}.deadline"), "must be a valid duration");
+    }
+
+    if !task.grace_period.is_empty() && parse_duration(&task.grace_period).is_err() {
+        issues.add(format!("{field}.grace_period"), "must be a valid duration");
+    }
+
+    if !task.max_captured_output.is_empty() && parse_size(&task.max_captured_output).is_err() {
+        issues.add(
+            format!("{field}.max_captured_output"),
+            "must be a valid size",
+        );
+    }
+
     if let Some(retries) = task.retries
         && !(0..=10).contains(&retries)
     {
@@ -387,6 +1923,23 @@ fn validate_task(issues: &mut ValidationErrors, name: &str, task: &Task) {
         issues.add(format!("{field}.retry_backoff"), "must be a valid duration");
     }
 
+    validate_retry_policy(
+        issues,
+        &field,
+        &task.retry_strategy,
+        &task.retry_jitter,
+        &task.retry_max_backoff,
+        &task.retry_on_exit_codes,
+        &task.retry_when_output_matches,
+    );
+
+    if !task.keep_tmpdir.is_empty() && !VALID_KEEP_TMPDIR.contains(&task.keep_tmpdir.as_str()) {
+        issues.add(
+            format!("{field}.keep_tmpdir"),
+            "must be one of never, on-failure, always",
+        );
+    }
+
     if !task.notify_on.is_empty() && !VALID_NOTIFY_ON.contains(&task.notify_on.as_str()) {
         issues.add(
             format!("{field}.notify_on"),
@@ -394,49 +1947,209 @@ fn validate_task(issues: &mut ValidationErrors, name: &str, task: &Task) {
         );
     }
 
-    if has_tasks {
-        if !task.dir.is_empty() {
-            issues.add(
-                format!("{field}.dir"),
-                "is not supported when using task composition",
-            );
-        }
-        if !task.env.is_empty() {
+    if !task.notify_min_duration.is_empty() && parse_duration(&task.notify_min_duration).is_err() {
+        issues.add(
+            format!("{field}.notify_min_duration"),
+            "must be a valid duration",
+        );
+    }
+
+    for (hook_name, hook) in [
+        ("before", &task.before),
+        ("after", &task.after),
+        ("on_failure", &task.on_failure),
+    ] {
+        let Some(hook) = hook else {
+            continue;
+        };
+
+        if has_tasks {
             issues.add(
-                format!("{field}.env"),
+                format!("{field}.{hook_name}"),
                 "is not supported when using task composition",
             );
+            continue;
         }
-        if !task.timeout.is_empty() {
-            issues.add(
-                format!("{field}.timeout"),
-                "is not supported when using task composition",
-            );
+
+        validate_hook(issues, &format!("{field}.{hook_name}"), hook);
+    }
+
+    if has_tasks {
+        let leaf_only = [
+            ("dir", !task.dir.is_empty()),
+            ("env", !task.env.is_empty()),
+            ("inherit_env", task.inherit_env.is_some()),
+            ("interactive", task.interactive),
+            ("stdin", !task.stdin.is_empty()),
+            ("stdin_file", !task.stdin_file.is_empty()),
+            ("pty", task.pty),
+            ("combine_output", task.combine_output),
+            ("shell", !task.shell.is_default()),
+            ("glob", task.glob),
+            ("nice", task.nice.is_some()),
+            ("io_priority", !task.io_priority.is_empty()),
+            ("container", task.container.is_some()),
+            ("service", task.service),
+            ("wait_for", task.wait_for.is_some()),
+            ("prompts", !task.prompts.is_empty()),
+            ("inputs", !task.inputs.is_empty()),
+            ("outputs", !task.outputs.is_empty()),
+            ("mutex", !task.mutex.is_empty()),
+            ("deadline", !task.deadline.is_empty()),
+            ("grace_period", !task.grace_period.is_empty()),
+            ("max_captured_output", !task.max_captured_output.is_empty()),
+            ("keep_tmpdir", !task.keep_tmpdir.is_empty()),
+            ("retries", task.retries.is_some()),
+            ("retry_backoff", !task.retry_backoff.is_empty()),
+            ("retry_strategy", !task.retry_strategy.is_empty()),
+            ("retry_jitter", !task.retry_jitter.is_empty()),
+            ("retry_max_backoff", !task.retry_max_backoff.is_empty()),
+            ("retry_on_exit_codes", !task.retry_on_exit_codes.is_empty()),
+            (
+                "retry_when_output_matches",
+                !task.retry_when_output_matches.is_empty(),
+            ),
+            ("retry_on_timeout", task.retry_on_timeout.is_some()),
+        ];
+        for (key, is_set) in leaf_only {
+            if is_set {
+                issues.add(
+                    format!("{field}.{key}"),
+                    "is not supported when using task composition",
+                );
+            }
         }
-        if task.retries.is_some() {
-            issues.add(
-                format!("{field}.retries"),
-                "is not supported when using task composition",
-            );
+        for (idx, entry) in task.tasks.iter().enumerate() {
+            match entry {
+                TaskRef::Name(dep) if dep.trim().is_empty() => {
+                    issues.add(format!("{field}.tasks[{idx}]"), "must not be empty");
+                }
+                TaskRef::Inline(step) if step.exec.is_empty() == step.run.is_empty() => {
+                    issues.add(
+                        format!("{field}.tasks[{idx}]"),
+                        "must define exactly one of exec or run",
+                    );
+                }
+                _ => {}
+            }
         }
-        if !task.retry_backoff.is_empty() {
+    }
+}
+
+fn validate_retry_policy(
+    issues: &mut ValidationErrors,
+    field: &str,
+    strategy: &str,
+    jitter: &str,
+    max_backoff: &str,
+    exit_codes: &[i32],
+    output_pattern: &str,
+) {
+    if !strategy.is_empty() && !VALID_RETRY_STRATEGIES.contains(&strategy) {
+        issues.add(
+            format!("{field}.retry_strategy"),
+            "must be one of fixed, linear, exponential",
+        );
+    }
+
+    if !jitter.is_empty() && parse_duration(jitter).is_err() {
+        issues.add(format!("{field}.retry_jitter"), "must be a valid duration");
+    }
+
+    if !max_backoff.is_empty() && parse_duration(max_backoff).is_err() {
+        issues.add(
+            format!("{field}.retry_max_backoff"),
+            "must be a valid duration",
+        );
+    }
+
+    for (idx, code) in exit_codes.iter().enumerate() {
+        if !(1..=255).contains(code) {
             issues.add(
-                format!("{field}.retry_backoff"),
-                "is not supported when using task composition",
+                format!("{field}.retry_on_exit_codes[{idx}]"),
+                "must be between 1 and 255",
             );
         }
-        for (idx, dep) in task.tasks.iter().enumerate() {
-            if dep.trim().is_empty() {
-                issues.add(format!("{field}.tasks[{idx}]"), "must not be empty");
-            }
+    }
+
+    if !output_pattern.is_empty() && Regex::new(output_pattern).is_err() {
+        issues.add(
+            format!("{field}.retry_when_output_matches"),
+            "must be a valid regular expression",
+        );
+    }
+}
+
+fn validate_hook(issues: &mut ValidationErrors, field: &str, hook: &Hook) {
+    if hook.exec.is_empty() == hook.run.is_empty() {
+        issues.add(field, "must define exactly one of exec or run");
+    }
+
+    for (idx, tok) in hook.exec.iter().enumerate() {
+        if tok.is_empty() {
+            issues.add(format!("{field}.exec[{idx}]"), "must not be empty");
         }
     }
+
+    if !hook.timeout.is_empty() && parse_duration(&hook.timeout).is_err() {
+        issues.add(format!("{field}.timeout"), "must be a valid duration");
+    }
+}
+
+fn ntfy_priority(value: &str) -> Option<u8> {
+    match value {
+        "min" | "1" => Some(1),
+        "low" | "2" => Some(2),
+        "default" | "3" => Some(3),
+        "high" | "4" => Some(4),
+        "urgent" | "max" | "5" => Some(5),
+        _ => None,
+    }
+}
+
+fn or_default(value: String, default: &str) -> String {
+    if value.is_empty() {
+        default.to_string()
+    } else {
+        value
+    }
+}
+
+fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
 }
 
 fn parse_duration(text: &str) -> Result<Duration, humantime::DurationError> {
     humantime::parse_duration(text)
 }
 
+pub fn parse_size(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid size {text:?}"))?;
+    let scale: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        _ => return Err(format!("invalid size unit {unit:?}")),
+    };
+    match number.checked_mul(scale) {
+        Some(0) => Err("size must be greater than 0".to_string()),
+        Some(bytes) => Ok(bytes),
+        None => Err(format!("size {text:?} is too large")),
+    }
+}
+
 fn validate_task_dependencies(issues: &mut ValidationErrors, tasks: &HashMap<String, Task>) {
     for (name, task) in tasks {
         if task.tasks.is_empty() {
@@ -444,7 +2157,10 @@ fn validate_task_dependencies(issues: &mut ValidationErrors, tasks: &HashMap<Str
         }
 
         let field = format!("tasks.{name}.tasks");
-        for (idx, dep) in task.tasks.iter().enumerate() {
+        for (idx, entry) in task.tasks.iter().enumerate() {
+            let TaskRef::Name(dep) = entry else {
+                continue;
+            };
             if dep == name {
                 issues.add(
                     format!("{field}[{idx}]"),
@@ -479,11 +2195,124 @@ fn resolve_duration(
     parse_duration(value).map_err(|_| "must be a valid duration".to_string())
 }
 
+fn resolve_size(primary: &str, fallback: &str) -> Result<Option<u64>, String> {
+    let value = if !primary.is_empty() {
+        primary
+    } else {
+        fallback
+    };
+    if value.is_empty() {
+        return Ok(None);
+    }
+    parse_size(value).map(Some)
+}
+
+fn resolve_hook(
+    hook: Option<&Hook>,
+    task_timeout: Duration,
+) -> Result<Option<ResolvedHook>, String> {
+    let Some(hook) = hook else {
+        return Ok(None);
+    };
+
+    let timeout =
+        resolve_duration(&hook.timeout, "", task_timeout).map_err(|e| format!("timeout: {e}"))?;
+
+    Ok(Some(ResolvedHook {
+        use_shell: hook.exec.is_empty(),
+        exec: hook.exec.clone(),
+        shell: hook.run.clone(),
+        timeout,
+    }))
+}
+
 fn resolve_retries(primary: Option<i32>, fallback: Option<i32>, default_value: i32) -> i32 {
     primary.or(fallback).unwrap_or(default_value)
 }
 
-fn resolve_notify_on(primary: &str, fallback: &str, default_value: &str) -> String {
+fn resolve_retry_strategy(primary: &str, fallback: &str) -> RetryStrategy {
+    let value = if !primary.is_empty() {
+        primary
+    } else {
+        fallback
+    };
+
+    match value {
+        "fixed" => RetryStrategy::Fixed,
+        "linear" => RetryStrategy::Linear,
+        _ => RetryStrategy::Exponential,
+    }
+}
+
+fn resolve_wait(wait: &WaitFor) -> Result<ResolvedWait, String> {
+    let target = if wait.http.is_empty() {
+        ready::Target::Tcp(wait.tcp.clone())
+    } else {
+        ready::Target::Http(wait.http.clone())
+    };
+    Ok(ResolvedWait {
+        target,
+        timeout: resolve_duration(&wait.timeout, "", DEFAULT_WAIT_TIMEOUT)?,
+    })
+}
+
+fn split_run(run: &str) -> Result<Vec<String>, String> {
+    match shlex::split(run) {
+        Some(args) if !args.is_empty() => Ok(args),
+        Some(_) => Err("command is empty".to_string()),
+        None => Err("cannot split command without a shell; check quoting".to_string()),
+    }
+}
+
+fn resolve_shell_kind(primary: &str, fallback: &str) -> ShellKind {
+    let value = if !primary.is_empty() {
+        primary
+    } else {
+        fallback
+    };
+
+    match value {
+        "sh" => ShellKind::Sh,
+        "bash" => ShellKind::Bash,
+        "cmd" => ShellKind::Cmd,
+        "pwsh" => ShellKind::Pwsh,
+        "powershell" => ShellKind::PowerShell,
+        "$SHELL" => ShellKind::User,
+        _ => ShellKind::Default,
+    }
+}
+
+fn resolve_io_priority(value: &str) -> IoPriority {
+    match value {
+        "low" => IoPriority::Low,
+        "idle" => IoPriority::Idle,
+        _ => IoPriority::Normal,
+    }
+}
+
+fn resolve_stdin(value: &str, file: &str) -> StdinMode {
+    if !file.is_empty() {
+        return StdinMode::File(PathBuf::from(file));
+    }
+    match value {
+        "null" => StdinMode::Null,
+        _ => StdinMode::Inherit,
+    }
+}
+
+fn resolve_output_pattern(primary: &str, fallback: &str) -> Result<Option<Regex>, String> {
+    let pattern = if primary.is_empty() {
+        fallback
+    } else {
+        primary
+    };
+    if pattern.is_empty() {
+        return Ok(None);
+    }
+    Regex::new(pattern).map(Some).map_err(|e| e.to_string())
+}
+
+fn resolve_choice(primary: &str, fallback: &str, default_value: &str) -> String {
     if !primary.is_empty() {
         primary.to_string()
     } else if !fallback.is_empty() {
@@ -497,7 +2326,22 @@ fn join_command_preview(args: &[String]) -> String {
     args.join(" ")
 }
 
-fn join_task_preview(tasks: &[String], parallel: bool) -> String {
+pub fn steps_preview(steps: &[Step]) -> String {
+    steps
+        .iter()
+        .map(|step| {
+            if step.exec.is_empty() {
+                step.run.clone()
+            } else {
+                join_command_preview(&step.exec)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+pub fn join_task_preview(tasks: &[TaskRef], parallel: bool) -> String {
     let mode = if parallel { "parallel" } else { "sequential" };
-    format!("tasks ({mode}): {}", tasks.join(", "))
+    let previews: Vec<String> = tasks.iter().map(TaskRef::preview).collect();
+    format!("tasks ({mode}): {}", previews.join(", "))
 }
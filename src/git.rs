@@ -0,0 +1,45 @@
+use crate::model::GitContext;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+pub fn context(dir: &Path) -> Option<GitContext> {
+    let output = Command::new("git")
+        .args([
+            "--no-optional-locks",
+            "status",
+            "--porcelain=v2",
+            "--branch",
+        ])
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    parse_status(&String::from_utf8_lossy(&output.stdout))
+}
+
+pub fn parse_status(text: &str) -> Option<GitContext> {
+    let mut sha = None;
+    let mut branch = None;
+    let mut dirty = false;
+
+    for line in text.lines() {
+        if let Some(oid) = line.strip_prefix("# branch.oid ") {
+            sha = Some(oid.trim().to_string()).filter(|oid| oid != "(initial)");
+        } else if let Some(head) = line.strip_prefix("# branch.head ") {
+            branch = Some(head.trim().to_string()).filter(|head| head != "(detached)");
+        } else if !line.starts_with('#') && !line.is_empty() {
+            dirty = true;
+        }
+    }
+
+    Some(GitContext {
+        sha: sha?,
+        branch,
+        dirty,
+    })
+}
@@ -1,25 +1,106 @@
-use crate::model::RunRecord;
+use crate::model::{RunRecord, RunStatus};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
 use std::fs::{self, File, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use time::OffsetDateTime;
 
 pub const DEFAULT_PATH: &str = ".otto/history.jsonl";
+pub const LOG_DIR_NAME: &str = "logs";
+pub const DEFAULT_MAX_ARCHIVES: usize = 5;
+pub const PATH_ENV: &str = "OTTO_HISTORY";
+const READ_CHUNK_BYTES: u64 = 64 * 1024;
 
 #[derive(Debug, Clone, Default)]
 pub struct Filter {
     pub limit: Option<usize>,
+    pub offset: usize,
+    pub before: Option<String>,
     pub status: Option<String>,
     pub source: Option<String>,
+    pub name: Option<String>,
+    pub since: Option<OffsetDateTime>,
+    pub until: Option<OffsetDateTime>,
+    pub grep: Option<String>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Retention {
+    pub keep: Option<usize>,
+    pub newer_than: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub duplicates: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskStats {
+    pub name: String,
+    pub runs: usize,
+    pub successes: usize,
+    pub failures: usize,
+    pub success_rate: f64,
+    pub p50_ms: i64,
+    pub p95_ms: i64,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_failure: Option<OffsetDateTime>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Store {
     path: PathBuf,
+    max_entries: usize,
+    max_size: Option<u64>,
+    max_archives: usize,
+    compress: bool,
+    log_dir: PathBuf,
 }
 
 impl Store {
     pub fn new(path: impl Into<PathBuf>) -> Self {
-        Self { path: path.into() }
+        let path = path.into();
+        let log_dir = path.parent().unwrap_or(Path::new("")).join(LOG_DIR_NAME);
+        Self {
+            path,
+            max_entries: 0,
+            max_size: None,
+            max_archives: DEFAULT_MAX_ARCHIVES,
+            compress: false,
+            log_dir,
+        }
+    }
+
+    pub fn with_log_dir(mut self, log_dir: impl Into<PathBuf>) -> Self {
+        self.log_dir = log_dir.into();
+        self
+    }
+
+    pub fn log_dir(&self) -> &Path {
+        &self.log_dir
+    }
+
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    pub fn with_rotation(mut self, max_size: Option<u64>, max_archives: usize) -> Self {
+        self.max_size = max_size;
+        self.max_archives = max_archives;
+        self
+    }
+
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
     }
 
     pub fn path(&self) -> &Path {
@@ -33,6 +114,7 @@ impl Store {
             .ok_or_else(|| "invalid history path".to_string())?;
 
         fs::create_dir_all(parent).map_err(|e| format!("create history directory: {e}"))?;
+        let _lock = self.lock()?;
 
         let mut file = OpenOptions::new()
             .create(true)
@@ -44,56 +126,617 @@ impl Store {
             serde_json::to_vec(record).map_err(|e| format!("serialize history record: {e}"))?;
         file.write_all(&line)
             .and_then(|_| file.write_all(b"\n"))
-            .map_err(|e| format!("write history record: {e}"))
+            .map_err(|e| format!("write history record: {e}"))?;
+
+        self.enforce_limits_locked()
     }
 
-    pub fn list(&self, filter: &Filter) -> Result<Vec<RunRecord>, String> {
-        let file = match File::open(&self.path) {
-            Ok(file) => file,
+    fn enforce_limits_locked(&self) -> Result<(), String> {
+        if self.max_entries > 0 && self.active_entries()? > self.max_entries {
+            self.trim_locked()?;
+        }
+
+        if let Some(max_size) = self.max_size
+            && fs::metadata(&self.path).is_ok_and(|meta| meta.len() > max_size)
+        {
+            self.rotate()?;
+            if self.max_entries > 0 {
+                self.trim_locked()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn active_entries(&self) -> Result<usize, String> {
+        match fs::read(&self.path) {
+            Ok(bytes) => Ok(bytes
+                .split(|&b| b == b'\n')
+                .filter(|line| !line.trim_ascii().is_empty())
+                .count()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(err) => Err(format!("read history file: {err}")),
+        }
+    }
+
+    fn trim_locked(&self) -> Result<(), String> {
+        let removed = self.prune_locked(&Retention {
+            keep: Some(self.max_entries),
+            newer_than: None,
+        })?;
+        self.remove_logs(&removed);
+        Ok(())
+    }
+
+    pub fn archives(&self) -> Result<Vec<PathBuf>, String> {
+        let (Some(dir), Some(stem), Some(ext)) = (
+            self.path.parent(),
+            self.path.file_stem().and_then(|s| s.to_str()),
+            self.path.extension().and_then(|s| s.to_str()),
+        ) else {
+            return Ok(Vec::new());
+        };
+        let dir = if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        };
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
             Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
-            Err(err) => return Err(format!("open history file: {err}")),
+            Err(err) => return Err(format!("read history directory: {err}")),
         };
 
-        let mut records = Vec::new();
-        let reader = BufReader::new(file);
+        let prefix = format!("{stem}.");
+        let suffix = format!(".{ext}");
+        let compressed = format!(".{ext}.gz");
+        let mut archives = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|e| format!("read history directory: {e}"))?
+                .path();
+            if path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    [&suffix, &compressed].iter().any(|suffix| {
+                        name.len() > prefix.len() + suffix.len()
+                            && name.starts_with(&prefix)
+                            && name.ends_with(suffix.as_str())
+                    })
+                })
+            {
+                archives.push(path);
+            }
+        }
 
-        for line in reader.lines() {
-            let Ok(line) = line else {
-                continue;
+        archives.sort();
+        Ok(archives)
+    }
+
+    fn rotate(&self) -> Result<(), String> {
+        let stem = self
+            .path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("history");
+        let ext = self
+            .path
+            .extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or("jsonl");
+        let archive = loop {
+            let stamp = OffsetDateTime::now_utc()
+                .format(time::macros::format_description!(
+                    "[year]-[month]-[day]T[hour]-[minute]-[second]-[subsecond digits:6]"
+                ))
+                .map_err(|e| format!("format archive name: {e}"))?;
+            let name = if self.compress {
+                format!("{stem}.{stamp}.{ext}.gz")
+            } else {
+                format!("{stem}.{stamp}.{ext}")
             };
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
+            let archive = self.path.with_file_name(name);
+            if !archive.exists() {
+                break archive;
+            }
+            std::thread::sleep(std::time::Duration::from_micros(1));
+        };
+        if self.compress {
+            let text = fs::read(&self.path).map_err(|e| format!("read history file: {e}"))?;
+            write_segment(&archive, &text)?;
+            fs::remove_file(&self.path).map_err(|e| format!("rotate history file: {e}"))?;
+        } else {
+            fs::rename(&self.path, &archive).map_err(|e| format!("rotate history file: {e}"))?;
+        }
+
+        let archives = self.archives()?;
+        let excess = archives.len().saturating_sub(self.max_archives);
+        for old in &archives[..excess] {
+            let records: Vec<RunRecord> = read_archive(old)
+                .unwrap_or_default()
+                .split(|&b| b == b'\n')
+                .filter_map(|line| serde_json::from_slice(line.trim_ascii()).ok())
+                .collect();
+            fs::remove_file(old).map_err(|e| format!("remove history archive: {e}"))?;
+            self.remove_logs(&records);
+        }
+
+        Ok(())
+    }
+
+    pub fn prune(&self, retention: &Retention) -> Result<Vec<RunRecord>, String> {
+        let _lock = self.lock()?;
+        self.prune_locked(retention)
+    }
+
+    fn prune_locked(&self, retention: &Retention) -> Result<Vec<RunRecord>, String> {
+        let mut segments = vec![self.path.clone()];
+        segments.extend(self.archives()?.into_iter().rev());
+
+        let mut seen = 0;
+        let mut removed = Vec::new();
+        for segment in segments {
+            let bytes = match read_archive(&segment) {
+                Ok(bytes) => bytes,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(format!("read {}: {err}", segment.display())),
+            };
+            let text = String::from_utf8_lossy(&bytes);
+            let lines: Vec<&str> = text
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .collect();
+            let mut kept = Vec::new();
+            for line in lines.iter().rev() {
+                let record = serde_json::from_str::<RunRecord>(line.trim()).ok();
+                let within_keep = retention.keep.is_none_or(|keep| seen < keep);
+                let recent_enough = match (&record, retention.newer_than) {
+                    (Some(record), Some(cutoff)) => record.started_at >= cutoff,
+                    _ => true,
+                };
+                seen += 1;
+                if within_keep && recent_enough {
+                    kept.push(*line);
+                } else if let Some(record) = record {
+                    removed.push(record);
+                }
+            }
+            if kept.len() == lines.len() {
                 continue;
             }
 
-            let Ok(rec) = serde_json::from_str::<RunRecord>(trimmed) else {
+            kept.reverse();
+            let mut contents = kept.join("\n");
+            if !contents.is_empty() {
+                contents.push('\n');
+            }
+            if segment == self.path {
+                self.replace(&contents)?;
+            } else if kept.is_empty() {
+                fs::remove_file(&segment).map_err(|e| format!("remove history archive: {e}"))?;
+            } else {
+                write_segment(&segment, contents.as_bytes())?;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    pub fn import(&self, other: &Path) -> Result<ImportSummary, String> {
+        let incoming =
+            fs::read_to_string(other).map_err(|e| format!("read {}: {e}", other.display()))?;
+        let _lock = self.lock()?;
+        let mut seen: HashSet<String> = self
+            .list(&Filter::default())?
+            .into_iter()
+            .map(|record| record.id)
+            .collect();
+
+        let mut summary = ImportSummary::default();
+        let mut added = Vec::new();
+        for line in incoming.lines().filter(|line| !line.trim().is_empty()) {
+            let Ok(mut record) = serde_json::from_str::<RunRecord>(line.trim()) else {
                 continue;
             };
+            if seen.insert(record.id.clone()) {
+                record.log_path = None;
+                let line = serde_json::to_string(&record)
+                    .map_err(|e| format!("serialize history record: {e}"))?;
+                added.push((record.started_at, line));
+            } else {
+                summary.duplicates += 1;
+            }
+        }
+        summary.imported = added.len();
+        let Some(oldest) = added.iter().map(|(started_at, _)| *started_at).min() else {
+            return Ok(summary);
+        };
 
-            if !matches_filter(&rec, filter) {
-                continue;
+        let mut touched = Vec::new();
+        let mut lines = read_segment(&self.path)?;
+        for archive in self.archives()?.into_iter().rev() {
+            let archived = read_segment(&archive)?;
+            if archived
+                .iter()
+                .map(|(started_at, _)| *started_at)
+                .max()
+                .is_none_or(|newest| newest <= oldest)
+            {
+                break;
             }
+            touched.push((archive, archived.len()));
+            lines.extend(archived);
+        }
+        lines.extend(added);
+        lines.sort_by_key(|(started_at, _)| *started_at);
 
-            records.push(rec);
+        let mut lines = lines.into_iter().map(|(_, line)| line);
+        for (archive, count) in touched.iter().rev() {
+            let contents = join_lines(lines.by_ref().take(*count));
+            write_segment(archive, contents.as_bytes())?;
         }
+        self.replace(&join_lines(lines))?;
 
-        records.reverse();
+        self.enforce_limits_locked()?;
+        Ok(summary)
+    }
 
-        if let Some(limit) = filter.limit
-            && records.len() > limit
+    fn lock(&self) -> Result<File, String> {
+        let dir = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let name = self
+            .path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("history");
+        crate::lock::acquire(dir, name, || {})
+    }
+
+    fn replace(&self, contents: &str) -> Result<(), String> {
+        write_segment(&self.path, contents.as_bytes())
+    }
+
+    pub fn list(&self, filter: &Filter) -> Result<Vec<RunRecord>, String> {
+        let name = filter
+            .name
+            .as_deref()
+            .map(glob::Pattern::new)
+            .transpose()
+            .map_err(|e| format!("invalid name pattern: {e}"))?;
+        let mut records = Vec::new();
+        let mut skipped = 0;
+        let mut cursor_seen = filter.before.is_none();
+        let mut visited = 0;
+        let mut paths = vec![self.path.clone()];
+        paths.extend(self.archives()?.into_iter().rev());
+
+        'segments: for path in paths {
+            let lines: Box<dyn Iterator<Item = std::io::Result<Vec<u8>>>> = if is_compressed(&path)
+            {
+                let bytes = match read_archive(&path) {
+                    Ok(bytes) => bytes,
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(err) => return Err(format!("read history archive: {err}")),
+                };
+                let lines: Vec<Vec<u8>> =
+                    bytes.split(|&b| b == b'\n').map(<[u8]>::to_vec).collect();
+                Box::new(lines.into_iter().rev().map(Ok))
+            } else {
+                match File::open(&path) {
+                    Ok(file) => Box::new(ReverseLines::new(file)),
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(err) => return Err(format!("open history file: {err}")),
+                }
+            };
+
+            for line in lines {
+                let line = line.map_err(|e| format!("read history file: {e}"))?;
+                let Ok(rec) = serde_json::from_slice::<RunRecord>(line.trim_ascii()) else {
+                    continue;
+                };
+                if self.max_entries > 0 && visited == self.max_entries {
+                    break 'segments;
+                }
+                visited += 1;
+
+                if !cursor_seen {
+                    cursor_seen = filter.before.as_deref() == Some(rec.id.as_str());
+                    continue;
+                }
+
+                if !matches_filter(&rec, filter)
+                    || name.as_ref().is_some_and(|name| !name.matches(&rec.name))
+                    || filter
+                        .grep
+                        .as_deref()
+                        .is_some_and(|term| matching_lines(&rec, term).is_empty())
+                {
+                    continue;
+                }
+
+                if skipped < filter.offset {
+                    skipped += 1;
+                    continue;
+                }
+
+                records.push(rec);
+                if filter.limit.is_some_and(|limit| records.len() >= limit) {
+                    return Ok(records);
+                }
+            }
+        }
+
+        if let Some(id) = filter.before.as_deref()
+            && !cursor_seen
         {
-            records.truncate(limit);
+            return Err(format!("no run with id {id:?} in history"));
         }
 
         Ok(records)
     }
 }
 
+fn read_segment(path: &Path) -> Result<Vec<(OffsetDateTime, String)>, String> {
+    let bytes = match read_archive(path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(format!("read {}: {err}", path.display())),
+    };
+    Ok(String::from_utf8_lossy(&bytes)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let started_at = serde_json::from_str::<RunRecord>(line.trim())
+                .map(|record| record.started_at)
+                .unwrap_or(OffsetDateTime::UNIX_EPOCH);
+            (started_at, line.trim().to_string())
+        })
+        .collect())
+}
+
+fn join_lines(lines: impl Iterator<Item = String>) -> String {
+    let mut contents = String::new();
+    for line in lines {
+        contents.push_str(&line);
+        contents.push('\n');
+    }
+    contents
+}
+
+fn is_compressed(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "gz")
+}
+
+fn read_archive(path: &Path) -> std::io::Result<Vec<u8>> {
+    let file = File::open(path)?;
+    let mut bytes = Vec::new();
+    if is_compressed(path) {
+        GzDecoder::new(file).read_to_end(&mut bytes)?;
+    } else {
+        BufReader::new(file).read_to_end(&mut bytes)?;
+    }
+    Ok(bytes)
+}
+
+struct ReverseLines {
+    file: File,
+    pos: u64,
+    partial: Vec<u8>,
+    lines: Vec<Vec<u8>>,
+}
+
+impl ReverseLines {
+    fn new(file: File) -> Self {
+        let pos = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+        Self {
+            file,
+            pos,
+            partial: Vec::new(),
+            lines: Vec::new(),
+        }
+    }
+
+    fn read_chunk(&mut self) -> std::io::Result<()> {
+        let size = self.pos.min(READ_CHUNK_BYTES);
+        self.pos -= size;
+        let mut chunk = vec![0_u8; size as usize];
+        self.file.seek(SeekFrom::Start(self.pos))?;
+        self.file.read_exact(&mut chunk)?;
+        chunk.append(&mut self.partial);
+
+        let mut parts = chunk.split(|&b| b == b'\n');
+        self.partial = parts.next().unwrap_or_default().to_vec();
+        self.lines.extend(parts.map(<[u8]>::to_vec));
+        Ok(())
+    }
+}
+
+impl Iterator for ReverseLines {
+    type Item = std::io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(line) = self.lines.pop() {
+                if line.trim_ascii().is_empty() {
+                    continue;
+                }
+                return Some(Ok(line));
+            }
+            if self.pos == 0 {
+                let line = std::mem::take(&mut self.partial);
+                return (!line.trim_ascii().is_empty()).then_some(Ok(line));
+            }
+            if let Err(err) = self.read_chunk() {
+                self.pos = 0;
+                self.partial.clear();
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+impl Store {
+    pub fn remove_logs(&self, records: &[RunRecord]) -> Vec<String> {
+        let Ok(log_dir) = self.log_dir.canonicalize() else {
+            return Vec::new();
+        };
+
+        let mut errors = Vec::new();
+        for record in records {
+            let Some(log_path) = &record.log_path else {
+                continue;
+            };
+            let path = Path::new(log_path);
+            let owned = path
+                .file_name()
+                .is_some_and(|name| *name == *format!("{}.log", record.id))
+                && path
+                    .parent()
+                    .and_then(|parent| parent.canonicalize().ok())
+                    .is_some_and(|parent| parent == log_dir);
+            if !owned {
+                continue;
+            }
+            if let Err(err) = fs::remove_file(path)
+                && err.kind() != std::io::ErrorKind::NotFound
+            {
+                errors.push(format!("remove log {log_path}: {err}"));
+            }
+        }
+        errors
+    }
+}
+
+fn write_segment(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let tmp = temp_path(path);
+    let written = if is_compressed(path) {
+        File::create(&tmp).and_then(|file| {
+            let mut encoder = GzEncoder::new(file, Compression::default());
+            encoder.write_all(contents)?;
+            encoder.finish().map(drop)
+        })
+    } else {
+        fs::write(&tmp, contents)
+    };
+    written.and_then(|()| fs::rename(&tmp, path)).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        format!("write {}: {e}", path.display())
+    })
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let name = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("history");
+    path.with_file_name(format!(".{name}.{}.{nanos}.tmp", std::process::id()))
+}
+
+pub fn matching_lines(record: &RunRecord, term: &str) -> Vec<String> {
+    let needle = term.to_ascii_lowercase();
+    let log = record
+        .log_path
+        .as_ref()
+        .and_then(|path| fs::read(path).ok())
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+    let sources = [
+        Some(record.command_preview.as_str()),
+        record.stderr_tail.as_deref(),
+        log.as_deref(),
+    ];
+
+    let mut lines: Vec<String> = Vec::new();
+    for text in sources.into_iter().flatten() {
+        for line in text.lines() {
+            let line = line.trim();
+            if line.to_ascii_lowercase().contains(&needle) && !lines.iter().any(|seen| seen == line)
+            {
+                lines.push(line.to_string());
+            }
+        }
+    }
+    lines
+}
+
+pub fn global_path() -> Option<PathBuf> {
+    let data_dir = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .filter(|home| !home.is_empty())
+                .map(|home| PathBuf::from(home).join(".local/share"))
+        })
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))?;
+    Some(data_dir.join("otto").join("history.jsonl"))
+}
+
+pub fn stats(records: &[RunRecord]) -> Vec<TaskStats> {
+    let mut by_name: BTreeMap<&str, Vec<&RunRecord>> = BTreeMap::new();
+    for record in records {
+        if record.status != RunStatus::Skipped {
+            by_name.entry(&record.name).or_default().push(record);
+        }
+    }
+
+    by_name
+        .into_iter()
+        .map(|(name, runs)| {
+            let successes = runs
+                .iter()
+                .filter(|run| run.status == RunStatus::Success)
+                .count();
+            let failed: Vec<&&RunRecord> = runs
+                .iter()
+                .filter(|run| matches!(run.status, RunStatus::Failed | RunStatus::TimedOut))
+                .collect();
+            let mut durations: Vec<i64> = runs.iter().map(|run| run.duration_ms).collect();
+            durations.sort_unstable();
+
+            TaskStats {
+                name: name.to_string(),
+                runs: runs.len(),
+                successes,
+                failures: failed.len(),
+                success_rate: successes as f64 / runs.len() as f64,
+                p50_ms: percentile(&durations, 50),
+                p95_ms: percentile(&durations, 95),
+                last_failure: failed.iter().map(|run| run.started_at).max(),
+            }
+        })
+        .collect()
+}
+
+fn percentile(sorted: &[i64], pct: usize) -> i64 {
+    let rank = (sorted.len() * pct).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
 fn matches_filter(record: &RunRecord, filter: &Filter) -> bool {
+    if !filter.tags.iter().all(|tag| record.tags.contains(tag)) {
+        return false;
+    }
+
+    if filter.since.is_some_and(|since| record.started_at < since)
+        || filter.until.is_some_and(|until| record.started_at > until)
+    {
+        return false;
+    }
+
     if let Some(status) = &filter.status {
         let current = match record.status {
             crate::model::RunStatus::Success => "success",
             crate::model::RunStatus::Failed => "failed",
+            crate::model::RunStatus::Skipped => "skipped",
+            crate::model::RunStatus::Interrupted => "interrupted",
+            crate::model::RunStatus::TimedOut => "timedout",
         };
         if current != status {
             return false;
@@ -104,6 +747,7 @@ fn matches_filter(record: &RunRecord, filter: &Filter) -> bool {
         let current = match record.source {
             crate::model::RunSource::Task => "task",
             crate::model::RunSource::Inline => "inline",
+            crate::model::RunSource::Schedule => "schedule",
         };
         if current != source {
             return false;
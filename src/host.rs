@@ -0,0 +1,35 @@
+use std::env;
+
+pub fn name() -> Option<String> {
+    system_name()
+        .or_else(|| env::var("HOSTNAME").ok())
+        .or_else(|| env::var("COMPUTERNAME").ok())
+        .filter(|name| !name.is_empty())
+}
+
+pub fn user() -> Option<String> {
+    env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .ok()
+        .filter(|user| !user.is_empty())
+}
+
+pub fn cwd() -> Option<String> {
+    env::current_dir().ok().map(|dir| dir.display().to_string())
+}
+
+#[cfg(unix)]
+fn system_name() -> Option<String> {
+    let mut buf = [0_u8; 256];
+    let rc = unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) };
+    if rc != 0 {
+        return None;
+    }
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    String::from_utf8(buf[..len].to_vec()).ok()
+}
+
+#[cfg(not(unix))]
+fn system_name() -> Option<String> {
+    None
+}
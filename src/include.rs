@@ -0,0 +1,92 @@
+use reqwest::blocking::Client;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+
+pub const DEFAULT_CACHE_DIR: &str = ".otto/cache/includes";
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FetchMode {
+    #[default]
+    Cached,
+    Offline,
+    Refresh,
+}
+
+static FETCH_MODE: AtomicU8 = AtomicU8::new(0);
+
+pub fn configure(mode: FetchMode) {
+    let value = match mode {
+        FetchMode::Cached => 0,
+        FetchMode::Offline => 1,
+        FetchMode::Refresh => 2,
+    };
+    FETCH_MODE.store(value, Ordering::Relaxed);
+}
+
+fn fetch_mode() -> FetchMode {
+    match FETCH_MODE.load(Ordering::Relaxed) {
+        1 => FetchMode::Offline,
+        2 => FetchMode::Refresh,
+        _ => FetchMode::Cached,
+    }
+}
+
+pub fn is_remote(entry: &str) -> bool {
+    entry.starts_with("https://")
+}
+
+pub fn read(entry: &str, base_dir: &Path) -> Result<String, String> {
+    if is_remote(entry) {
+        return fetch(entry, &base_dir.join(DEFAULT_CACHE_DIR), fetch_mode());
+    }
+
+    if entry.starts_with("http://") {
+        return Err("remote includes must use https".to_string());
+    }
+
+    fs::read_to_string(base_dir.join(entry)).map_err(|e| format!("read include: {e}"))
+}
+
+pub fn fetch(url: &str, cache_dir: &Path, mode: FetchMode) -> Result<String, String> {
+    let cached = cache_path(cache_dir, url);
+
+    if mode != FetchMode::Refresh
+        && let Ok(text) = fs::read_to_string(&cached)
+    {
+        return Ok(text);
+    }
+
+    if mode == FetchMode::Offline {
+        return Err(format!("{url} is not cached and --offline is set"));
+    }
+
+    let client = Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| format!("build client: {e}"))?;
+    let response = client
+        .get(url)
+        .send()
+        .map_err(|e| format!("fetch {url}: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "fetch {url}: unexpected status {}",
+            response.status().as_u16()
+        ));
+    }
+    let text = response.text().map_err(|e| format!("fetch {url}: {e}"))?;
+
+    fs::create_dir_all(cache_dir).map_err(|e| format!("create include cache: {e}"))?;
+    fs::write(&cached, &text).map_err(|e| format!("write include cache: {e}"))?;
+    Ok(text)
+}
+
+pub fn cache_path(cache_dir: &Path, url: &str) -> PathBuf {
+    let digest = Sha256::digest(url.as_bytes());
+    let name: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+    cache_dir.join(format!("{name}.yml"))
+}
@@ -1,12 +1,24 @@
 pub mod app_error;
+pub mod cache;
 pub mod cli;
 pub mod config;
 pub mod envfile;
+pub mod git;
 pub mod history;
+pub mod host;
+pub mod include;
+pub mod lock;
+pub mod metrics;
 pub mod model;
 pub mod notify;
 pub mod output;
+pub mod prompt;
+pub mod ready;
 pub mod runner;
+pub mod runs;
+pub mod schedule;
+pub mod schema;
+pub mod smtp;
 pub mod version;
 
 pub fn run() -> i32 {
@@ -0,0 +1,27 @@
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::Path;
+
+pub const DEFAULT_DIR: &str = ".otto/locks";
+
+pub fn acquire(dir: &Path, name: &str, on_wait: impl FnOnce()) -> Result<File, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("create lock directory: {e}"))?;
+
+    let path = dir.join(format!("{name}.lock"));
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .map_err(|e| format!("open lock {}: {e}", path.display()))?;
+
+    match file.try_lock() {
+        Ok(()) => Ok(file),
+        Err(TryLockError::WouldBlock) => {
+            on_wait();
+            file.lock()
+                .map_err(|e| format!("lock {}: {e}", path.display()))?;
+            Ok(file)
+        }
+        Err(TryLockError::Error(err)) => Err(format!("lock {}: {err}", path.display())),
+    }
+}
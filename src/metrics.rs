@@ -0,0 +1,127 @@
+use reqwest::Url;
+use reqwest::blocking::Client;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct Sample {
+    pub name: String,
+    pub status: String,
+    pub exit_code: i32,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct Emitter {
+    pub statsd_addr: String,
+    pub pushgateway_url: String,
+    pub prefix: String,
+    pub timeout: Duration,
+}
+
+impl Emitter {
+    pub fn is_enabled(&self) -> bool {
+        !self.statsd_addr.is_empty() || !self.pushgateway_url.is_empty()
+    }
+
+    pub fn emit(&self, sample: &Sample) -> Result<(), String> {
+        let mut errors = Vec::new();
+
+        if !self.statsd_addr.is_empty()
+            && let Err(err) = send_statsd(&self.statsd_addr, &statsd_lines(&self.prefix, sample))
+        {
+            errors.push(format!("statsd: {err}"));
+        }
+
+        if !self.pushgateway_url.is_empty()
+            && let Err(err) = push_gateway(self, sample)
+        {
+            errors.push(format!("pushgateway: {err}"));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+}
+
+pub fn statsd_lines(prefix: &str, sample: &Sample) -> String {
+    let base = format!("{}.{}", metric_name(prefix), metric_name(&sample.name));
+    format!(
+        "{base}.duration:{}|ms\n{base}.{}:1|c\n{base}.exit_code:{}|g",
+        sample.duration.as_millis(),
+        metric_name(&sample.status),
+        sample.exit_code
+    )
+}
+
+pub fn prometheus_text(prefix: &str, sample: &Sample) -> String {
+    let prefix = metric_name(prefix);
+    let success = u8::from(sample.status == "success");
+    format!(
+        "# TYPE {prefix}_run_duration_seconds gauge\n\
+         {prefix}_run_duration_seconds {}\n\
+         # TYPE {prefix}_run_exit_code gauge\n\
+         {prefix}_run_exit_code {}\n\
+         # TYPE {prefix}_run_success gauge\n\
+         {prefix}_run_success {success}\n",
+        sample.duration.as_secs_f64(),
+        sample.exit_code
+    )
+}
+
+fn send_statsd(addr: &str, payload: &str) -> Result<(), String> {
+    let target = addr
+        .to_socket_addrs()
+        .map_err(|e| format!("resolve {addr}: {e}"))?
+        .next()
+        .ok_or_else(|| format!("resolve {addr}: no addresses"))?;
+    let local = match target {
+        SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+        SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+    };
+    let socket = UdpSocket::bind(local).map_err(|e| format!("bind socket: {e}"))?;
+    socket
+        .send_to(payload.as_bytes(), target)
+        .map_err(|e| format!("send to {addr}: {e}"))?;
+    Ok(())
+}
+
+fn push_gateway(emitter: &Emitter, sample: &Sample) -> Result<(), String> {
+    let mut url = Url::parse(&emitter.pushgateway_url).map_err(|e| format!("parse url: {e}"))?;
+    url.path_segments_mut()
+        .map_err(|()| "url cannot be a base".to_string())?
+        .pop_if_empty()
+        .extend([
+            "metrics",
+            "job",
+            &metric_name(&emitter.prefix),
+            "task",
+            &sample.name,
+        ]);
+
+    let client = Client::builder()
+        .timeout(emitter.timeout)
+        .build()
+        .map_err(|e| format!("build client: {e}"))?;
+    let response = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "text/plain; version=0.0.4")
+        .body(prometheus_text(&emitter.prefix, sample))
+        .send()
+        .map_err(|e| format!("send request: {e}"))?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("unexpected status {}", response.status().as_u16()))
+    }
+}
+
+fn metric_name(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
@@ -1,5 +1,9 @@
-use reqwest::blocking::Client;
+use crate::model::GitContext;
+use crate::smtp::{self, Security};
+use reqwest::blocking::{Client, RequestBuilder};
 use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
 use std::process::Command;
 use std::time::Duration;
 use time::OffsetDateTime;
@@ -13,7 +17,60 @@ pub struct Event {
     pub duration: Duration,
     pub started_at: OffsetDateTime,
     pub command_preview: String,
+    pub stdout_tail: Option<String>,
     pub stderr_tail: Option<String>,
+    pub signal: Option<String>,
+    pub git: Option<GitContext>,
+}
+
+const SNIPPET_CHARS: usize = 1500;
+const TELEGRAM_API: &str = "https://api.telegram.org";
+const PUSHOVER_API: &str = "https://api.pushover.net/1/messages.json";
+
+#[derive(Debug, Clone)]
+pub struct Slack {
+    pub webhook_url: String,
+    pub channel: String,
+    pub mention_on_failure: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Discord {
+    pub webhook_url: String,
+    pub username: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Telegram {
+    pub bot_token_env: String,
+    pub chat_id: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub host: String,
+    pub port: u16,
+    pub security: Security,
+    pub username_env: String,
+    pub password_env: String,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Ntfy {
+    pub url: String,
+    pub token_env: String,
+    pub priorities: BTreeMap<String, u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Pushover {
+    pub user_key: String,
+    pub token_env: String,
+    pub failure_priority: i8,
+    pub retry: Duration,
+    pub expire: Duration,
 }
 
 #[derive(Debug, Clone)]
@@ -21,6 +78,13 @@ pub struct Manager {
     pub desktop_enabled: bool,
     pub webhook_url: String,
     pub webhook_timeout: Duration,
+    pub slack: Option<Slack>,
+    pub discord: Option<Discord>,
+    pub teams_webhook_url: String,
+    pub telegram: Option<Telegram>,
+    pub email: Option<Email>,
+    pub ntfy: Option<Ntfy>,
+    pub pushover: Option<Pushover>,
 }
 
 impl Manager {
@@ -39,6 +103,57 @@ impl Manager {
             errors.push(format!("webhook: {err}"));
         }
 
+        if let Some(slack) = &self.slack
+            && let Err(err) = post_json(
+                &slack.webhook_url,
+                self.webhook_timeout,
+                &slack_payload(slack, event),
+            )
+        {
+            errors.push(format!("slack: {err}"));
+        }
+
+        if let Some(discord) = &self.discord
+            && let Err(err) = discord_payload(discord, event)
+                .and_then(|payload| post_json(&discord.webhook_url, self.webhook_timeout, &payload))
+        {
+            errors.push(format!("discord: {err}"));
+        }
+
+        if !self.teams_webhook_url.is_empty()
+            && let Err(err) = post_json(
+                &self.teams_webhook_url,
+                self.webhook_timeout,
+                &teams_payload(event),
+            )
+        {
+            errors.push(format!("teams: {err}"));
+        }
+
+        if let Some(telegram) = &self.telegram
+            && let Err(err) = telegram_notify(telegram, self.webhook_timeout, event)
+        {
+            errors.push(format!("telegram: {err}"));
+        }
+
+        if let Some(email) = &self.email
+            && let Err(err) = email_notify(email, self.webhook_timeout, event)
+        {
+            errors.push(format!("email: {err}"));
+        }
+
+        if let Some(ntfy) = &self.ntfy
+            && let Err(err) = ntfy_notify(ntfy, self.webhook_timeout, event)
+        {
+            errors.push(format!("ntfy: {err}"));
+        }
+
+        if let Some(pushover) = &self.pushover
+            && let Err(err) = pushover_notify(pushover, self.webhook_timeout, event)
+        {
+            errors.push(format!("pushover: {err}"));
+        }
+
         if errors.is_empty() {
             Ok(())
         } else {
@@ -49,11 +164,11 @@ impl Manager {
 
 fn desktop_notify(event: &Event) -> Result<(), String> {
     let title = format!("{} {}", event.name, event.status);
-    let body = format!(
-        "exit {}, duration {}",
-        event.exit_code,
-        format_duration(event.duration)
-    );
+    let outcome = match &event.signal {
+        Some(signal) => format!("killed by {signal}"),
+        None => format!("exit {}", event.exit_code),
+    };
+    let body = format!("{outcome}, duration {}", format_duration(event.duration));
 
     if cfg!(target_os = "macos") {
         let script = format!("display notification {:?} with title {:?}", body, title);
@@ -97,21 +212,15 @@ struct WebhookPayload<'a> {
     duration_ms: i128,
     started_at: String,
     command_preview: &'a str,
+    stdout_tail: &'a str,
     stderr_tail: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signal: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    git: Option<&'a GitContext>,
 }
 
 fn webhook_notify(webhook_url: &str, timeout: Duration, event: &Event) -> Result<(), String> {
-    let timeout = if timeout.is_zero() {
-        Duration::from_secs(5)
-    } else {
-        timeout
-    };
-
-    let client = Client::builder()
-        .timeout(timeout)
-        .build()
-        .map_err(|e| format!("build client: {e}"))?;
-
     let payload = WebhookPayload {
         name: &event.name,
         source: &event.source,
@@ -123,15 +232,410 @@ fn webhook_notify(webhook_url: &str, timeout: Duration, event: &Event) -> Result
             .format(&time::format_description::well_known::Rfc3339)
             .map_err(|e| format!("format started_at: {e}"))?,
         command_preview: &event.command_preview,
+        stdout_tail: event.stdout_tail.as_deref().unwrap_or(""),
         stderr_tail: event.stderr_tail.as_deref().unwrap_or(""),
+        signal: event.signal.as_deref(),
+        git: event.git.as_ref(),
+    };
+
+    post_json(webhook_url, timeout, &payload)
+}
+
+pub fn slack_payload(slack: &Slack, event: &Event) -> Value {
+    let failed = is_failure(&event.status);
+    let mut headline = format!("*{}* {}", escape_html(&event.name), event.status);
+    if failed && !slack.mention_on_failure.is_empty() {
+        headline = format!("{} {headline}", slack.mention_on_failure);
+    }
+
+    let mut blocks = vec![
+        json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": headline },
+        }),
+        json!({
+            "type": "context",
+            "elements": [{ "type": "mrkdwn", "text": escape_html(&summary(event).join(" | ")) }],
+        }),
+    ];
+    if failed && let Some(stderr) = snippet(event.stderr_tail.as_deref()) {
+        let stderr = escape_html(&stderr).replace('`', "\u{2cb}");
+        blocks.push(json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": format!("```{stderr}```") },
+        }));
+    }
+
+    let mut payload = json!({
+        "text": format!("{} {}", event.name, event.status),
+        "attachments": [{ "color": status_color(&event.status), "blocks": blocks }],
+    });
+    if !slack.channel.is_empty() {
+        payload["channel"] = json!(slack.channel);
+    }
+    payload
+}
+
+pub fn discord_payload(discord: &Discord, event: &Event) -> Result<Value, String> {
+    let color = u32::from_str_radix(&status_color(&event.status)[1..], 16)
+        .map_err(|e| format!("parse color: {e}"))?;
+    let mut fields = vec![
+        json!({ "name": "Status", "value": event.status, "inline": true }),
+        json!({
+            "name": "Exit",
+            "value": match &event.signal {
+                Some(signal) => format!("{} ({signal})", event.exit_code),
+                None => event.exit_code.to_string(),
+            },
+            "inline": true,
+        }),
+        json!({ "name": "Duration", "value": format_duration(event.duration), "inline": true }),
+    ];
+    if let Some(git) = &event.git {
+        fields.push(json!({ "name": "Git", "value": git_label(git), "inline": true }));
+    }
+
+    let mut embed = json!({
+        "title": format!("{} {}", event.name, event.status),
+        "color": color,
+        "fields": fields,
+        "timestamp": event
+            .started_at
+            .format(&time::format_description::well_known::Rfc3339)
+            .map_err(|e| format!("format started_at: {e}"))?,
+    });
+    if is_failure(&event.status)
+        && let Some(stderr) = snippet(event.stderr_tail.as_deref())
+    {
+        embed["description"] = json!(format!("```\n{stderr}\n```"));
+    }
+
+    let mut payload = json!({ "embeds": [embed] });
+    if !discord.username.is_empty() {
+        payload["username"] = json!(discord.username);
+    }
+    Ok(payload)
+}
+
+pub fn teams_payload(event: &Event) -> Value {
+    let color = match event.status.as_str() {
+        "success" => "good",
+        "failed" | "timedout" => "attention",
+        "interrupted" => "warning",
+        _ => "default",
+    };
+    let mut facts = vec![
+        json!({ "title": "Status", "value": event.status }),
+        json!({
+            "title": "Exit",
+            "value": match &event.signal {
+                Some(signal) => format!("{} (killed by {signal})", event.exit_code),
+                None => event.exit_code.to_string(),
+            },
+        }),
+        json!({ "title": "Duration", "value": format_duration(event.duration) }),
+        json!({ "title": "Source", "value": event.source }),
+        json!({ "title": "Command", "value": event.command_preview }),
+    ];
+    if let Some(git) = &event.git {
+        facts.push(json!({ "title": "Git", "value": git_label(git) }));
+    }
+
+    let mut body = vec![
+        json!({
+            "type": "TextBlock",
+            "text": format!("{} {}", event.name, event.status),
+            "size": "Medium",
+            "weight": "Bolder",
+            "color": color,
+            "wrap": true,
+        }),
+        json!({ "type": "FactSet", "facts": facts }),
+    ];
+    if is_failure(&event.status)
+        && let Some(stderr) = snippet(event.stderr_tail.as_deref())
+    {
+        body.push(json!({
+            "type": "TextBlock",
+            "text": stderr,
+            "fontType": "Monospace",
+            "wrap": true,
+        }));
+    }
+
+    json!({
+        "type": "message",
+        "attachments": [{
+            "contentType": "application/vnd.microsoft.card.adaptive",
+            "content": {
+                "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                "type": "AdaptiveCard",
+                "version": "1.4",
+                "body": body,
+            },
+        }],
+    })
+}
+
+fn telegram_notify(telegram: &Telegram, timeout: Duration, event: &Event) -> Result<(), String> {
+    let token = std::env::var(&telegram.bot_token_env)
+        .ok()
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| format!("{} is not set", telegram.bot_token_env))?;
+    post_json(
+        &format!("{TELEGRAM_API}/bot{token}/sendMessage"),
+        timeout,
+        &telegram_payload(&telegram.chat_id, event),
+    )
+}
+
+pub fn telegram_payload(chat_id: &str, event: &Event) -> Value {
+    let mut text = format!(
+        "<b>{}</b> {}\n{}",
+        escape_html(&event.name),
+        event.status,
+        escape_html(&summary(event).join(" | "))
+    );
+    if is_failure(&event.status)
+        && let Some(stderr) = snippet(event.stderr_tail.as_deref())
+    {
+        text.push_str(&format!("\n<pre>{}</pre>", escape_html(&stderr)));
+    }
+
+    json!({
+        "chat_id": chat_id,
+        "text": text,
+        "parse_mode": "HTML",
+        "disable_web_page_preview": true,
+    })
+}
+
+fn email_notify(email: &Email, timeout: Duration, event: &Event) -> Result<(), String> {
+    let credentials = match std::env::var(&email.username_env) {
+        Ok(username) if !username.is_empty() => {
+            let password = std::env::var(&email.password_env)
+                .map_err(|_| format!("{} is not set", email.password_env))?;
+            Some((username, password))
+        }
+        _ => None,
+    };
+
+    let server = smtp::Server {
+        host: email.host.clone(),
+        port: email.port,
+        security: email.security,
+        credentials,
+        timeout: if timeout.is_zero() {
+            Duration::from_secs(5)
+        } else {
+            timeout
+        },
     };
+    let message = smtp::Message {
+        from: email.from.clone(),
+        to: email.to.clone(),
+        body: email_message(email, event, OffsetDateTime::now_utc())?,
+    };
+    smtp::send(&server, &message)
+}
+
+pub fn email_message(email: &Email, event: &Event, now: OffsetDateTime) -> Result<String, String> {
+    let date = now
+        .format(&time::format_description::well_known::Rfc2822)
+        .map_err(|e| format!("format date: {e}"))?;
+    let started = event
+        .started_at
+        .format(&time::format_description::well_known::Rfc3339)
+        .map_err(|e| format!("format started_at: {e}"))?;
+
+    let mut text = format!(
+        "From: {}\r\nTo: {}\r\nSubject: [otto] {} {}\r\nDate: {date}\r\n\
+         MIME-Version: 1.0\r\nContent-Type: text/plain; charset=utf-8\r\n\
+         Content-Transfer-Encoding: 8bit\r\n\r\n",
+        email.from,
+        email.to.join(", "),
+        event.name,
+        event.status
+    );
+    text.push_str(&format!("Task:     {}\n", event.name));
+    text.push_str(&format!("Status:   {}\n", event.status));
+    text.push_str(&format!(
+        "Exit:     {}\n",
+        match &event.signal {
+            Some(signal) => format!("{} (killed by {signal})", event.exit_code),
+            None => event.exit_code.to_string(),
+        }
+    ));
+    text.push_str(&format!("Duration: {}\n", format_duration(event.duration)));
+    text.push_str(&format!("Source:   {}\n", event.source));
+    text.push_str(&format!("Started:  {started}\n"));
+    text.push_str(&format!("Command:  {}\n", event.command_preview));
+    if let Some(git) = &event.git {
+        text.push_str(&format!("Git:      {}\n", git_label(git)));
+    }
+    if is_failure(&event.status)
+        && let Some(stderr) = snippet(event.stderr_tail.as_deref())
+    {
+        text.push_str(&format!("\nstderr:\n{stderr}\n"));
+    }
+    Ok(text)
+}
+
+fn ntfy_notify(ntfy: &Ntfy, timeout: Duration, event: &Event) -> Result<(), String> {
+    let (endpoint, payload) = ntfy_request(ntfy, event)?;
+    let mut request = client(timeout)?.post(endpoint);
+    if let Ok(token) = std::env::var(&ntfy.token_env)
+        && !token.is_empty()
+    {
+        request = request.bearer_auth(token);
+    }
+    send_json(request, &payload)
+}
+
+pub fn ntfy_request(ntfy: &Ntfy, event: &Event) -> Result<(String, Value), String> {
+    let mut url = reqwest::Url::parse(&ntfy.url).map_err(|e| format!("parse url: {e}"))?;
+    let path = url.path().trim_end_matches('/').to_string();
+    let (base, topic) = path
+        .rsplit_once('/')
+        .filter(|(_, topic)| !topic.is_empty())
+        .ok_or_else(|| "url must include the topic".to_string())?;
+    url.set_path(&format!("{base}/"));
+
+    let tag = match event.status.as_str() {
+        "success" => "white_check_mark",
+        "failed" | "timedout" => "x",
+        "interrupted" => "warning",
+        _ => "fast_forward",
+    };
+    let mut message = summary(event).join(" | ");
+    if is_failure(&event.status)
+        && let Some(stderr) = snippet(event.stderr_tail.as_deref())
+    {
+        message.push_str(&format!("\n\n{stderr}"));
+    }
+
+    Ok((
+        url.to_string(),
+        json!({
+            "topic": topic,
+            "title": format!("{} {}", event.name, event.status),
+            "message": message,
+            "priority": ntfy.priorities.get(&event.status).copied().unwrap_or(3),
+            "tags": [tag],
+        }),
+    ))
+}
+
+fn pushover_notify(pushover: &Pushover, timeout: Duration, event: &Event) -> Result<(), String> {
+    let token = std::env::var(&pushover.token_env)
+        .ok()
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| format!("{} is not set", pushover.token_env))?;
+    post_json(
+        PUSHOVER_API,
+        timeout,
+        &pushover_payload(pushover, &token, event),
+    )
+}
+
+pub fn pushover_payload(pushover: &Pushover, token: &str, event: &Event) -> Value {
+    let failed = is_failure(&event.status);
+    let priority = if failed { pushover.failure_priority } else { 0 };
+    let mut message = summary(event).join(" | ");
+    if failed && let Some(stderr) = snippet(event.stderr_tail.as_deref()) {
+        message.push_str(&format!("\n\n{stderr}"));
+    }
+
+    let mut payload = json!({
+        "token": token,
+        "user": pushover.user_key,
+        "title": format!("{} {}", event.name, event.status),
+        "message": message,
+        "priority": priority,
+        "timestamp": event.started_at.unix_timestamp(),
+    });
+    if priority == 2 {
+        payload["retry"] = json!(pushover.retry.as_secs());
+        payload["expire"] = json!(pushover.expire.as_secs());
+    }
+    payload
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn summary(event: &Event) -> Vec<String> {
+    let mut parts = vec![
+        match &event.signal {
+            Some(signal) => format!("killed by {signal}"),
+            None => format!("exit {}", event.exit_code),
+        },
+        format!("duration {}", format_duration(event.duration)),
+        format!("source {}", event.source),
+    ];
+    if let Some(git) = &event.git {
+        parts.push(git_label(git));
+    }
+    parts
+}
+
+fn git_label(git: &GitContext) -> String {
+    let short = &git.sha[..git.sha.len().min(7)];
+    match &git.branch {
+        Some(branch) => format!("{branch}@{short}"),
+        None => short.to_string(),
+    }
+}
+
+fn is_failure(status: &str) -> bool {
+    matches!(status, "failed" | "timedout")
+}
+
+fn status_color(status: &str) -> &'static str {
+    match status {
+        "success" => "#2eb886",
+        "failed" | "timedout" => "#d00000",
+        "interrupted" => "#daa038",
+        _ => "#9e9e9e",
+    }
+}
+
+fn snippet(text: Option<&str>) -> Option<String> {
+    let text = text?.trim_end();
+    if text.is_empty() {
+        return None;
+    }
+
+    let skip = text.chars().count().saturating_sub(SNIPPET_CHARS);
+    Some(text.chars().skip(skip).collect())
+}
+
+fn post_json(url: &str, timeout: Duration, payload: &impl Serialize) -> Result<(), String> {
+    send_json(client(timeout)?.post(url), payload)
+}
+
+fn client(timeout: Duration) -> Result<Client, String> {
+    let timeout = if timeout.is_zero() {
+        Duration::from_secs(5)
+    } else {
+        timeout
+    };
+
+    Client::builder()
+        .timeout(timeout)
+        .build()
+        .map_err(|e| format!("build client: {e}"))
+}
 
-    let response = client
-        .post(webhook_url)
+fn send_json(request: RequestBuilder, payload: &impl Serialize) -> Result<(), String> {
+    let response = request
         .header(reqwest::header::CONTENT_TYPE, "application/json")
-        .json(&payload)
+        .json(payload)
         .send()
-        .map_err(|e| format!("send request: {e}"))?;
+        .map_err(|e| format!("send request: {}", e.without_url()))?;
 
     if response.status().is_success() {
         Ok(())
@@ -0,0 +1,202 @@
+use crate::model::{RunRecord, RunStatus};
+use crate::output::{accent, failure, format_duration_ms, info, muted, success};
+use std::io::Write;
+
+const CONTEXT_LINES: usize = 3;
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<Change<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut changes: Vec<Change> = old[..prefix]
+        .iter()
+        .map(|line| Change::Same(line))
+        .collect();
+
+    if old_mid.len() * new_mid.len() > MAX_DIFF_CELLS {
+        changes.extend(old_mid.iter().map(|line| Change::Removed(line)));
+        changes.extend(new_mid.iter().map(|line| Change::Added(line)));
+    } else {
+        let width = new_mid.len() + 1;
+        let mut lcs = vec![0_usize; (old_mid.len() + 1) * width];
+        for i in (0..old_mid.len()).rev() {
+            for j in (0..new_mid.len()).rev() {
+                lcs[i * width + j] = if old_mid[i] == new_mid[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < old_mid.len() && j < new_mid.len() {
+            if old_mid[i] == new_mid[j] {
+                changes.push(Change::Same(old_mid[i]));
+                i += 1;
+                j += 1;
+            } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+                changes.push(Change::Removed(old_mid[i]));
+                i += 1;
+            } else {
+                changes.push(Change::Added(new_mid[j]));
+                j += 1;
+            }
+        }
+        changes.extend(old_mid[i..].iter().map(|line| Change::Removed(line)));
+        changes.extend(new_mid[j..].iter().map(|line| Change::Added(line)));
+    }
+
+    changes.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| Change::Same(line)),
+    );
+    changes
+}
+
+pub fn print_run_diff(
+    mut w: impl Write,
+    old: &RunRecord,
+    new: &RunRecord,
+    label: &str,
+    old_text: &str,
+    new_text: &str,
+) -> std::io::Result<()> {
+    writeln!(w, "{} {}", failure("---"), summary(old))?;
+    writeln!(w, "{} {}", success("+++"), summary(new))?;
+
+    if old.status != new.status {
+        writeln!(
+            w,
+            "  status: {} -> {}",
+            status_name(old.status),
+            status_name(new.status)
+        )?;
+    }
+    if old.exit_code != new.exit_code {
+        writeln!(w, "  exit: {} -> {}", old.exit_code, new.exit_code)?;
+    }
+    let delta = new.duration_ms - old.duration_ms;
+    let sign = if delta < 0 { "-" } else { "+" };
+    writeln!(
+        w,
+        "  duration: {} -> {} ({sign}{})",
+        format_duration_ms(old.duration_ms),
+        format_duration_ms(new.duration_ms),
+        format_duration_ms(delta.abs())
+    )?;
+
+    let changes = diff_lines(old_text, new_text);
+    if changes
+        .iter()
+        .all(|change| matches!(change, Change::Same(_)))
+    {
+        writeln!(w)?;
+        writeln!(w, "{} {label} is identical", info("i"))?;
+        return Ok(());
+    }
+
+    writeln!(w)?;
+    writeln!(w, "{}", accent(label))?;
+    for hunk in hunks(&changes) {
+        let (old_start, old_len, new_start, new_len) = hunk_range(&changes, hunk.clone());
+        writeln!(
+            w,
+            "{}",
+            muted(&format!(
+                "@@ -{old_start},{old_len} +{new_start},{new_len} @@"
+            ))
+        )?;
+        for change in &changes[hunk] {
+            match change {
+                Change::Same(line) => writeln!(w, " {line}")?,
+                Change::Removed(line) => writeln!(w, "{}", failure(&format!("-{line}")))?,
+                Change::Added(line) => writeln!(w, "{}", success(&format!("+{line}")))?,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn summary(record: &RunRecord) -> String {
+    let started = record
+        .started_at
+        .format(&time::macros::format_description!(
+            "[year]-[month]-[day] [hour]:[minute]:[second]"
+        ))
+        .unwrap_or_else(|_| "-".to_string());
+    format!(
+        "{} {} {}",
+        accent(&record.name),
+        muted(&record.id),
+        muted(&format!("({started})"))
+    )
+}
+
+fn status_name(status: RunStatus) -> &'static str {
+    match status {
+        RunStatus::Success => "success",
+        RunStatus::Failed => "failed",
+        RunStatus::Skipped => "skipped",
+        RunStatus::Interrupted => "interrupted",
+        RunStatus::TimedOut => "timedout",
+    }
+}
+
+fn hunks(changes: &[Change]) -> Vec<std::ops::Range<usize>> {
+    let mut ranges: Vec<std::ops::Range<usize>> = Vec::new();
+    for (idx, change) in changes.iter().enumerate() {
+        if matches!(change, Change::Same(_)) {
+            continue;
+        }
+        let start = idx.saturating_sub(CONTEXT_LINES);
+        let end = (idx + CONTEXT_LINES + 1).min(changes.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.end => last.end = end,
+            _ => ranges.push(start..end),
+        }
+    }
+    ranges
+}
+
+fn hunk_range(changes: &[Change], hunk: std::ops::Range<usize>) -> (usize, usize, usize, usize) {
+    let before = &changes[..hunk.start];
+    let old_start = before
+        .iter()
+        .filter(|change| !matches!(change, Change::Added(_)))
+        .count();
+    let new_start = before
+        .iter()
+        .filter(|change| !matches!(change, Change::Removed(_)))
+        .count();
+    let inside = &changes[hunk];
+    let old_len = inside
+        .iter()
+        .filter(|change| !matches!(change, Change::Added(_)))
+        .count();
+    let new_len = inside
+        .iter()
+        .filter(|change| !matches!(change, Change::Removed(_)))
+        .count();
+    (old_start + 1, old_len, new_start + 1, new_len)
+}
@@ -1,51 +1,192 @@
-use crate::model::{RunSource, RunStatus};
-use crate::output::{accent, failure, format_duration_ms, info, number, success};
+use crate::model::{GitContext, RunRecord, RunSource, RunStatus};
+use crate::output::{
+    accent, bold, failure, format_duration_ms, highlight, info, muted, number, success, warning,
+};
 use std::io::Write;
 use time::OffsetDateTime;
 
+pub const CSV_COLUMNS: &[&str] = &[
+    "id",
+    "name",
+    "source",
+    "status",
+    "exit_code",
+    "signal",
+    "started_at",
+    "duration_ms",
+    "command",
+    "skip_reason",
+    "log_path",
+    "rerun_of",
+    "tags",
+    "git_sha",
+    "git_branch",
+    "git_dirty",
+    "host",
+    "user",
+    "cwd",
+];
+
+pub const DEFAULT_CSV_COLUMNS: &[&str] = &[
+    "id",
+    "name",
+    "source",
+    "status",
+    "exit_code",
+    "started_at",
+    "duration_ms",
+    "command",
+];
+
+pub const HISTORY_COLUMNS: &[&str] = &[
+    "id", "name", "source", "status", "exit", "started", "duration", "rerun_of", "tags", "reason",
+    "git", "host", "user", "cwd", "log",
+];
+
+const COLUMN_ALIASES: &[(&str, &str)] = &[
+    ("exit", "exit_code"),
+    ("started", "started_at"),
+    ("duration", "duration_ms"),
+    ("reason", "skip_reason"),
+    ("log", "log_path"),
+];
+
+pub fn text_column(name: &str) -> Option<&'static str> {
+    let name = COLUMN_ALIASES
+        .iter()
+        .find(|(_, csv)| *csv == name)
+        .map_or(name, |(text, _)| text);
+    HISTORY_COLUMNS
+        .iter()
+        .copied()
+        .find(|column| *column == name)
+}
+
+pub fn csv_column(name: &str) -> Option<&'static str> {
+    let name = COLUMN_ALIASES
+        .iter()
+        .find(|(text, _)| *text == name)
+        .map_or(name, |(_, csv)| csv);
+    CSV_COLUMNS.iter().copied().find(|column| *column == name)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HistoryView {
+    pub columns: Vec<String>,
+    pub relative_to: Option<OffsetDateTime>,
+}
+
 #[derive(Debug, Clone)]
 pub struct HistoryRow {
+    pub id: String,
     pub name: String,
     pub source: RunSource,
     pub status: RunStatus,
     pub exit_code: i32,
+    pub signal: Option<String>,
+    pub skip_reason: Option<String>,
+    pub forced: bool,
+    pub rerun_of: Option<String>,
+    pub tags: Vec<String>,
+    pub git: Option<GitContext>,
+    pub host: Option<String>,
+    pub user: Option<String>,
+    pub cwd: Option<String>,
     pub started_at: OffsetDateTime,
     pub duration_ms: i64,
+    pub log_path: Option<String>,
+    pub matches: Vec<String>,
 }
 
-pub fn print_history(mut w: impl Write, rows: &[HistoryRow]) -> std::io::Result<()> {
+pub fn print_history(
+    mut w: impl Write,
+    rows: &[HistoryRow],
+    view: &HistoryView,
+) -> std::io::Result<()> {
     if rows.is_empty() {
         writeln!(w, "{} No run history yet.", info("i"))?;
         return Ok(());
     }
 
+    if !view.columns.is_empty() {
+        return print_history_table(w, rows, view);
+    }
+
     for (idx, row) in rows.iter().enumerate() {
         let source = match row.source {
             RunSource::Task => "task",
             RunSource::Inline => "inline",
+            RunSource::Schedule => "schedule",
         };
 
         let status = match row.status {
             RunStatus::Success => success("ok success"),
             RunStatus::Failed => failure("x failed"),
+            RunStatus::Skipped => muted("- skipped"),
+            RunStatus::Interrupted => warning("! interrupted"),
+            RunStatus::TimedOut => failure("x timed out"),
         };
 
-        let started = row
-            .started_at
-            .format(&time::macros::format_description!(
-                "[year]-[month]-[day] [hour]:[minute]:[second]"
-            ))
-            .unwrap_or_else(|_| "-".to_string());
-
-        writeln!(w, "{} {}", accent(&row.name), status)?;
+        if row.forced {
+            writeln!(w, "{} {} {}", accent(&row.name), status, muted("(forced)"))?;
+        } else {
+            writeln!(w, "{} {}", accent(&row.name), status)?;
+        }
+        writeln!(w, "  id: {}", muted(&row.id))?;
         writeln!(w, "  source: {}", source)?;
-        writeln!(w, "  exit: {}", number(&row.exit_code.to_string()))?;
-        writeln!(w, "  started (UTC): {}", started)?;
+        if let Some(original) = &row.rerun_of {
+            writeln!(w, "  rerun of: {}", muted(original))?;
+        }
+        if !row.tags.is_empty() {
+            writeln!(w, "  tags: {}", row.tags.join(", "))?;
+        }
+        if let Some(reason) = &row.skip_reason {
+            writeln!(w, "  reason: {reason}")?;
+        }
+        match &row.signal {
+            Some(signal) => writeln!(
+                w,
+                "  exit: {} (killed by {signal})",
+                number(&row.exit_code.to_string())
+            )?,
+            None => writeln!(w, "  exit: {}", number(&row.exit_code.to_string()))?,
+        }
+        if let Some(git) = &row.git {
+            let short = &git.sha[..git.sha.len().min(7)];
+            let at = match &git.branch {
+                Some(branch) => format!("{branch}@{short}"),
+                None => short.to_string(),
+            };
+            if git.dirty {
+                writeln!(w, "  git: {} {}", muted(&at), warning("(dirty)"))?;
+            } else {
+                writeln!(w, "  git: {}", muted(&at))?;
+            }
+        }
+        match (&row.user, &row.host) {
+            (Some(user), Some(host)) => writeln!(w, "  host: {user}@{host}")?,
+            (None, Some(host)) => writeln!(w, "  host: {host}")?,
+            (Some(user), None) => writeln!(w, "  user: {user}")?,
+            (None, None) => {}
+        }
+        if let Some(cwd) = &row.cwd {
+            writeln!(w, "  cwd: {cwd}")?;
+        }
+        match view.relative_to {
+            Some(now) => writeln!(w, "  started: {}", format_relative(row.started_at, now))?,
+            None => writeln!(w, "  started (UTC): {}", format_started(row.started_at))?,
+        }
         writeln!(
             w,
             "  duration: {}",
             number(&format_duration_ms(row.duration_ms))
         )?;
+        if let Some(log_path) = &row.log_path {
+            writeln!(w, "  log: {log_path}")?;
+        }
+        for line in &row.matches {
+            writeln!(w, "  match: {line}")?;
+        }
 
         if idx + 1 < rows.len() {
             writeln!(w)?;
@@ -54,3 +195,232 @@ pub fn print_history(mut w: impl Write, rows: &[HistoryRow]) -> std::io::Result<
 
     Ok(())
 }
+
+fn print_history_table(
+    mut w: impl Write,
+    rows: &[HistoryRow],
+    view: &HistoryView,
+) -> std::io::Result<()> {
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            view.columns
+                .iter()
+                .map(|column| table_cell(row, column, view.relative_to))
+                .collect()
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = view.columns.iter().map(String::len).collect();
+    for row in &cells {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let heading: Vec<String> = view
+        .columns
+        .iter()
+        .zip(&widths)
+        .map(|(title, width)| format!("{title:<width$}"))
+        .collect();
+    writeln!(w, "{}", bold(heading.join("  ").trim_end()))?;
+
+    for (row, cells) in rows.iter().zip(&cells) {
+        let styled: Vec<String> = view
+            .columns
+            .iter()
+            .zip(cells)
+            .zip(&widths)
+            .enumerate()
+            .map(|(idx, ((column, cell), width))| {
+                let padded = if idx + 1 == cells.len() {
+                    cell.clone()
+                } else {
+                    format!("{cell:<width$}")
+                };
+                match column.as_str() {
+                    "name" => accent(&padded),
+                    "id" | "started" => muted(&padded),
+                    "status" => match row.status {
+                        RunStatus::Success => success(&padded),
+                        RunStatus::Failed | RunStatus::TimedOut => failure(&padded),
+                        RunStatus::Skipped => muted(&padded),
+                        RunStatus::Interrupted => warning(&padded),
+                    },
+                    _ => padded,
+                }
+            })
+            .collect();
+        writeln!(w, "{}", styled.join("  "))?;
+    }
+
+    Ok(())
+}
+
+fn table_cell(row: &HistoryRow, column: &str, relative_to: Option<OffsetDateTime>) -> String {
+    match column {
+        "id" => row.id.clone(),
+        "name" => row.name.clone(),
+        "source" => match row.source {
+            RunSource::Task => "task",
+            RunSource::Inline => "inline",
+            RunSource::Schedule => "schedule",
+        }
+        .to_string(),
+        "status" => match row.status {
+            RunStatus::Success => "success",
+            RunStatus::Failed => "failed",
+            RunStatus::Skipped => "skipped",
+            RunStatus::Interrupted => "interrupted",
+            RunStatus::TimedOut => "timed out",
+        }
+        .to_string(),
+        "exit" => match &row.signal {
+            Some(signal) => format!("{} ({signal})", row.exit_code),
+            None => row.exit_code.to_string(),
+        },
+        "started" => match relative_to {
+            Some(now) => format_relative(row.started_at, now),
+            None => format_started(row.started_at),
+        },
+        "duration" => format_duration_ms(row.duration_ms),
+        "rerun_of" => row.rerun_of.clone().unwrap_or_default(),
+        "tags" => row.tags.join(","),
+        "reason" => row.skip_reason.clone().unwrap_or_default(),
+        "git" => row
+            .git
+            .as_ref()
+            .map(|git| {
+                let short = &git.sha[..git.sha.len().min(7)];
+                let dirty = if git.dirty { "*" } else { "" };
+                match &git.branch {
+                    Some(branch) => format!("{branch}@{short}{dirty}"),
+                    None => format!("{short}{dirty}"),
+                }
+            })
+            .unwrap_or_default(),
+        "host" => row.host.clone().unwrap_or_default(),
+        "user" => row.user.clone().unwrap_or_default(),
+        "cwd" => row.cwd.clone().unwrap_or_default(),
+        "log" => row.log_path.clone().unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+fn format_started(at: OffsetDateTime) -> String {
+    at.format(&time::macros::format_description!(
+        "[year]-[month]-[day] [hour]:[minute]:[second]"
+    ))
+    .unwrap_or_else(|_| "-".to_string())
+}
+
+pub fn format_relative(at: OffsetDateTime, now: OffsetDateTime) -> String {
+    let secs = (now - at).whole_seconds();
+    match secs {
+        ..1 => "just now".to_string(),
+        1..60 => format!("{secs}s ago"),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+pub fn highlight_term(line: &str, term: &str) -> String {
+    if term.is_empty() {
+        return line.to_string();
+    }
+
+    let lower = line.to_ascii_lowercase();
+    let needle = term.to_ascii_lowercase();
+    let mut out = String::new();
+    let mut rest = 0;
+    for (start, _) in lower.match_indices(&needle) {
+        if start < rest {
+            continue;
+        }
+        out.push_str(&line[rest..start]);
+        out.push_str(&highlight(&line[start..start + needle.len()]));
+        rest = start + needle.len();
+    }
+    out.push_str(&line[rest..]);
+    out
+}
+
+pub fn print_history_csv(
+    mut w: impl Write,
+    records: &[RunRecord],
+    columns: &[&str],
+) -> std::io::Result<()> {
+    writeln!(w, "{}", columns.join(","))?;
+
+    for record in records {
+        let cells: Vec<String> = columns
+            .iter()
+            .map(|column| csv_field(&csv_value(record, column)))
+            .collect();
+        writeln!(w, "{}", cells.join(","))?;
+    }
+
+    Ok(())
+}
+
+fn csv_value(record: &RunRecord, column: &str) -> String {
+    match column {
+        "id" => record.id.clone(),
+        "name" => record.name.clone(),
+        "source" => match record.source {
+            RunSource::Task => "task",
+            RunSource::Inline => "inline",
+            RunSource::Schedule => "schedule",
+        }
+        .to_string(),
+        "status" => match record.status {
+            RunStatus::Success => "success",
+            RunStatus::Failed => "failed",
+            RunStatus::Skipped => "skipped",
+            RunStatus::Interrupted => "interrupted",
+            RunStatus::TimedOut => "timedout",
+        }
+        .to_string(),
+        "exit_code" => record.exit_code.to_string(),
+        "signal" => record.signal.clone().unwrap_or_default(),
+        "started_at" => record
+            .started_at
+            .format(&time::format_description::well_known::Rfc3339)
+            .unwrap_or_default(),
+        "duration_ms" => record.duration_ms.to_string(),
+        "command" => record.command_preview.clone(),
+        "skip_reason" => record.skip_reason.clone().unwrap_or_default(),
+        "log_path" => record.log_path.clone().unwrap_or_default(),
+        "rerun_of" => record.rerun_of.clone().unwrap_or_default(),
+        "tags" => record.tags.join(" "),
+        "git_sha" => record
+            .git
+            .as_ref()
+            .map(|git| git.sha.clone())
+            .unwrap_or_default(),
+        "git_branch" => record
+            .git
+            .as_ref()
+            .and_then(|git| git.branch.clone())
+            .unwrap_or_default(),
+        "git_dirty" => record
+            .git
+            .as_ref()
+            .map(|git| git.dirty.to_string())
+            .unwrap_or_default(),
+        "host" => record.host.clone().unwrap_or_default(),
+        "user" => record.user.clone().unwrap_or_default(),
+        "cwd" => record.cwd.clone().unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
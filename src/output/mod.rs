@@ -1,10 +1,20 @@
+mod diff;
 mod history;
+mod spinner;
+mod stats;
 mod style;
 mod tasks;
 
-pub use history::{HistoryRow, print_history};
+pub use diff::{Change, diff_lines, print_run_diff};
+pub use history::{
+    CSV_COLUMNS, DEFAULT_CSV_COLUMNS, HISTORY_COLUMNS, HistoryRow, HistoryView, csv_column,
+    format_relative, highlight_term, print_history, print_history_csv, text_column,
+};
+pub use spinner::Spinner;
+pub use stats::print_stats;
 pub use style::{
-    accent, bold, bullet, command, configure, failure, info, muted, number, success, warning,
+    accent, bold, bullet, command, configure, failure, highlight, info, muted, number, success,
+    tag, warning,
 };
 pub use tasks::{TaskRow, print_tasks};
 
@@ -0,0 +1,63 @@
+use std::io::{self, IsTerminal, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const TICK: Duration = Duration::from_millis(100);
+
+pub struct Spinner {
+    done: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl Spinner {
+    pub fn start(label: &str) -> Option<Self> {
+        if !io::stdout().is_terminal() || !io::stderr().is_terminal() {
+            return None;
+        }
+
+        let done = Arc::new(AtomicBool::new(false));
+        let label = label.to_string();
+        let handle = thread::spawn({
+            let done = Arc::clone(&done);
+            move || {
+                let started = Instant::now();
+                let mut frame = 0;
+                while !done.load(Ordering::Relaxed) {
+                    let elapsed = started.elapsed().as_secs();
+                    let mut stderr = io::stderr().lock();
+                    let _ = write!(
+                        stderr,
+                        "\r\x1b[2K{} {label} {}",
+                        super::info(FRAMES[frame % FRAMES.len()]),
+                        super::muted(&format!("{}:{:02}", elapsed / 60, elapsed % 60))
+                    );
+                    let _ = stderr.flush();
+                    drop(stderr);
+                    frame += 1;
+                    thread::sleep(TICK);
+                }
+
+                let mut stderr = io::stderr().lock();
+                let _ = write!(stderr, "\r\x1b[2K");
+                let _ = stderr.flush();
+            }
+        });
+
+        Some(Self {
+            done,
+            handle: Some(handle),
+        })
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.done.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
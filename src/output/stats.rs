@@ -0,0 +1,73 @@
+use crate::history::TaskStats;
+use crate::output::{bold, failure, format_duration_ms, info, muted, success, warning};
+use std::io::Write;
+
+pub fn print_stats(mut w: impl Write, rows: &[TaskStats]) -> std::io::Result<()> {
+    if rows.is_empty() {
+        writeln!(w, "{} No run history yet.", info("i"))?;
+        return Ok(());
+    }
+
+    let header = ["task", "runs", "success", "p50", "p95", "last failure"];
+    let cells: Vec<[String; 6]> = rows
+        .iter()
+        .map(|row| {
+            [
+                row.name.clone(),
+                row.runs.to_string(),
+                format!("{:.0}%", row.success_rate * 100.0),
+                format_duration_ms(row.p50_ms),
+                format_duration_ms(row.p95_ms),
+                row.last_failure
+                    .and_then(|at| {
+                        at.format(&time::macros::format_description!(
+                            "[year]-[month]-[day] [hour]:[minute]"
+                        ))
+                        .ok()
+                    })
+                    .unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+
+    let mut widths = header.map(str::len);
+    for row in &cells {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let heading: Vec<String> = header
+        .iter()
+        .zip(widths)
+        .map(|(title, width)| format!("{title:<width$}"))
+        .collect();
+    writeln!(w, "{}", bold(heading.join("  ").trim_end()))?;
+
+    for (row, cells) in rows.iter().zip(&cells) {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        let rate = if row.failures == 0 {
+            success(&padded[2])
+        } else if row.success_rate >= 0.9 {
+            warning(&padded[2])
+        } else {
+            failure(&padded[2])
+        };
+        let line = format!(
+            "{}  {}  {}  {}  {}  {}",
+            padded[0],
+            padded[1],
+            rate,
+            padded[3],
+            padded[4],
+            muted(&padded[5])
+        );
+        writeln!(w, "{}", line.trim_end())?;
+    }
+
+    Ok(())
+}
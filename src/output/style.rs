@@ -66,6 +66,18 @@ pub fn number(text: &str) -> String {
     style("96", text)
 }
 
+pub fn highlight(text: &str) -> String {
+    style("1;31", text)
+}
+
 pub fn bullet(text: &str) -> String {
     style("94", text)
 }
+
+pub fn tag(label: &str) -> String {
+    const PALETTE: [&str; 6] = ["36", "33", "35", "32", "34", "91"];
+    let hash = label.bytes().fold(0_usize, |acc, b| {
+        acc.wrapping_mul(31).wrapping_add(b as usize)
+    });
+    style(PALETTE[hash % PALETTE.len()], &format!("[{label}]"))
+}
@@ -1,4 +1,4 @@
-use crate::output::{bold, command, info, muted};
+use crate::output::{accent, bold, command, info, muted};
 use std::io::Write;
 
 #[derive(Debug, Clone)]
@@ -36,7 +36,18 @@ pub fn print_tasks(mut w: impl Write, rows: &[TaskRow]) -> std::io::Result<()> {
         });
     }
 
+    let mut current_namespace: Option<&str> = None;
+
     for (idx, row) in normalized.iter().enumerate() {
+        let namespace = row.name.rsplit_once(':').map(|(namespace, _)| namespace);
+        if let Some(prefix) = namespace
+            && namespace != current_namespace
+        {
+            writeln!(w, "{}", accent(&format!("{prefix}:*")))?;
+            writeln!(w)?;
+        }
+        current_namespace = namespace;
+
         writeln!(w, "{}", bold(&row.name))?;
 
         if !row.description.is_empty() {
@@ -0,0 +1,91 @@
+use crate::config::Prompt;
+use std::collections::HashMap;
+use std::io::{BufRead, IsTerminal, Write};
+use std::sync::Mutex;
+
+static TERMINAL: Mutex<()> = Mutex::new(());
+
+pub fn answer(
+    prompts: &[Prompt],
+    vars: &HashMap<String, String>,
+) -> Result<HashMap<String, String>, String> {
+    let interactive = std::io::stdin().is_terminal() && std::io::stderr().is_terminal();
+    let _terminal = TERMINAL
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    let mut answers = HashMap::new();
+    for prompt in prompts {
+        let value = match vars.get(&prompt.name) {
+            Some(value) => check_choice(prompt, value)?,
+            None if interactive => {
+                ask(prompt, &mut std::io::stdin().lock(), &mut std::io::stderr())?
+            }
+            None if !prompt.default.is_empty() => prompt.default.clone(),
+            None => {
+                return Err(format!(
+                    "prompt {:?} needs an answer; pass --var {}=<value>",
+                    prompt.name, prompt.name
+                ));
+            }
+        };
+        answers.insert(prompt.name.clone(), value);
+    }
+    Ok(answers)
+}
+
+pub fn ask(
+    prompt: &Prompt,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> Result<String, String> {
+    let mut question = if prompt.message.is_empty() {
+        prompt.name.clone()
+    } else {
+        prompt.message.clone()
+    };
+    if !prompt.choices.is_empty() {
+        question.push_str(&format!(" ({})", prompt.choices.join("/")));
+    }
+    if !prompt.default.is_empty() {
+        question.push_str(&format!(" [{}]", prompt.default));
+    }
+
+    loop {
+        write!(output, "{question}: ")
+            .and_then(|()| output.flush())
+            .map_err(|e| format!("write prompt: {e}"))?;
+
+        let mut line = String::new();
+        let read = input
+            .read_line(&mut line)
+            .map_err(|e| format!("read answer: {e}"))?;
+        if read == 0 {
+            return Err(format!("no answer for prompt {:?}", prompt.name));
+        }
+
+        let answer = match line.trim() {
+            "" => prompt.default.as_str(),
+            answer => answer,
+        };
+        if answer.is_empty() {
+            continue;
+        }
+        match check_choice(prompt, answer) {
+            Ok(value) => return Ok(value),
+            Err(err) => writeln!(output, "{err}").map_err(|e| format!("write prompt: {e}"))?,
+        }
+    }
+}
+
+fn check_choice(prompt: &Prompt, value: &str) -> Result<String, String> {
+    if prompt.choices.is_empty() || prompt.choices.iter().any(|choice| choice == value) {
+        Ok(value.to_string())
+    } else {
+        Err(format!(
+            "{value:?} is not one of {} for prompt {:?}",
+            prompt.choices.join(", "),
+            prompt.name
+        ))
+    }
+}
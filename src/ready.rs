@@ -0,0 +1,71 @@
+use reqwest::blocking::Client;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    Tcp(String),
+    Http(String),
+}
+
+impl Target {
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Tcp(addr) => format!("tcp {addr}"),
+            Self::Http(url) => url.clone(),
+        }
+    }
+}
+
+pub fn wait(target: &Target, timeout: Duration, stopped: impl Fn() -> bool) -> Result<(), String> {
+    let client = match target {
+        Target::Http(_) => Some(
+            Client::builder()
+                .timeout(PROBE_TIMEOUT)
+                .build()
+                .map_err(|e| format!("build client: {e}"))?,
+        ),
+        Target::Tcp(_) => None,
+    };
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if stopped() {
+            return Err(format!("stopped waiting for {}", target.describe()));
+        }
+
+        let ready = match (target, &client) {
+            (Target::Tcp(addr), _) => probe_tcp(addr),
+            (Target::Http(url), Some(client)) => client
+                .get(url)
+                .send()
+                .is_ok_and(|response| response.status().is_success()),
+            (Target::Http(_), None) => false,
+        };
+        if ready {
+            return Ok(());
+        }
+
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "{} was not ready after {}",
+                target.describe(),
+                humantime::format_duration(timeout)
+            ));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn probe_tcp(addr: &str) -> bool {
+    let Ok(addrs) = addr.to_socket_addrs() else {
+        return false;
+    };
+    addrs
+        .into_iter()
+        .any(|addr| TcpStream::connect_timeout(&addr, PROBE_TIMEOUT).is_ok())
+}
@@ -1,12 +1,226 @@
-use crate::model::RunStatus;
-use std::collections::HashMap;
-use std::io::{Read, Write};
-use std::process::{Command, ExitStatus, Stdio};
+use crate::model::{AttemptRecord, RunStatus};
+use rand::Rng;
+use regex::Regex;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::thread;
 use std::time::{Duration, Instant};
 use time::OffsetDateTime;
 use wait_timeout::ChildExt;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RetryStrategy {
+    Fixed,
+    Linear,
+    #[default]
+    Exponential,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RestartPolicy {
+    #[default]
+    OnFailure,
+    Always,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum StdinMode {
+    #[default]
+    Inherit,
+    Null,
+    File(PathBuf),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShellKind {
+    #[default]
+    Default,
+    Sh,
+    Bash,
+    Cmd,
+    Pwsh,
+    PowerShell,
+    User,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IoPriority {
+    #[default]
+    Normal,
+    Low,
+    Idle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Clone)]
+pub enum RunEvent {
+    AttemptStarted {
+        attempt: i32,
+        max_attempts: i32,
+    },
+    Output {
+        stream: Stream,
+        data: String,
+    },
+    AttemptFinished {
+        attempt: i32,
+        exit_code: i32,
+        duration: Duration,
+        error: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cause: Arc<OnceLock<Cancellation>>,
+    parent: Option<Arc<CancelToken>>,
+}
+
+#[derive(Debug)]
+struct Cancellation {
+    reason: String,
+    timed_out: bool,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn child(&self) -> Self {
+        Self {
+            cause: Arc::default(),
+            parent: Some(Arc::new(self.clone())),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancel_with("after a sibling task failed");
+    }
+
+    pub fn cancel_with(&self, reason: impl Into<String>) {
+        let _ = self.cause.set(Cancellation {
+            reason: reason.into(),
+            timed_out: false,
+        });
+    }
+
+    pub fn time_out(&self, reason: impl Into<String>) {
+        let _ = self.cause.set(Cancellation {
+            reason: reason.into(),
+            timed_out: true,
+        });
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cause().is_some()
+    }
+
+    pub fn timed_out(&self) -> bool {
+        self.cause().is_some_and(|cause| cause.timed_out)
+    }
+
+    pub fn reason(&self) -> Option<&str> {
+        self.cause().map(|cause| cause.reason.as_str())
+    }
+
+    fn cause(&self) -> Option<&Cancellation> {
+        self.cause
+            .get()
+            .or_else(|| self.parent.as_ref().and_then(|p| p.cause()))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OutputBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl OutputBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn take(&self) -> Vec<u8> {
+        std::mem::take(
+            &mut *self
+                .0
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        )
+    }
+}
+
+impl Write for OutputBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TeeFile(Arc<Mutex<File>>);
+
+impl TeeFile {
+    pub fn create(path: &Path) -> Result<Self, String> {
+        let file =
+            File::create(path).map_err(|e| format!("create tee file {}: {e}", path.display()))?;
+        Ok(Self(Arc::new(Mutex::new(file))))
+    }
+}
+
+impl Write for TeeFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .flush()
+    }
+}
+
+#[derive(Clone)]
+pub struct EventSink(Arc<dyn Fn(RunEvent) + Send + Sync>);
+
+impl EventSink {
+    pub fn new(emit: impl Fn(RunEvent) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(emit))
+    }
+
+    pub fn emit(&self, event: RunEvent) {
+        (self.0)(event);
+    }
+}
+
+impl fmt::Debug for EventSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EventSink")
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Request {
     pub name: String,
@@ -14,12 +228,36 @@ pub struct Request {
     pub use_shell: bool,
     pub exec: Vec<String>,
     pub shell: String,
+    pub shell_kind: ShellKind,
     pub dir: String,
     pub env: HashMap<String, String>,
+    pub inherit_env: bool,
+    pub interactive: bool,
+    pub stdin: StdinMode,
+    pub pty: bool,
+    pub combine_output: bool,
+    pub nice: Option<i32>,
+    pub io_priority: IoPriority,
     pub timeout: Duration,
+    pub deadline: Duration,
+    pub grace_period: Duration,
     pub retries: i32,
     pub retry_backoff: Duration,
+    pub retry_strategy: RetryStrategy,
+    pub retry_jitter: Duration,
+    pub retry_max_backoff: Duration,
+    pub retry_on_exit_codes: Vec<i32>,
+    pub retry_on_timeout: bool,
+    pub retry_when_output_matches: Option<Regex>,
     pub stream_output: bool,
+    pub output_prefix: Option<String>,
+    pub capture: Option<OutputBuffer>,
+    pub max_captured_output: Option<u64>,
+    pub tee: Option<TeeFile>,
+    pub log_path: Option<PathBuf>,
+    pub events: Option<EventSink>,
+    pub cancel: Option<CancelToken>,
+    pub container: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -28,33 +266,79 @@ pub struct RunResult {
     pub duration: Duration,
     pub exit_code: i32,
     pub status: RunStatus,
+    pub stdout_tail: Option<String>,
     pub stderr_tail: Option<String>,
+    pub signal: Option<String>,
+    pub attempts: Vec<AttemptRecord>,
 }
 
 #[derive(Debug, Clone)]
 pub struct RunFailure {
-    pub result: RunResult,
+    pub result: Box<RunResult>,
     pub message: String,
 }
 
+struct Attempt {
+    exit_code: i32,
+    stdout_tail: Option<String>,
+    stderr_tail: Option<String>,
+    error: Option<String>,
+    signal: Option<String>,
+    termination: Termination,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Termination {
+    Exited,
+    TimedOut,
+    Interrupted,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum StopSignal {
+    Terminate,
+    Interrupt,
+}
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+const TRUNCATION_MARKER: &[u8] = b"\n[otto: output truncated, max_captured_output reached]\n";
+const TAIL_LINES: usize = 10;
+const TAIL_CHARS: usize = 1400;
+const TAIL_BYTES: usize = 8 * 1024;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+pub fn interrupt() {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+pub fn clear_interrupt() {
+    INTERRUPTED.store(false, Ordering::SeqCst);
+}
+
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
 pub fn execute(req: &Request) -> Result<RunResult, RunFailure> {
     if req.retries < 0 {
         return Err(RunFailure {
-            result: failed_result(127, Duration::ZERO, None),
+            result: failed_result(127, Duration::ZERO),
             message: "retries must be >= 0".to_string(),
         });
     }
 
     if req.use_shell && req.shell.trim().is_empty() {
         return Err(RunFailure {
-            result: failed_result(127, Duration::ZERO, None),
+            result: failed_result(127, Duration::ZERO),
             message: "shell command is required".to_string(),
         });
     }
 
     if !req.use_shell && req.exec.is_empty() {
         return Err(RunFailure {
-            result: failed_result(127, Duration::ZERO, None),
+            result: failed_result(127, Duration::ZERO),
             message: "exec command is required".to_string(),
         });
     }
@@ -67,84 +351,633 @@ pub fn execute(req: &Request) -> Result<RunResult, RunFailure> {
 
     let start = OffsetDateTime::now_utc();
     let wall = Instant::now();
+    let deadline = (!req.deadline.is_zero()).then(|| wall + req.deadline);
     let attempts = req.retries + 1;
 
     let mut last_exit = 0;
+    let mut last_status = RunStatus::Failed;
+    let mut last_stdout = None;
     let mut last_stderr = None;
+    let mut last_signal = None;
     let mut last_error = String::new();
+    let mut history = Vec::new();
 
     for attempt in 0..attempts {
-        match run_once(req) {
-            Ok((code, stderr_tail, None)) => {
+        if let Some(cancel) = &req.cancel
+            && let Some(reason) = cancel.reason()
+        {
+            (last_exit, last_status) = if cancel.timed_out() {
+                (124, RunStatus::TimedOut)
+            } else {
+                (130, RunStatus::Interrupted)
+            };
+            last_error = format!("command cancelled {reason}");
+            break;
+        }
+
+        let mut termination = Termination::Exited;
+        let (timeout, capped) = attempt_timeout(req.timeout, deadline);
+        if let Some(events) = &req.events {
+            events.emit(RunEvent::AttemptStarted {
+                attempt: attempt + 1,
+                max_attempts: attempts,
+            });
+        }
+        let attempt_wall = Instant::now();
+        let outcome = run_once(req, timeout, attempt + 1, attempts);
+        let (exit_code, stderr_tail, error) = match &outcome {
+            Ok(run) => (run.exit_code, run.stderr_tail.clone(), run.error.clone()),
+            Err(err) => (127, None, Some(err.clone())),
+        };
+        history.push(AttemptRecord {
+            attempt: attempt + 1,
+            exit_code,
+            duration_ms: attempt_wall.elapsed().as_millis() as i64,
+            stderr_tail,
+        });
+        if let Some(events) = &req.events {
+            events.emit(RunEvent::AttemptFinished {
+                attempt: attempt + 1,
+                exit_code,
+                duration: attempt_wall.elapsed(),
+                error,
+            });
+        }
+        match outcome {
+            Ok(Attempt {
+                exit_code,
+                stdout_tail,
+                stderr_tail,
+                error: None,
+                signal,
+                ..
+            }) => {
                 return Ok(RunResult {
                     started_at: start,
                     duration: wall.elapsed(),
-                    exit_code: code,
+                    exit_code,
                     status: RunStatus::Success,
+                    stdout_tail,
                     stderr_tail,
+                    signal,
+                    attempts: history,
                 });
             }
-            Ok((code, stderr_tail, Some(err))) => {
-                last_exit = code;
+            Ok(Attempt {
+                exit_code,
+                stdout_tail,
+                stderr_tail,
+                error: Some(err),
+                signal,
+                termination: how,
+            }) => {
+                last_exit = exit_code;
+                last_stdout = stdout_tail;
                 last_stderr = stderr_tail;
+                last_signal = signal;
                 last_error = err;
+                termination = how;
+                last_status = if termination == Termination::TimedOut
+                    || (termination == Termination::Cancelled
+                        && req.cancel.as_ref().is_some_and(CancelToken::timed_out))
+                {
+                    RunStatus::TimedOut
+                } else {
+                    RunStatus::Failed
+                };
+                if termination == Termination::TimedOut && capped {
+                    last_error =
+                        format!("run exceeded deadline of {}", format_duration(req.deadline));
+                }
             }
             Err(err) => {
                 last_exit = 127;
+                last_stdout = None;
                 last_stderr = None;
+                last_signal = None;
                 last_error = err;
+                last_status = RunStatus::Failed;
             }
         }
 
+        if interrupted()
+            || termination == Termination::Interrupted
+            || (termination == Termination::Cancelled && last_status != RunStatus::TimedOut)
+        {
+            last_status = RunStatus::Interrupted;
+            break;
+        }
+
+        if termination == Termination::Cancelled {
+            break;
+        }
+
+        if capped && termination == Termination::TimedOut {
+            break;
+        }
+
+        if !should_retry(
+            req,
+            last_exit,
+            termination == Termination::TimedOut,
+            [&last_stdout, &last_stderr],
+        ) {
+            break;
+        }
+
         if attempt < attempts - 1 {
-            let wait = retry_backoff
-                .checked_mul(1_u32 << attempt)
-                .unwrap_or(Duration::from_secs(60));
+            let mut wait = retry_delay(
+                req.retry_strategy,
+                retry_backoff,
+                attempt as u32,
+                req.retry_max_backoff,
+            );
+            if !req.retry_jitter.is_zero() {
+                let jitter_ms = req.retry_jitter.as_millis() as u64;
+                wait = wait.saturating_add(Duration::from_millis(
+                    rand::rng().random_range(0..=jitter_ms),
+                ));
+            }
+            if let Some(deadline) = deadline
+                && Instant::now()
+                    .checked_add(wait)
+                    .is_none_or(|resume_at| resume_at >= deadline)
+            {
+                last_error = format!(
+                    "{last_error}; deadline of {} leaves no time to retry",
+                    format_duration(req.deadline)
+                );
+                break;
+            }
             thread::sleep(wait);
         }
     }
 
     Err(RunFailure {
-        result: RunResult {
+        result: Box::new(RunResult {
             started_at: start,
             duration: wall.elapsed(),
             exit_code: last_exit,
-            status: RunStatus::Failed,
+            status: last_status,
+            stdout_tail: last_stdout,
             stderr_tail: last_stderr,
-        },
+            signal: last_signal,
+            attempts: history,
+        }),
         message: last_error,
     })
 }
 
-fn run_once(req: &Request) -> Result<(i32, Option<String>, Option<String>), String> {
+fn attempt_timeout(timeout: Duration, deadline: Option<Instant>) -> (Duration, bool) {
+    let Some(deadline) = deadline else {
+        return (timeout, false);
+    };
+
+    let remaining = deadline
+        .saturating_duration_since(Instant::now())
+        .max(Duration::from_millis(1));
+    if timeout.is_zero() || remaining < timeout {
+        (remaining, true)
+    } else {
+        (timeout, false)
+    }
+}
+
+fn should_retry(
+    req: &Request,
+    exit_code: i32,
+    timed_out: bool,
+    tails: [&Option<String>; 2],
+) -> bool {
+    if timed_out {
+        return req.retry_on_timeout;
+    }
+
+    if let Some(pattern) = &req.retry_when_output_matches
+        && !tails
+            .into_iter()
+            .flatten()
+            .any(|tail| pattern.is_match(tail))
+    {
+        return false;
+    }
+
+    req.retry_on_exit_codes.is_empty() || req.retry_on_exit_codes.contains(&exit_code)
+}
+
+pub fn retry_delay(
+    strategy: RetryStrategy,
+    base: Duration,
+    attempt: u32,
+    max_backoff: Duration,
+) -> Duration {
+    let delay = match strategy {
+        RetryStrategy::Fixed => Some(base),
+        RetryStrategy::Linear => base.checked_mul(attempt.saturating_add(1)),
+        RetryStrategy::Exponential => 1_u32
+            .checked_shl(attempt)
+            .and_then(|factor| base.checked_mul(factor)),
+    }
+    .unwrap_or(Duration::MAX);
+
+    if !max_backoff.is_zero() && delay > max_backoff {
+        max_backoff
+    } else {
+        delay
+    }
+}
+
+fn run_once(
+    req: &Request,
+    timeout: Duration,
+    attempt: i32,
+    attempts: i32,
+) -> Result<Attempt, String> {
     let mut command = build_command(req)?;
     if !req.dir.is_empty() {
         command.current_dir(&req.dir);
     }
+    if !req.inherit_env {
+        command.env_clear();
+    }
     if !req.env.is_empty() {
         command.envs(&req.env);
     }
+    command.env("OTTO_ATTEMPT", attempt.to_string());
+    command.env("OTTO_MAX_ATTEMPTS", attempts.to_string());
+    set_priority(&mut command, req.nice, req.io_priority);
+    bind_to_parent(&mut command, req.container.is_some());
 
-    if req.stream_output {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    let foreground = if req.interactive || req.stdin == StdinMode::Inherit {
+        terminal::claim(&mut command)
+    } else {
+        None
+    };
+
+    let mut merged: Option<Box<dyn Read + Send>> = None;
+    if req.interactive {
+        command.stdin(Stdio::inherit());
         command.stdout(Stdio::inherit());
+        command.stderr(Stdio::inherit());
     } else {
-        command.stdout(Stdio::null());
+        command.stdin(match &req.stdin {
+            StdinMode::Inherit
+                if foreground.is_none() && terminal::busy() && std::io::stdin().is_terminal() =>
+            {
+                Stdio::null()
+            }
+            StdinMode::Inherit => Stdio::inherit(),
+            StdinMode::Null => Stdio::null(),
+            StdinMode::File(path) => Stdio::from(
+                File::open(path).map_err(|e| format!("open stdin file {}: {e}", path.display()))?,
+            ),
+        });
+        command.stdout(if captures_stdout(req) {
+            Stdio::piped()
+        } else {
+            Stdio::inherit()
+        });
+        command.stderr(Stdio::piped());
+        if req.combine_output {
+            let (reader, writer) = std::io::pipe().map_err(|e| format!("open output pipe: {e}"))?;
+            let stderr = writer
+                .try_clone()
+                .map_err(|e| format!("open output pipe: {e}"))?;
+            command.stdout(writer);
+            command.stderr(stderr);
+            merged = Some(Box::new(reader));
+        }
+        #[cfg(unix)]
+        if req.pty {
+            let pty = pty::open()?;
+            let stdout = pty
+                .slave
+                .try_clone()
+                .map_err(|e| format!("open pty: {e}"))?;
+            let stderr = pty
+                .slave
+                .try_clone()
+                .map_err(|e| format!("open pty: {e}"))?;
+            command.stdout(Stdio::from(stdout));
+            command.stderr(Stdio::from(stderr));
+            merged = Some(Box::new(pty.master));
+        }
     }
-    command.stderr(Stdio::piped());
+
+    let log = match &req.log_path {
+        Some(path) => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| format!("open run log: {e}"))?;
+            let written = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+            let limit = req
+                .max_captured_output
+                .map(|limit| limit.saturating_sub(written));
+            Some(Arc::new(Mutex::new(Capped::new(file, limit))))
+        }
+        None => None,
+    };
 
     let mut child = command.spawn().map_err(|e| format!("run command: {e}"))?;
+    drop(command);
+    let tree = ProcessTree::attach(&child);
+
+    let combined = merged.is_some();
+    let stdout_handle = match merged {
+        Some(reader) => Some(spawn_tee(
+            reader,
+            output_sink(req, Stream::Stdout),
+            req.output_prefix.clone(),
+            log.clone(),
+            req.events.clone().map(|events| (events, Stream::Stdout)),
+            req.max_captured_output,
+        )),
+        None => child.stdout.take().map(|stdout| {
+            spawn_tee(
+                stdout,
+                output_sink(req, Stream::Stdout),
+                req.output_prefix.clone(),
+                log.clone(),
+                req.events.clone().map(|events| (events, Stream::Stdout)),
+                req.max_captured_output,
+            )
+        }),
+    };
+    let stderr_handle = child.stderr.take().map(|stderr| {
+        spawn_tee(
+            stderr,
+            output_sink(req, Stream::Stderr),
+            req.output_prefix.clone(),
+            log.clone(),
+            req.events.clone().map(|events| (events, Stream::Stderr)),
+            req.max_captured_output,
+        )
+    });
+    if !req.interactive
+        && ((stdout_handle.is_none() && captures_stdout(req))
+            || (stderr_handle.is_none() && !combined))
+    {
+        return Err("failed to capture command output".to_string());
+    }
+
+    let (status, mut termination) = wait_child(
+        &mut child,
+        &tree,
+        timeout,
+        req.grace_period,
+        req.cancel.as_ref(),
+        foreground.as_ref(),
+    )?;
+    if termination == Termination::Exited
+        && foreground
+            .as_ref()
+            .is_some_and(|foreground| foreground.interrupted(&status))
+    {
+        interrupt();
+        termination = Termination::Interrupted;
+    }
+    drop(foreground);
+    if termination != Termination::Exited
+        && let Some(name) = &req.container
+    {
+        remove_container(name);
+    }
+    let stdout_tail = collect_tail(stdout_handle, "stdout")?;
+    let stderr_tail = collect_tail(stderr_handle, "stderr")?;
+
+    if termination == Termination::Interrupted {
+        return Ok(Attempt {
+            exit_code: 130,
+            stdout_tail,
+            stderr_tail,
+            error: Some("command interrupted".to_string()),
+            signal: None,
+            termination,
+        });
+    }
+
+    if termination == Termination::Cancelled {
+        let cancel = req.cancel.as_ref();
+        return Ok(Attempt {
+            exit_code: if cancel.is_some_and(CancelToken::timed_out) {
+                124
+            } else {
+                130
+            },
+            stdout_tail,
+            stderr_tail,
+            error: Some(format!(
+                "command cancelled {}",
+                cancel.and_then(CancelToken::reason).unwrap_or_default()
+            )),
+            signal: None,
+            termination,
+        });
+    }
+
+    if termination == Termination::TimedOut {
+        return Ok(Attempt {
+            exit_code: 124,
+            stdout_tail,
+            stderr_tail,
+            error: Some(format!(
+                "command timed out after {}",
+                format_duration(timeout)
+            )),
+            signal: None,
+            termination,
+        });
+    }
+
+    if status.success() {
+        return Ok(Attempt {
+            exit_code: 0,
+            stdout_tail,
+            stderr_tail,
+            error: None,
+            signal: None,
+            termination,
+        });
+    }
+
+    if let Some(signal) = terminating_signal(&status) {
+        let name = signal_name(signal);
+        return Ok(Attempt {
+            exit_code: 128 + signal,
+            stdout_tail,
+            stderr_tail,
+            error: Some(format!("command killed by {name}")),
+            signal: Some(name),
+            termination,
+        });
+    }
+
+    let code = status.code().unwrap_or(1);
+    Ok(Attempt {
+        exit_code: code,
+        stdout_tail,
+        stderr_tail,
+        error: Some(format!("command failed with exit code {code}")),
+        signal: None,
+        termination,
+    })
+}
+
+#[cfg(unix)]
+fn terminating_signal(status: &ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn terminating_signal(_status: &ExitStatus) -> Option<i32> {
+    None
+}
+
+fn signal_name(signal: i32) -> String {
+    let name = match signal {
+        1 => "SIGHUP",
+        2 => "SIGINT",
+        3 => "SIGQUIT",
+        4 => "SIGILL",
+        5 => "SIGTRAP",
+        6 => "SIGABRT",
+        8 => "SIGFPE",
+        9 => "SIGKILL",
+        11 => "SIGSEGV",
+        13 => "SIGPIPE",
+        14 => "SIGALRM",
+        15 => "SIGTERM",
+        _ => return format!("signal {signal}"),
+    };
+    name.to_string()
+}
+
+fn captures_stdout(req: &Request) -> bool {
+    !req.stream_output
+        || req.capture.is_some()
+        || req.tee.is_some()
+        || req.log_path.is_some()
+        || req.output_prefix.is_some()
+        || req.events.is_some()
+}
+
+fn output_sink(req: &Request, stream: Stream) -> Option<Box<dyn Write + Send>> {
+    let primary: Option<Box<dyn Write + Send>> = if let Some(capture) = &req.capture {
+        Some(Box::new(Capped::new(
+            capture.clone(),
+            req.max_captured_output,
+        )))
+    } else if req.stream_output {
+        Some(match stream {
+            Stream::Stdout => Box::new(std::io::stdout()),
+            Stream::Stderr => Box::new(std::io::stderr()),
+        })
+    } else {
+        None
+    };
+
+    let Some(tee) = &req.tee else {
+        return primary;
+    };
+    let mirror = StripAnsi::new(tee.clone());
+    Some(match primary {
+        Some(primary) => Box::new(Fanout(primary, mirror)),
+        None => Box::new(mirror),
+    })
+}
+
+struct Fanout<A, B>(A, B);
+
+impl<A: Write, B: Write> Write for Fanout<A, B> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let first = self.0.write_all(buf);
+        let second = self.1.write_all(buf);
+        first.and(second)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        let first = self.0.flush();
+        let second = self.1.flush();
+        first.and(second)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AnsiState {
+    Text,
+    Escape,
+    Csi,
+    Osc,
+}
+
+struct StripAnsi<W> {
+    inner: W,
+    state: AnsiState,
+}
+
+impl<W: Write> StripAnsi<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            state: AnsiState::Text,
+        }
+    }
+}
+
+impl<W: Write> Write for StripAnsi<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut plain = Vec::with_capacity(buf.len());
+        for &byte in buf {
+            self.state = match (self.state, byte) {
+                (_, 0x1b) => AnsiState::Escape,
+                (AnsiState::Text, _) => {
+                    plain.push(byte);
+                    AnsiState::Text
+                }
+                (AnsiState::Escape, b'[') => AnsiState::Csi,
+                (AnsiState::Escape, b']') => AnsiState::Osc,
+                (AnsiState::Escape, _) => AnsiState::Text,
+                (AnsiState::Csi, 0x40..=0x7e) => AnsiState::Text,
+                (AnsiState::Csi, _) => AnsiState::Csi,
+                (AnsiState::Osc, 0x07) => AnsiState::Text,
+                (AnsiState::Osc, _) => AnsiState::Osc,
+            };
+        }
+        self.inner.write_all(&plain)?;
+        Ok(buf.len())
+    }
 
-    let stderr = child
-        .stderr
-        .take()
-        .ok_or_else(|| "failed to capture stderr".to_string())?;
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
 
-    let stream_output = req.stream_output;
-    let stderr_handle = thread::spawn(move || {
-        let mut reader = std::io::BufReader::new(stderr);
+fn spawn_tee(
+    mut reader: impl Read + Send + 'static,
+    mut sink: Option<Box<dyn Write + Send>>,
+    prefix: Option<String>,
+    log: Option<Arc<Mutex<Capped<File>>>>,
+    events: Option<(EventSink, Stream)>,
+    limit: Option<u64>,
+) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
         let mut buf = [0_u8; 4096];
-        let mut all = Vec::new();
-        let mut sink = std::io::stderr().lock();
+        let mut tail = TailBuffer::new(
+            limit
+                .and_then(|limit| usize::try_from(limit).ok())
+                .map_or(TAIL_BYTES, |limit| limit.min(TAIL_BYTES)),
+        );
+        let mut pending = Vec::new();
+        let mut undecoded = Vec::new();
 
         loop {
             let read = match reader.read(&mut buf) {
@@ -154,79 +987,701 @@ fn run_once(req: &Request) -> Result<(i32, Option<String>, Option<String>), Stri
             };
 
             let chunk = &buf[..read];
-            if stream_output {
-                let _ = sink.write_all(chunk);
-                let _ = sink.flush();
+            if let Some(sink) = sink.as_mut() {
+                match &prefix {
+                    Some(prefix) => {
+                        pending.extend_from_slice(chunk);
+                        while let Some(end) = pending.iter().position(|b| *b == b'\n') {
+                            let line: Vec<u8> = pending.drain(..=end).collect();
+                            write_prefixed(sink, prefix, &line);
+                        }
+                    }
+                    None => {
+                        let _ = sink.write_all(chunk);
+                        let _ = sink.flush();
+                    }
+                }
             }
-            all.extend_from_slice(chunk);
+            if let Some(log) = &log {
+                let mut file = log.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                let _ = file.write_all(chunk);
+            }
+            if let Some((events, stream)) = &events {
+                undecoded.extend_from_slice(chunk);
+                let data = decode_utf8(&mut undecoded);
+                if !data.is_empty() {
+                    events.emit(RunEvent::Output {
+                        stream: *stream,
+                        data,
+                    });
+                }
+            }
+            tail.push(chunk);
         }
 
-        all
-    });
+        if let Some((events, stream)) = &events
+            && !undecoded.is_empty()
+        {
+            events.emit(RunEvent::Output {
+                stream: *stream,
+                data: String::from_utf8_lossy(&undecoded).into_owned(),
+            });
+        }
+        if let (Some(sink), Some(prefix)) = (sink.as_mut(), &prefix)
+            && !pending.is_empty()
+        {
+            pending.push(b'\n');
+            write_prefixed(sink, prefix, &pending);
+        }
 
-    let (status, timeout_hit) = wait_child(&mut child, req.timeout)?;
-    let stderr_bytes = stderr_handle
-        .join()
-        .map_err(|_| "stderr reader thread panicked".to_string())?;
+        let mut bytes = Vec::new();
+        if limit.is_some_and(|limit| tail.seen > limit) {
+            bytes.extend_from_slice(&TRUNCATION_MARKER[1..]);
+        }
+        bytes.extend(tail.bytes);
+        bytes
+    })
+}
 
-    let stderr_text = String::from_utf8_lossy(&stderr_bytes).to_string();
-    let stderr_tail = tail(&stderr_text, 10, 1400);
+struct TailBuffer {
+    bytes: VecDeque<u8>,
+    capacity: usize,
+    seen: u64,
+}
 
-    if timeout_hit {
-        return Ok((
-            124,
-            stderr_tail,
-            Some(format!(
-                "command timed out after {}",
-                format_duration(req.timeout)
-            )),
-        ));
+impl TailBuffer {
+    fn new(capacity: usize) -> Self {
+        Self {
+            bytes: VecDeque::with_capacity(capacity),
+            capacity,
+            seen: 0,
+        }
     }
 
-    if status.success() {
-        return Ok((0, stderr_tail, None));
+    fn push(&mut self, chunk: &[u8]) {
+        self.seen += chunk.len() as u64;
+        let chunk = &chunk[chunk.len().saturating_sub(self.capacity)..];
+        let overflow = (self.bytes.len() + chunk.len()).saturating_sub(self.capacity);
+        self.bytes.drain(..overflow);
+        self.bytes.extend(chunk);
     }
+}
 
-    let code = status.code().unwrap_or(1);
-    Ok((
-        code,
-        stderr_tail,
-        Some(format!("command failed with exit code {code}")),
+pub fn decode_utf8(bytes: &mut Vec<u8>) -> String {
+    let mut out = String::new();
+    let mut rest = bytes.as_slice();
+    loop {
+        match std::str::from_utf8(rest) {
+            Ok(text) => {
+                out.push_str(text);
+                rest = &[];
+                break;
+            }
+            Err(err) => {
+                let (valid, after) = rest.split_at(err.valid_up_to());
+                out.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                let Some(len) = err.error_len() else {
+                    rest = after;
+                    break;
+                };
+                out.push(char::REPLACEMENT_CHARACTER);
+                rest = &after[len..];
+            }
+        }
+    }
+    let consumed = bytes.len() - rest.len();
+    bytes.drain(..consumed);
+    out
+}
+
+struct Capped<W> {
+    inner: W,
+    remaining: Option<u64>,
+    truncated: bool,
+}
+
+impl<W: Write> Capped<W> {
+    fn new(inner: W, limit: Option<u64>) -> Self {
+        Self {
+            inner,
+            remaining: limit,
+            truncated: false,
+        }
+    }
+}
+
+impl<W: Write> Write for Capped<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.truncated {
+            return Ok(buf.len());
+        }
+        let Some(remaining) = self.remaining else {
+            self.inner.write_all(buf)?;
+            return Ok(buf.len());
+        };
+
+        let keep = buf
+            .len()
+            .min(usize::try_from(remaining).unwrap_or(usize::MAX));
+        self.inner.write_all(&buf[..keep])?;
+        self.remaining = Some(remaining - keep as u64);
+        if keep < buf.len() {
+            self.truncated = true;
+            self.inner.write_all(TRUNCATION_MARKER)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn write_prefixed(sink: &mut impl Write, prefix: &str, line: &[u8]) {
+    let mut out = Vec::with_capacity(prefix.len() + line.len());
+    out.extend_from_slice(prefix.as_bytes());
+    out.extend_from_slice(line);
+    let _ = sink.write_all(&out);
+    let _ = sink.flush();
+}
+
+fn collect_tail(
+    handle: Option<thread::JoinHandle<Vec<u8>>>,
+    label: &str,
+) -> Result<Option<String>, String> {
+    let Some(handle) = handle else {
+        return Ok(None);
+    };
+    let bytes = handle
+        .join()
+        .map_err(|_| format!("{label} reader thread panicked"))?;
+    Ok(tail(
+        &String::from_utf8_lossy(&bytes),
+        TAIL_LINES,
+        TAIL_CHARS,
     ))
 }
 
 fn wait_child(
-    child: &mut std::process::Child,
+    child: &mut Child,
+    tree: &ProcessTree,
     timeout: Duration,
-) -> Result<(ExitStatus, bool), String> {
-    if timeout.is_zero() {
-        let status = child.wait().map_err(|e| format!("wait command: {e}"))?;
-        return Ok((status, false));
+    grace_period: Duration,
+    cancel: Option<&CancelToken>,
+    foreground: Option<&terminal::Foreground>,
+) -> Result<(ExitStatus, Termination), String> {
+    let deadline = (!timeout.is_zero()).then(|| Instant::now() + timeout);
+
+    loop {
+        if let Some(foreground) = foreground {
+            foreground.resume_if_stopped(child);
+        }
+
+        if interrupted() {
+            let status = terminate(child, tree, grace_period, StopSignal::Interrupt)?;
+            return Ok((status, Termination::Interrupted));
+        }
+
+        if cancel.is_some_and(CancelToken::is_cancelled) {
+            let status = terminate(child, tree, grace_period, StopSignal::Terminate)?;
+            return Ok((status, Termination::Cancelled));
+        }
+
+        let slice = match deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    let status = terminate(child, tree, grace_period, StopSignal::Terminate)?;
+                    return Ok((status, Termination::TimedOut));
+                }
+                remaining.min(POLL_INTERVAL)
+            }
+            None => POLL_INTERVAL,
+        };
+
+        if let Some(status) = child
+            .wait_timeout(slice)
+            .map_err(|e| format!("wait command: {e}"))?
+        {
+            return Ok((status, Termination::Exited));
+        }
     }
+}
 
-    match child
-        .wait_timeout(timeout)
-        .map_err(|e| format!("wait command: {e}"))?
+fn terminate(
+    child: &mut Child,
+    tree: &ProcessTree,
+    grace_period: Duration,
+    signal: StopSignal,
+) -> Result<ExitStatus, String> {
+    if !grace_period.is_zero()
+        && tree.signal(child, signal)
+        && let Some(status) = child
+            .wait_timeout(grace_period)
+            .map_err(|e| format!("wait command: {e}"))?
     {
-        Some(status) => Ok((status, false)),
-        None => {
-            let _ = child.kill();
-            let status = child.wait().map_err(|e| format!("wait command: {e}"))?;
-            Ok((status, true))
+        tree.kill(child);
+        return Ok(status);
+    }
+
+    tree.kill(child);
+    child.wait().map_err(|e| format!("wait command: {e}"))
+}
+
+#[cfg(unix)]
+struct ProcessTree;
+
+#[cfg(unix)]
+impl ProcessTree {
+    fn attach(_child: &Child) -> Self {
+        Self
+    }
+
+    fn signal(&self, child: &Child, signal: StopSignal) -> bool {
+        let Ok(pid) = libc::pid_t::try_from(child.id()) else {
+            return false;
+        };
+        let signal = match signal {
+            StopSignal::Terminate => libc::SIGTERM,
+            StopSignal::Interrupt => libc::SIGINT,
+        };
+        unsafe { libc::kill(-pid, signal) == 0 }
+    }
+
+    fn kill(&self, child: &mut Child) {
+        if let Ok(pid) = libc::pid_t::try_from(child.id()) {
+            unsafe {
+                libc::kill(-pid, libc::SIGKILL);
+            }
+        }
+        let _ = child.kill();
+    }
+}
+
+#[cfg(unix)]
+mod terminal {
+    use std::io::IsTerminal;
+    use std::mem;
+    use std::os::unix::process::{CommandExt, ExitStatusExt};
+    use std::process::{Child, Command, ExitStatus};
+    use std::ptr;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static CLAIMED: AtomicBool = AtomicBool::new(false);
+
+    pub struct Foreground;
+
+    pub fn claim(command: &mut Command) -> Option<Foreground> {
+        if !std::io::stdin().is_terminal()
+            || unsafe { libc::tcgetpgrp(libc::STDIN_FILENO) != libc::getpgrp() }
+            || CLAIMED.swap(true, Ordering::SeqCst)
+        {
+            return None;
+        }
+
+        unsafe {
+            command.pre_exec(|| {
+                with_sigttou_ignored(|| {
+                    libc::tcsetpgrp(libc::STDIN_FILENO, libc::getpgrp());
+                });
+                Ok(())
+            });
+        }
+        Some(Foreground)
+    }
+
+    pub fn busy() -> bool {
+        CLAIMED.load(Ordering::SeqCst)
+    }
+
+    impl Foreground {
+        pub fn interrupted(&self, status: &ExitStatus) -> bool {
+            status.signal() == Some(libc::SIGINT)
+        }
+
+        pub fn resume_if_stopped(&self, child: &Child) {
+            let Ok(pid) = libc::pid_t::try_from(child.id()) else {
+                return;
+            };
+            let mut info: libc::siginfo_t = unsafe { mem::zeroed() };
+            let polled = unsafe {
+                libc::waitid(
+                    libc::P_PID,
+                    pid as libc::id_t,
+                    &mut info,
+                    libc::WSTOPPED | libc::WNOHANG | libc::WNOWAIT,
+                )
+            };
+            if polled != 0 || info.si_signo != libc::SIGCHLD || info.si_code != libc::CLD_STOPPED {
+                return;
+            }
+
+            unsafe {
+                with_sigttou_ignored(|| {
+                    libc::tcsetpgrp(libc::STDIN_FILENO, libc::getpgrp());
+                });
+                libc::raise(libc::SIGTSTP);
+                with_sigttou_ignored(|| {
+                    libc::tcsetpgrp(libc::STDIN_FILENO, pid);
+                });
+                libc::kill(-pid, libc::SIGCONT);
+            }
+        }
+    }
+
+    impl Drop for Foreground {
+        fn drop(&mut self) {
+            unsafe {
+                with_sigttou_ignored(|| {
+                    libc::tcsetpgrp(libc::STDIN_FILENO, libc::getpgrp());
+                });
+            }
+            CLAIMED.store(false, Ordering::SeqCst);
+        }
+    }
+
+    unsafe fn with_sigttou_ignored(f: impl FnOnce()) {
+        let mut ignore: libc::sigaction = unsafe { mem::zeroed() };
+        ignore.sa_sigaction = libc::SIG_IGN;
+        let mut previous: libc::sigaction = unsafe { mem::zeroed() };
+        unsafe {
+            libc::sigaction(libc::SIGTTOU, &ignore, &mut previous);
+        }
+        f();
+        unsafe {
+            libc::sigaction(libc::SIGTTOU, &previous, ptr::null_mut());
         }
     }
 }
 
+#[cfg(not(unix))]
+mod terminal {
+    use std::process::{Child, Command, ExitStatus};
+
+    pub struct Foreground;
+
+    pub fn claim(_command: &mut Command) -> Option<Foreground> {
+        None
+    }
+
+    pub fn busy() -> bool {
+        false
+    }
+
+    impl Foreground {
+        pub fn interrupted(&self, _status: &ExitStatus) -> bool {
+            false
+        }
+
+        pub fn resume_if_stopped(&self, _child: &Child) {}
+    }
+}
+
+#[cfg(unix)]
+mod pty {
+    use std::fs::File;
+    use std::io;
+    use std::mem;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::ptr;
+
+    pub struct Pty {
+        pub master: File,
+        pub slave: OwnedFd,
+    }
+
+    pub fn open() -> Result<Pty, String> {
+        let mut master = -1;
+        let mut slave = -1;
+        let mut size: libc::winsize = unsafe { mem::zeroed() };
+        let has_size =
+            unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+        let size_ptr = if has_size {
+            &mut size as *mut libc::winsize
+        } else {
+            ptr::null_mut()
+        };
+
+        let opened = unsafe {
+            libc::openpty(
+                &mut master,
+                &mut slave,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                size_ptr,
+            )
+        };
+        if opened != 0 {
+            return Err(format!("open pty: {}", io::Error::last_os_error()));
+        }
+
+        let master = unsafe { File::from_raw_fd(master) };
+        let slave = unsafe { OwnedFd::from_raw_fd(slave) };
+
+        let mut termios: libc::termios = unsafe { mem::zeroed() };
+        if unsafe { libc::tcgetattr(slave.as_raw_fd(), &mut termios) } == 0 {
+            termios.c_oflag &= !libc::OPOST;
+            unsafe {
+                libc::tcsetattr(slave.as_raw_fd(), libc::TCSANOW, &termios);
+            }
+        }
+
+        Ok(Pty { master, slave })
+    }
+}
+
+#[cfg(windows)]
+struct ProcessTree {
+    job: Option<windows_job::Job>,
+}
+
+#[cfg(windows)]
+impl ProcessTree {
+    fn attach(child: &Child) -> Self {
+        Self {
+            job: windows_job::Job::assign(child),
+        }
+    }
+
+    fn signal(&self, _child: &Child, _signal: StopSignal) -> bool {
+        false
+    }
+
+    fn kill(&self, child: &mut Child) {
+        if let Some(job) = &self.job {
+            job.terminate();
+        }
+        let _ = child.kill();
+    }
+}
+
+#[cfg(windows)]
+mod windows_job {
+    use std::os::windows::io::AsRawHandle;
+    use std::process::Child;
+    use std::ptr;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+        JOBOBJECT_BASIC_LIMIT_INFORMATION, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JobObjectExtendedLimitInformation, SetInformationJobObject, TerminateJobObject,
+    };
+
+    pub struct Job(HANDLE);
+
+    impl Job {
+        pub fn assign(child: &Child) -> Option<Self> {
+            let handle = unsafe { CreateJobObjectW(ptr::null(), ptr::null()) };
+            if handle.is_null() {
+                return None;
+            }
+            let job = Self(handle);
+            let limits = JOBOBJECT_EXTENDED_LIMIT_INFORMATION {
+                BasicLimitInformation: JOBOBJECT_BASIC_LIMIT_INFORMATION {
+                    LimitFlags: JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let configured = unsafe {
+                SetInformationJobObject(
+                    job.0,
+                    JobObjectExtendedLimitInformation,
+                    (&raw const limits).cast(),
+                    size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+                )
+            };
+            if configured == 0 {
+                return None;
+            }
+            let assigned =
+                unsafe { AssignProcessToJobObject(job.0, child.as_raw_handle() as HANDLE) };
+            (assigned != 0).then_some(job)
+        }
+
+        pub fn terminate(&self) {
+            unsafe {
+                TerminateJobObject(self.0, 1);
+            }
+        }
+    }
+
+    impl Drop for Job {
+        fn drop(&mut self) {
+            unsafe {
+                CloseHandle(self.0);
+            }
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+struct ProcessTree;
+
+#[cfg(not(any(unix, windows)))]
+impl ProcessTree {
+    fn attach(_child: &Child) -> Self {
+        Self
+    }
+
+    fn signal(&self, _child: &Child, _signal: StopSignal) -> bool {
+        false
+    }
+
+    fn kill(&self, child: &mut Child) {
+        let _ = child.kill();
+    }
+}
+
+fn remove_container(name: &str) {
+    let _ = Command::new("docker")
+        .args(["rm", "-f", name])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+#[cfg(target_os = "linux")]
+fn bind_to_parent(command: &mut Command, graceful: bool) {
+    use std::os::unix::process::CommandExt;
+
+    let parent = unsafe { libc::getpid() };
+    let signal = if graceful {
+        libc::SIGTERM
+    } else {
+        libc::SIGKILL
+    };
+    unsafe {
+        command.pre_exec(move || {
+            if libc::prctl(libc::PR_SET_PDEATHSIG, signal) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            if libc::getppid() != parent {
+                libc::raise(signal);
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn bind_to_parent(_command: &mut Command, _graceful: bool) {}
+
+#[cfg(unix)]
+fn set_priority(command: &mut Command, nice: Option<i32>, io_priority: IoPriority) {
+    use std::os::unix::process::CommandExt;
+
+    if nice.is_none() && io_priority == IoPriority::Normal {
+        return;
+    }
+
+    unsafe {
+        command.pre_exec(move || {
+            if let Some(nice) = nice
+                && libc::setpriority(libc::PRIO_PROCESS, 0, nice) != 0
+            {
+                return Err(std::io::Error::last_os_error());
+            }
+            #[cfg(target_os = "linux")]
+            {
+                const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+                let value = match io_priority {
+                    IoPriority::Normal => None,
+                    IoPriority::Low => Some((2 << 13) | 7),
+                    IoPriority::Idle => Some(3 << 13),
+                };
+                if let Some(value) = value {
+                    libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, value);
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(windows)]
+fn set_priority(command: &mut Command, nice: Option<i32>, _io_priority: IoPriority) {
+    use std::os::windows::process::CommandExt;
+
+    const IDLE_PRIORITY_CLASS: u32 = 0x0000_0040;
+    const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x0000_4000;
+    const ABOVE_NORMAL_PRIORITY_CLASS: u32 = 0x0000_8000;
+
+    match nice {
+        Some(nice) if nice >= 15 => {
+            command.creation_flags(IDLE_PRIORITY_CLASS);
+        }
+        Some(nice) if nice > 0 => {
+            command.creation_flags(BELOW_NORMAL_PRIORITY_CLASS);
+        }
+        Some(nice) if nice < 0 => {
+            command.creation_flags(ABOVE_NORMAL_PRIORITY_CLASS);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn set_priority(_command: &mut Command, _nice: Option<i32>, _io_priority: IoPriority) {}
+
+fn user_shell() -> Option<String> {
+    std::env::var("SHELL")
+        .ok()
+        .filter(|shell| !shell.trim().is_empty())
+}
+
+fn default_shell() -> String {
+    if cfg!(target_os = "windows") {
+        return "sh".to_string();
+    }
+    if Path::new("/bin/sh").exists() {
+        return "/bin/sh".to_string();
+    }
+    user_shell().unwrap_or_else(|| "sh".to_string())
+}
+
 fn build_command(req: &Request) -> Result<Command, String> {
     if req.use_shell {
-        if cfg!(target_os = "windows") {
-            let mut cmd = Command::new("cmd");
-            cmd.arg("/C").arg(&req.shell);
-            return Ok(cmd);
-        }
+        let kind = match req.shell_kind {
+            ShellKind::Default if cfg!(target_os = "windows") => ShellKind::Cmd,
+            ShellKind::Default => ShellKind::Sh,
+            kind => kind,
+        };
 
-        let mut cmd = Command::new("/bin/sh");
-        cmd.arg("-c").arg(&req.shell);
+        let mut cmd = match kind {
+            ShellKind::Cmd => {
+                let mut cmd = Command::new("cmd");
+                cmd.arg("/C");
+                cmd
+            }
+            ShellKind::Pwsh | ShellKind::PowerShell => {
+                let program = if kind == ShellKind::Pwsh {
+                    "pwsh"
+                } else {
+                    "powershell"
+                };
+                let mut cmd = Command::new(program);
+                cmd.args(["-NoProfile", "-NonInteractive", "-Command"]);
+                cmd
+            }
+            ShellKind::Bash => {
+                let mut cmd = Command::new("bash");
+                cmd.arg("-c");
+                cmd
+            }
+            ShellKind::User => {
+                let mut cmd = Command::new(user_shell().unwrap_or_else(default_shell));
+                cmd.arg("-c");
+                cmd
+            }
+            _ => {
+                let mut cmd = Command::new(default_shell());
+                cmd.arg("-c");
+                cmd
+            }
+        };
+        cmd.arg(&req.shell);
         return Ok(cmd);
     }
 
@@ -241,14 +1696,17 @@ fn build_command(req: &Request) -> Result<Command, String> {
     Ok(cmd)
 }
 
-fn failed_result(exit_code: i32, duration: Duration, stderr_tail: Option<String>) -> RunResult {
-    RunResult {
+fn failed_result(exit_code: i32, duration: Duration) -> Box<RunResult> {
+    Box::new(RunResult {
         started_at: OffsetDateTime::now_utc(),
         duration,
         exit_code,
         status: RunStatus::Failed,
-        stderr_tail,
-    }
+        stdout_tail: None,
+        stderr_tail: None,
+        signal: None,
+        attempts: Vec::new(),
+    })
 }
 
 pub fn tail(input: &str, line_limit: usize, char_limit: usize) -> Option<String> {
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use time::OffsetDateTime;
+
+pub const DEFAULT_DIR: &str = ".otto/runs";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetachedRun {
+    pub id: String,
+    pub name: String,
+    pub pid: u32,
+    #[serde(with = "time::serde::rfc3339")]
+    pub started_at: OffsetDateTime,
+    pub log_path: String,
+    #[serde(skip)]
+    pub exit_code: Option<i32>,
+}
+
+impl DetachedRun {
+    pub fn is_running(&self) -> bool {
+        self.exit_code.is_none() && is_alive(self.pid)
+    }
+
+    pub fn status(&self) -> &'static str {
+        match self.exit_code {
+            Some(0) => "success",
+            Some(130) => "interrupted",
+            Some(_) => "failed",
+            None if is_alive(self.pid) => "running",
+            None => "exited",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Store {
+    root: PathBuf,
+}
+
+impl Store {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn log_path(&self, id: &str) -> PathBuf {
+        self.root.join(format!("{id}.log"))
+    }
+
+    pub fn save(&self, run: &DetachedRun) -> Result<(), String> {
+        fs::create_dir_all(&self.root).map_err(|e| format!("create runs directory: {e}"))?;
+        let text = serde_json::to_string_pretty(run).map_err(|e| format!("encode run: {e}"))?;
+        fs::write(self.state_path(&run.id), text).map_err(|e| format!("write run state: {e}"))
+    }
+
+    pub fn finish(&self, id: &str, exit_code: i32) -> Result<(), String> {
+        fs::write(self.root.join(format!("{id}.exit")), exit_code.to_string())
+            .map_err(|e| format!("write run exit code: {e}"))
+    }
+
+    pub fn list(&self) -> Result<Vec<DetachedRun>, String> {
+        let entries = match fs::read_dir(&self.root) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(format!("read runs directory: {err}")),
+        };
+
+        let mut runs = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|e| format!("read runs directory: {e}"))?
+                .path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+
+            let text =
+                fs::read_to_string(&path).map_err(|e| format!("read {}: {e}", path.display()))?;
+            let mut run: DetachedRun = serde_json::from_str(&text)
+                .map_err(|e| format!("parse {}: {e}", path.display()))?;
+            run.exit_code = fs::read_to_string(path.with_extension("exit"))
+                .ok()
+                .and_then(|code| code.trim().parse().ok());
+            runs.push(run);
+        }
+
+        runs.sort_by_key(|run| Reverse(run.started_at));
+        Ok(runs)
+    }
+
+    pub fn find(&self, id: &str) -> Result<DetachedRun, String> {
+        let runs = self.list()?;
+        if let Some(run) = runs.iter().find(|run| run.id == id) {
+            return Ok(run.clone());
+        }
+
+        let mut matches: Vec<DetachedRun> = runs
+            .into_iter()
+            .filter(|run| run.id.starts_with(id))
+            .collect();
+        match matches.len() {
+            1 => Ok(matches.remove(0)),
+            0 => Err(format!("no detached run with id {id:?}")),
+            n => Err(format!("id {id:?} matches {n} runs; use more characters")),
+        }
+    }
+
+    fn state_path(&self, id: &str) -> PathBuf {
+        self.root.join(format!("{id}.json"))
+    }
+}
+
+#[cfg(unix)]
+pub fn is_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    unsafe { libc::kill(pid, 0) == 0 }
+}
+
+#[cfg(not(unix))]
+pub fn is_alive(_pid: u32) -> bool {
+    true
+}
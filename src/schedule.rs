@@ -0,0 +1,115 @@
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Schedule {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let expanded = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "expected 5 fields (minute hour day month weekday), got {}",
+                fields.len()
+            ));
+        };
+
+        let mut weekdays = parse_field(weekday, "weekday", 0, 7)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+
+        Ok(Self {
+            minutes: parse_field(minute, "minute", 0, 59)?,
+            hours: parse_field(hour, "hour", 0, 23)?,
+            days: parse_field(day, "day", 1, 31)?,
+            months: parse_field(month, "month", 1, 12)?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+
+    pub fn matches(&self, at: OffsetDateTime) -> bool {
+        let day_matches = self.days & (1 << at.day()) != 0;
+        let weekday_matches = self.weekdays & (1 << at.weekday().number_days_from_sunday()) != 0;
+        let calendar_matches = match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (false, true) => day_matches,
+            (true, false) => weekday_matches,
+            (false, false) => day_matches || weekday_matches,
+        };
+
+        calendar_matches
+            && self.minutes & (1 << at.minute()) != 0
+            && self.hours & (1 << at.hour()) != 0
+            && self.months & (1 << u8::from(at.month())) != 0
+    }
+}
+
+fn parse_field(text: &str, name: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut mask = 0_u64;
+
+    for part in text.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| format!("invalid {name} step {step:?}"))?;
+                if step == 0 {
+                    return Err(format!("{name} step must be greater than 0"));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                parse_value(start, name, min, max)?,
+                parse_value(end, name, min, max)?,
+            )
+        } else {
+            let start = parse_value(range, name, min, max)?;
+            (start, if step > 1 { max } else { start })
+        };
+
+        if start > end {
+            return Err(format!("invalid {name} range {range:?}"));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+
+    Ok(mask)
+}
+
+fn parse_value(text: &str, name: &str, min: u32, max: u32) -> Result<u32, String> {
+    let value: u32 = text
+        .parse()
+        .map_err(|_| format!("invalid {name} {text:?}"))?;
+    if value < min || value > max {
+        return Err(format!("{name} {value} is outside {min}-{max}"));
+    }
+    Ok(value)
+}
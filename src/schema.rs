@@ -0,0 +1,125 @@
+use serde_json::{Map, Value, json};
+
+const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+const REQUIRED: &[&str] = &[
+    "id",
+    "name",
+    "source",
+    "command_preview",
+    "started_at",
+    "duration_ms",
+    "exit_code",
+    "status",
+];
+
+pub fn run() -> Value {
+    let mut properties = common_properties();
+    properties.insert("error".to_string(), json!({ "type": "string" }));
+
+    json!({
+        "$schema": DRAFT,
+        "$id": "https://github.com/mcmanussliam/otto/schema/run.json",
+        "title": "otto run --json",
+        "type": "object",
+        "properties": properties,
+        "required": REQUIRED,
+        "additionalProperties": false,
+        "$defs": defs(),
+    })
+}
+
+pub fn history() -> Value {
+    let mut properties = common_properties();
+    properties.insert(
+        "args".to_string(),
+        json!({ "type": "array", "items": { "type": "string" } }),
+    );
+    properties.insert(
+        "env".to_string(),
+        json!({ "type": "array", "items": { "type": "string" } }),
+    );
+    properties.insert(
+        "env_hashes".to_string(),
+        json!({ "type": "object", "additionalProperties": { "type": "string" } }),
+    );
+    properties.insert("replay".to_string(), json!({ "$ref": "#/$defs/replay" }));
+    properties.insert("log_path".to_string(), json!({ "type": "string" }));
+
+    json!({
+        "$schema": DRAFT,
+        "$id": "https://github.com/mcmanussliam/otto/schema/history.json",
+        "title": "otto history --json",
+        "type": "array",
+        "items": {
+            "type": "object",
+            "properties": properties,
+            "required": REQUIRED,
+            "additionalProperties": false,
+        },
+        "$defs": defs(),
+    })
+}
+
+fn common_properties() -> Map<String, Value> {
+    let Value::Object(properties) = json!({
+        "id": { "type": "string" },
+        "name": { "type": "string" },
+        "source": { "enum": ["task", "inline", "schedule"] },
+        "command_preview": { "type": "string" },
+        "started_at": { "type": "string", "format": "date-time" },
+        "duration_ms": { "type": "integer", "minimum": 0 },
+        "exit_code": { "type": "integer" },
+        "status": { "enum": ["success", "failed", "skipped", "interrupted", "timedout"] },
+        "stdout_tail": { "type": "string" },
+        "stderr_tail": { "type": "string" },
+        "signal": { "type": "string" },
+        "skip_reason": { "type": "string" },
+        "forced": { "type": "boolean" },
+        "rerun_of": { "type": "string" },
+        "tags": { "type": "array", "items": { "type": "string" } },
+        "git": { "$ref": "#/$defs/git" },
+        "host": { "type": "string" },
+        "user": { "type": "string" },
+        "cwd": { "type": "string" },
+        "attempts": { "type": "array", "items": { "$ref": "#/$defs/attempt" } },
+    }) else {
+        return Map::new();
+    };
+    properties
+}
+
+fn defs() -> Value {
+    json!({
+        "attempt": {
+            "type": "object",
+            "properties": {
+                "attempt": { "type": "integer", "minimum": 1 },
+                "exit_code": { "type": "integer" },
+                "duration_ms": { "type": "integer", "minimum": 0 },
+                "stderr_tail": { "type": "string" },
+            },
+            "required": ["attempt", "exit_code", "duration_ms"],
+            "additionalProperties": false,
+        },
+        "git": {
+            "type": "object",
+            "properties": {
+                "sha": { "type": "string" },
+                "branch": { "type": "string" },
+                "dirty": { "type": "boolean" },
+            },
+            "required": ["sha", "dirty"],
+            "additionalProperties": false,
+        },
+        "replay": {
+            "type": "object",
+            "properties": {
+                "env_file": { "type": "string" },
+                "no_dotenv": { "type": "boolean" },
+                "clean_env": { "type": "boolean" },
+                "vars": { "type": "object", "additionalProperties": { "type": "string" } },
+            },
+            "additionalProperties": false,
+        },
+    })
+}
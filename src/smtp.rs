@@ -0,0 +1,139 @@
+use lettre::address::{Address, Envelope};
+use lettre::transport::smtp::authentication::{Credentials, Mechanism};
+use lettre::transport::smtp::client::{Tls, TlsParameters};
+use lettre::transport::smtp::extension::ClientId;
+use lettre::{SmtpTransport, Transport};
+use std::net::IpAddr;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Security {
+    None,
+    StartTls,
+    Tls,
+}
+
+impl Security {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "none" => Some(Self::None),
+            "starttls" => Some(Self::StartTls),
+            "tls" => Some(Self::Tls),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Server {
+    pub host: String,
+    pub port: u16,
+    pub security: Security,
+    pub credentials: Option<(String, String)>,
+    pub timeout: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub from: String,
+    pub to: Vec<String>,
+    pub body: String,
+}
+
+pub fn address(value: &str) -> Result<&str, String> {
+    if value.contains(['\r', '\n']) {
+        return Err(format!("{value:?} contains a line break"));
+    }
+    let value = value.trim();
+    let addr = match value.rfind('<') {
+        Some(start) if value.ends_with('>') => &value[start + 1..value.len() - 1],
+        _ => value,
+    };
+    if addr.is_empty()
+        || !addr.contains('@')
+        || addr.contains(['<', '>'])
+        || addr.chars().any(char::is_whitespace)
+    {
+        return Err(format!("{value:?} is not an email address"));
+    }
+    Ok(addr)
+}
+
+pub fn recipient(value: &str) -> Result<&str, String> {
+    if value.contains(['<', '>']) {
+        return Err(format!("{value:?} must be a bare email address"));
+    }
+    address(value)
+}
+
+fn is_loopback(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+pub fn send(server: &Server, message: &Message) -> Result<(), String> {
+    let from = address(&message.from)?;
+    let to = message
+        .to
+        .iter()
+        .map(|to| recipient(to))
+        .collect::<Result<Vec<_>, _>>()?;
+    if server.credentials.is_some()
+        && server.security == Security::None
+        && !is_loopback(&server.host)
+    {
+        return Err(format!(
+            "refusing to send credentials to {} without TLS or STARTTLS",
+            server.host
+        ));
+    }
+
+    let envelope = Envelope::new(
+        Some(parse_address(from)?),
+        to.into_iter()
+            .map(parse_address)
+            .collect::<Result<Vec<_>, _>>()?,
+    )
+    .map_err(|e| format!("envelope: {e}"))?;
+
+    let tls = match server.security {
+        Security::None => Tls::None,
+        Security::StartTls => Tls::Required(tls_parameters(&server.host)?),
+        Security::Tls => Tls::Wrapper(tls_parameters(&server.host)?),
+    };
+    let mut transport = SmtpTransport::builder_dangerous(server.host.as_str())
+        .port(server.port)
+        .tls(tls)
+        .hello_name(ClientId::Domain("otto".to_string()))
+        .timeout(Some(server.timeout));
+    if let Some((username, password)) = &server.credentials {
+        transport = transport
+            .credentials(Credentials::new(username.clone(), password.clone()))
+            .authentication(vec![Mechanism::Plain]);
+    }
+
+    let mut data = String::new();
+    for line in message.body.lines() {
+        data.push_str(line);
+        data.push_str("\r\n");
+    }
+    transport
+        .build()
+        .send_raw(&envelope, data.as_bytes())
+        .map(drop)
+        .map_err(|e| format!("send to {}:{}: {e}", server.host, server.port))
+}
+
+fn parse_address(value: &str) -> Result<Address, String> {
+    value
+        .parse()
+        .map_err(|e| format!("{value:?} is not an email address: {e}"))
+}
+
+fn tls_parameters(host: &str) -> Result<TlsParameters, String> {
+    TlsParameters::new(host.to_string()).map_err(|e| format!("configure TLS: {e}"))
+}
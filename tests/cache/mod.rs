@@ -0,0 +1,38 @@
+use otto_cli::cache::{Cache, fingerprint, outputs_exist};
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn fingerprint_changes_with_input_contents() {
+    let dir = tempdir().expect("tempdir");
+    fs::create_dir_all(dir.path().join("src")).expect("create src");
+    fs::write(dir.path().join("src/a.txt"), "one").expect("write input");
+
+    let inputs = vec!["src/*.txt".to_string()];
+    let first = fingerprint(dir.path(), &inputs, "cmd").expect("fingerprint");
+    let again = fingerprint(dir.path(), &inputs, "cmd").expect("fingerprint");
+    assert_eq!(first, again);
+
+    fs::write(dir.path().join("src/a.txt"), "two").expect("rewrite input");
+    let changed = fingerprint(dir.path(), &inputs, "cmd").expect("fingerprint");
+    assert_ne!(first, changed);
+
+    let salted = fingerprint(dir.path(), &inputs, "other cmd").expect("fingerprint");
+    assert_ne!(changed, salted);
+}
+
+#[test]
+fn cache_round_trip_and_outputs() {
+    let dir = tempdir().expect("tempdir");
+    let cache = Cache::new(dir.path().join("cache"));
+
+    assert!(cache.load("build:web").is_none());
+    cache.store("build:web", "abc").expect("store");
+    assert_eq!(cache.load("build:web").as_deref(), Some("abc"));
+
+    let outputs = vec!["dist/*".to_string()];
+    assert!(!outputs_exist(dir.path(), &outputs).expect("outputs"));
+    fs::create_dir_all(dir.path().join("dist")).expect("create dist");
+    fs::write(dir.path().join("dist/app"), "bin").expect("write output");
+    assert!(outputs_exist(dir.path(), &outputs).expect("outputs"));
+}
@@ -1,7 +1,10 @@
 use predicates::prelude::*;
 use serde_json::Value;
 use std::fs;
+use std::path::Path;
 use tempfile::tempdir;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
 
 #[test]
 fn run_inline_json_preserves_unknown_variable_token() {
@@ -115,3 +118,2868 @@ tasks:
     assert!(parsed["issues"][0]["message"].as_str().is_some());
     assert!(parsed["error"].as_str().is_some());
 }
+
+#[test]
+fn run_task_hooks_wrap_main_command() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  deploy:
+    run: touch main
+    before:
+      run: exit 4
+    on_failure:
+      exec: ["touch", "failed"]
+    after:
+      run: touch after
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "deploy"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("before hook"));
+
+    assert!(!dir.path().join("main").exists());
+    assert!(dir.path().join("failed").exists());
+    assert!(dir.path().join("after").exists());
+}
+
+#[test]
+fn run_global_hooks_receive_run_context() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+hooks:
+  pre_run:
+    run: echo "$OTTO_TASK_NAME" > pre.txt
+  post_run:
+    run: echo "$OTTO_TASK_NAME $OTTO_RUN_STATUS" > post.txt
+
+tasks:
+  build:
+    run: exit 2
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "build"])
+        .assert()
+        .failure();
+
+    let pre = fs::read_to_string(dir.path().join("pre.txt")).expect("pre hook output");
+    assert_eq!(pre.trim(), "build");
+    let post = fs::read_to_string(dir.path().join("post.txt")).expect("post hook output");
+    assert_eq!(post.trim(), "build failed");
+}
+
+#[test]
+fn run_global_hooks_report_timeouts_and_run_from_the_config_dir() {
+    let dir = tempdir().expect("tempdir");
+    fs::create_dir_all(dir.path().join("nested")).expect("create nested");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+dir_relative_to: config
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+hooks:
+  post_run:
+    run: echo "$OTTO_RUN_STATUS" > post.txt
+
+tasks:
+  slow:
+    run: sleep 5
+    timeout: 200ms
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path().join("nested"))
+        .args(["run", "slow", "--config", "../otto.yml"])
+        .assert()
+        .failure();
+
+    let post = fs::read_to_string(dir.path().join("post.txt")).expect("post hook output");
+    assert_eq!(post.trim(), "timedout");
+    assert!(!dir.path().join("nested/post.txt").exists());
+}
+
+#[test]
+fn run_task_dir_relative_to_config() {
+    let dir = tempdir().expect("tempdir");
+    fs::create_dir_all(dir.path().join("nested")).expect("create nested");
+    fs::create_dir_all(dir.path().join("assets")).expect("create assets");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+dir_relative_to: config
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  mark:
+    dir: assets
+    run: touch marker
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path().join("nested"))
+        .args(["run", "mark", "--config", "../otto.yml"])
+        .assert()
+        .success();
+
+    assert!(dir.path().join("assets").join("marker").exists());
+}
+
+#[test]
+fn mutex_locks_live_next_to_the_config() {
+    let dir = tempdir().expect("tempdir");
+    fs::create_dir_all(dir.path().join("nested")).expect("create nested");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 2
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  seed:
+    mutex: db
+    exec: ["true"]
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path().join("nested"))
+        .args(["run", "seed", "--config", "../otto.yml"])
+        .assert()
+        .success();
+
+    assert!(dir.path().join(".otto/locks/db.lock").exists());
+    assert!(!dir.path().join("nested/.otto/locks").exists());
+}
+
+#[test]
+fn internal_tasks_are_hidden_and_not_runnable_directly() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  setup:
+    internal: true
+    run: touch setup-ran
+  ci:
+    tasks: ["setup"]
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["tasks", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: Value = serde_json::from_slice(&out).expect("tasks json");
+    let names: Vec<&str> = parsed
+        .as_array()
+        .expect("task list")
+        .iter()
+        .filter_map(|task| task["name"].as_str())
+        .collect();
+    assert_eq!(names, vec!["ci"]);
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "setup"])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("internal"));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "ci"])
+        .assert()
+        .success();
+    assert!(dir.path().join("setup-ran").exists());
+}
+
+#[test]
+fn validate_strict_fails_on_warnings() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+tasks:
+  test:
+    exec: ["echo", "ok"]
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["validate"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("tasks.test.description"));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["validate", "--json", "--strict"])
+        .assert()
+        .failure()
+        .code(2)
+        .get_output()
+        .stdout
+        .clone();
+
+    let parsed: Value = serde_json::from_slice(&out).expect("validate json");
+    assert_eq!(parsed["valid"], false);
+    assert_eq!(parsed["warnings"][0]["field"], "tasks.test.description");
+}
+
+#[test]
+fn run_wildcard_executes_matching_namespaced_tasks() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  build:frontend:
+    run: touch frontend
+  build:backend:
+    run: touch backend
+  test:
+    run: touch test
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "build:*"])
+        .assert()
+        .success();
+
+    assert!(dir.path().join("frontend").exists());
+    assert!(dir.path().join("backend").exists());
+    assert!(!dir.path().join("test").exists());
+}
+
+#[test]
+fn bare_invocation_runs_default_task() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+default_task: build
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  build:
+    run: touch built
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path()).assert().success();
+    assert!(dir.path().join("built").exists());
+}
+
+#[test]
+fn input_cache_is_shared_across_working_directories() {
+    let dir = tempdir().expect("tempdir");
+    fs::create_dir_all(dir.path().join("nested")).expect("create nested");
+    fs::write(dir.path().join("input.txt"), "v1").expect("write input");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 2
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  build:
+    inputs: ["input.txt"]
+    run: echo ran >> runs.log
+"#,
+    )
+    .expect("write config");
+
+    let run_json = |cwd: &Path, config: &str| {
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+        let out = cmd
+            .current_dir(cwd)
+            .args(["run", "build", "--json", "--config", config])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        serde_json::from_slice::<Value>(&out).expect("run json")
+    };
+
+    assert_eq!(run_json(dir.path(), "otto.yml")["status"], "success");
+    let skipped = run_json(&dir.path().join("nested"), "../otto.yml");
+    assert_eq!(skipped["status"], "skipped");
+    assert!(!dir.path().join("nested/.otto/cache").exists());
+}
+
+#[test]
+fn run_skips_task_when_inputs_unchanged() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(dir.path().join("input.txt"), "v1").expect("write input");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  build:
+    inputs: ["input.txt"]
+    run: echo ran >> runs.log
+"#,
+    )
+    .expect("write config");
+
+    let run_json = || {
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+        let out = cmd
+            .current_dir(dir.path())
+            .args(["run", "build", "--json"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        serde_json::from_slice::<Value>(&out).expect("run json")
+    };
+
+    assert_eq!(run_json()["status"], "success");
+    let skipped = run_json();
+    assert_eq!(skipped["status"], "skipped");
+    assert_eq!(skipped["skip_reason"], "inputs unchanged");
+
+    fs::write(dir.path().join("input.txt"), "v2").expect("rewrite input");
+    assert_eq!(run_json()["status"], "success");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["run", "build", "--force", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let forced: Value = serde_json::from_slice(&out).expect("run json");
+    assert_eq!(forced["status"], "success");
+    assert_eq!(forced["forced"], true);
+
+    let log = fs::read_to_string(dir.path().join("runs.log")).expect("runs log");
+    assert_eq!(log.lines().count(), 3);
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["history", "--limit", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(forced)"));
+}
+
+#[test]
+fn run_parallel_group_respects_max_parallel() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  one:
+    run: mkdir slot && sleep 0.2 && rmdir slot
+  two:
+    run: mkdir slot && sleep 0.2 && rmdir slot
+  three:
+    run: mkdir slot && sleep 0.2 && rmdir slot
+  all:
+    tasks: ["one", "two", "three"]
+    parallel: true
+    max_parallel: 1
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "all"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn run_sequential_group_continue_on_error_runs_remaining_tasks() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  flaky:
+    run: exit 1
+  lint:
+    continue_on_error: true
+    run: exit 2
+  report:
+    run: touch reported
+  ci:
+    tasks: ["flaky", "lint", "report"]
+    continue_on_error: true
+  partial:
+    tasks: ["lint", "flaky", "report"]
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "ci"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("flaky:"))
+        .stderr(predicate::str::contains("lint:"));
+    assert!(dir.path().join("reported").exists());
+    fs::remove_file(dir.path().join("reported")).expect("reset marker");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "partial"])
+        .assert()
+        .failure();
+    assert!(!dir.path().join("reported").exists());
+}
+
+#[test]
+fn history_settings_control_location_and_recording() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 2
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+history:
+  path: runs/log.jsonl
+
+tasks:
+  hello:
+    run: "true"
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "hello"])
+        .assert()
+        .success();
+    assert!(dir.path().join("runs/log.jsonl").exists());
+    assert!(!dir.path().join(".otto/history.jsonl").exists());
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["history", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: Value = serde_json::from_slice(&out).expect("history json");
+    assert_eq!(parsed.as_array().map(Vec::len), Some(1));
+
+    let text = fs::read_to_string(dir.path().join("otto.yml")).expect("read config");
+    fs::write(
+        dir.path().join("otto.yml"),
+        text.replace(
+            "  path: runs/log.jsonl",
+            "  path: runs/log.jsonl\n  enabled: false",
+        ),
+    )
+    .expect("rewrite config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "hello"])
+        .assert()
+        .success();
+    let lines = fs::read_to_string(dir.path().join("runs/log.jsonl")).expect("read history");
+    assert_eq!(lines.lines().count(), 1);
+}
+
+#[test]
+fn history_opt_out_skips_recording() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 2
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  secret:
+    run: "true"
+    history: false
+  normal:
+    run: "true"
+"#,
+    )
+    .expect("write config");
+
+    for args in [vec!["run", "secret"], vec!["run", "normal", "--no-history"]] {
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+        cmd.current_dir(dir.path()).args(args).assert().success();
+    }
+    assert!(!dir.path().join(".otto/history.jsonl").exists());
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "normal"])
+        .assert()
+        .success();
+    let text = fs::read_to_string(dir.path().join(".otto/history.jsonl")).expect("history");
+    assert_eq!(text.lines().count(), 1);
+}
+
+#[test]
+fn run_inline_expands_defaults_and_escaped_dollars() {
+    let dir = tempdir().expect("tempdir");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .env_remove("OTTO_NOPE")
+        .args([
+            "run",
+            "--json",
+            "--",
+            "echo",
+            "${OTTO_NOPE:-fallback}",
+            "$$HOME",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let parsed: Value = serde_json::from_slice(&out).expect("run json");
+    assert_eq!(parsed["command_preview"], "echo fallback $HOME");
+}
+
+#[test]
+fn run_inline_fails_on_missing_required_variable() {
+    let dir = tempdir().expect("tempdir");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .env_remove("OTTO_TOKEN")
+        .args(["run", "--", "echo", "${OTTO_TOKEN:?set a deploy token}"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("OTTO_TOKEN: set a deploy token"));
+}
+
+#[test]
+fn run_steps_in_order_with_step_continue_on_error() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 2
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  pipeline:
+    env:
+      GREETING: hi
+    steps:
+      - name: write
+        run: echo "$GREETING" > out.txt
+      - name: optional
+        run: exit 3
+        continue_on_error: true
+      - exec: ["sh", "-c", "echo done >> out.txt"]
+  strict:
+    steps:
+      - run: exit 4
+      - run: touch never
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "pipeline"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("step \"optional\""));
+    let out = fs::read_to_string(dir.path().join("out.txt")).expect("read output");
+    assert_eq!(out, "hi\ndone\n");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "strict"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "step \"step-1\": command failed with exit code 4",
+        ));
+    assert!(!dir.path().join("never").exists());
+}
+
+#[test]
+fn run_group_with_inline_entries() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 2
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  build:
+    run: touch built
+  ci:
+    tasks:
+      - build
+      - run: test -f built && touch checked
+      - name: glue
+        exec: ["sh", "-c", "exit 5"]
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "ci"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("glue:"));
+    assert!(dir.path().join("checked").exists());
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["tasks", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: Value = serde_json::from_slice(&out).expect("tasks json");
+    let ci = parsed
+        .as_array()
+        .and_then(|items| items.iter().find(|item| item["name"] == "ci"))
+        .expect("ci task");
+    assert_eq!(
+        ci["command"],
+        "tasks (sequential): build, test -f built && touch checked, glue"
+    );
+}
+
+#[test]
+fn run_skips_task_on_unsupported_platform() {
+    let dir = tempdir().expect("tempdir");
+    let other = if cfg!(target_os = "windows") {
+        "linux"
+    } else {
+        "windows"
+    };
+    fs::write(
+        dir.path().join("otto.yml"),
+        format!(
+            r#"version: 2
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  elsewhere:
+    run: touch ran
+    platforms: ["{other}"]
+  all:
+    tasks: ["elsewhere", {{ run: "echo here" }}]
+"#
+        ),
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["run", "elsewhere", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: Value = serde_json::from_slice(&out).expect("run json");
+    assert_eq!(parsed["status"], "skipped");
+    assert_eq!(
+        parsed["skip_reason"],
+        format!("not supported on {}", std::env::consts::OS)
+    );
+    assert!(!dir.path().join("ran").exists());
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "all"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "2 sub-tasks sequentially, 1 skipped: elsewhere",
+        ));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["history", "--status", "skipped"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "reason: not supported on {}",
+            std::env::consts::OS
+        )));
+}
+
+#[test]
+fn history_open_prints_saved_run_log() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 2
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  noisy:
+    run: echo "to stdout"; echo "to stderr" >&2
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["run", "noisy", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: Value = serde_json::from_slice(&out).expect("run json");
+    let id = parsed["id"].as_str().expect("run id").to_string();
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["history", "open", &id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("to stdout"))
+        .stdout(predicate::str::contains("to stderr"));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["history", "open", "nope"])
+        .assert()
+        .code(2);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn killing_otto_stops_the_running_command() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("work.sh"),
+        "echo $$ > pid\nsleep 30\ntouch survived\n",
+    )
+    .expect("write script");
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin!("otto"))
+        .current_dir(dir.path())
+        .args(["run", "--", "sh", "work.sh"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .expect("spawn otto");
+
+    let pid_file = dir.path().join("pid");
+    let started = std::time::Instant::now();
+    while !pid_file.exists() && started.elapsed() < std::time::Duration::from_secs(10) {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    let pid = fs::read_to_string(&pid_file).expect("read pid");
+    let stat = format!("/proc/{}/stat", pid.trim());
+
+    child.kill().expect("kill otto");
+    child.wait().expect("wait otto");
+
+    let running = || {
+        fs::read_to_string(&stat)
+            .map(|text| !text.contains(") Z "))
+            .unwrap_or(false)
+    };
+    let killed = std::time::Instant::now();
+    while running() && killed.elapsed() < std::time::Duration::from_secs(5) {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    assert!(!running());
+}
+
+#[cfg(unix)]
+#[test]
+fn sigint_records_interrupted_run() {
+    let dir = tempdir().expect("tempdir");
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin!("otto"))
+        .current_dir(dir.path())
+        .args(["run", "--", "sleep", "30"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .expect("spawn otto");
+
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    let pid = libc::pid_t::try_from(child.id()).expect("pid");
+    unsafe {
+        libc::kill(pid, libc::SIGINT);
+    }
+
+    let status = child.wait().expect("wait otto");
+    assert_eq!(status.code(), Some(130));
+
+    let history = fs::read_to_string(dir.path().join(".otto/history.jsonl")).expect("history");
+    let record: Value = serde_json::from_str(history.trim()).expect("record");
+    assert_eq!(record["status"], "interrupted");
+    assert_eq!(record["exit_code"], 130);
+}
+
+#[cfg(unix)]
+#[test]
+fn watch_reruns_task_on_change() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  build:
+    run: cat src.txt >> out.txt
+    watch: ["src.txt"]
+"#,
+    )
+    .expect("write config");
+    fs::write(dir.path().join("src.txt"), "one\n").expect("write src");
+
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin!("otto"))
+        .current_dir(dir.path())
+        .args(["watch", "build", "--debounce", "50ms"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .expect("spawn otto");
+
+    let out = dir.path().join("out.txt");
+    let wait_for = |expected: &str| {
+        for _ in 0..100 {
+            if fs::read_to_string(&out).is_ok_and(|text| text == expected) {
+                return true;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        false
+    };
+
+    assert!(wait_for("one\n"));
+    fs::write(dir.path().join("src.txt"), "two!\n").expect("update src");
+    assert!(wait_for("one\ntwo!\n"));
+
+    let pid = libc::pid_t::try_from(child.id()).expect("pid");
+    unsafe {
+        libc::kill(pid, libc::SIGINT);
+    }
+    let status = child.wait().expect("wait otto");
+    assert_eq!(status.code(), Some(130));
+}
+
+#[cfg(unix)]
+#[test]
+fn detached_run_can_be_listed_followed_and_stopped() {
+    let dir = tempdir().expect("tempdir");
+    let nested = dir.path().join("nested");
+    fs::create_dir_all(&nested).expect("create nested");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args([
+            "run",
+            "--detach",
+            "--",
+            "sh",
+            "-c",
+            "echo started; sleep 30",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let id = String::from_utf8(out).expect("utf8").trim().to_string();
+    assert!(!id.is_empty());
+
+    let mut logged = false;
+    for _ in 0..100 {
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+        let out = cmd
+            .current_dir(&nested)
+            .args(["logs", &id, "--config", "../otto.yml"])
+            .output()
+            .expect("otto logs");
+        if String::from_utf8_lossy(&out.stdout).contains("started") {
+            logged = true;
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    assert!(logged);
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(&nested)
+        .args(["ps", "--config", "../otto.yml"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(&id).and(predicate::str::contains("running")));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(&nested)
+        .args(["stop", &id[..12], "--config", "../otto.yml"])
+        .assert()
+        .success();
+    assert!(!nested.join(".otto").exists());
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .arg("ps")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("interrupted"));
+}
+
+#[test]
+fn run_passes_stdin_through_or_from_file() {
+    let dir = tempdir().expect("tempdir");
+    fs::create_dir_all(dir.path().join("fixtures")).expect("create fixtures");
+    fs::write(dir.path().join("fixtures").join("input.txt"), "from-file").expect("write input");
+    fs::write(dir.path().join("fixtures").join("null"), "literal-null").expect("write input");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  echo-file:
+    dir: fixtures
+    stdin_file: input.txt
+    exec: ["cat"]
+  echo-null:
+    stdin: "null"
+    exec: ["cat"]
+  echo-stdin-file:
+    dir: fixtures
+    stdin_file: "null"
+    exec: ["cat"]
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "--", "cat"])
+        .write_stdin("piped-data")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("piped-data"));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "echo-file"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("from-file"));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "echo-null"])
+        .write_stdin("ignored")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ignored").not());
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "echo-stdin-file"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("literal-null"));
+
+    fs::write(dir.path().join("payload.json"), "from-flag").expect("write payload");
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "--stdin-file", "payload.json", "echo-null"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("from-flag"));
+}
+
+#[test]
+fn run_tee_mirrors_output_without_ansi() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  build:
+    run: printf '\033[32mcompiled\033[0m\n'; echo warned >&2
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "--no-history", "--tee", "build.log", "build"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[32mcompiled"))
+        .stderr(predicate::str::contains("warned"));
+
+    let text = fs::read_to_string(dir.path().join("build.log")).expect("read tee file");
+    assert!(text.contains("compiled\n"));
+    assert!(text.contains("warned\n"));
+    assert!(!text.contains('\x1b'));
+}
+
+#[test]
+fn run_exposes_context_variables_to_the_command() {
+    let dir = tempdir().expect("tempdir");
+    fs::create_dir(dir.path().join("sub")).expect("create sub");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 2
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  report:
+    dir: sub
+    run: echo "$OTTO_TASK_NAME|$OTTO_RUN_ID|$OTTO_CONFIG_DIR|$OTTO_STARTED_AT"
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["run", "report", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: Value = serde_json::from_slice(&out).expect("run json");
+    let line = parsed["stdout_tail"].as_str().expect("stdout tail");
+    let fields: Vec<&str> = line.split('|').collect();
+    assert_eq!(fields[0], "report");
+    assert_eq!(fields[1], parsed["id"]);
+    assert_eq!(
+        Path::new(fields[2]).canonicalize().expect("config dir"),
+        dir.path().canonicalize().expect("project dir")
+    );
+    assert!(OffsetDateTime::parse(fields[3], &Rfc3339).is_ok());
+}
+
+#[cfg(unix)]
+#[test]
+fn run_uses_login_shell_when_configured() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir().expect("tempdir");
+    let shell = dir.path().join("fakesh");
+    fs::write(&shell, "#!/bin/sh\necho \"fakesh $1 $2\"\n").expect("write shell");
+    fs::set_permissions(&shell, fs::Permissions::from_mode(0o755)).expect("chmod shell");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+  shell: $SHELL
+
+notifications:
+  desktop: false
+
+tasks:
+  greet:
+    run: echo hi
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .env("SHELL", &shell)
+        .args(["run", "greet", "--no-history"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("fakesh -c echo hi"));
+}
+
+#[test]
+fn run_parallel_group_prefixes_streamed_lines() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  web:
+    run: printf 'ready\nlistening'
+  api:
+    run: echo migrated >&2
+  dev:
+    tasks: ["web", "api"]
+    parallel: true
+  seq:
+    tasks: ["web"]
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .env("NO_COLOR", "1")
+        .args(["run", "dev"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[web] ready\n"))
+        .stdout(predicate::str::contains("[web] listening\n"))
+        .stderr(predicate::str::contains("[api] migrated\n"));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .env("NO_COLOR", "1")
+        .args(["run", "seq"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[web]").not());
+}
+
+#[test]
+fn run_jobs_caps_parallel_group() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  one:
+    run: mkdir slot && sleep 0.2 && rmdir slot
+  two:
+    run: mkdir slot && sleep 0.2 && rmdir slot
+  all:
+    tasks: ["one", "two"]
+    parallel: true
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "all", "--jobs", "1"])
+        .assert()
+        .success();
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "all", "--jobs", "0"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("--jobs must be at least 1"));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "one", "--jobs", "2"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "--jobs only applies to tasks that run a parallel group",
+        ));
+}
+
+#[test]
+fn run_jobs_is_shared_across_nested_parallel_groups() {
+    let dir = tempdir().expect("tempdir");
+    let leaf = "for s in 1 2; do mkdir slot$s 2>/dev/null && break; s=; done; \
+                [ -n \"$s\" ] && sleep 0.3 && rmdir slot$s";
+    fs::write(
+        dir.path().join("otto.yml"),
+        format!(
+            r#"version: 1
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  a1:
+    run: '{leaf}'
+  a2:
+    run: '{leaf}'
+  b1:
+    run: '{leaf}'
+  b2:
+    run: '{leaf}'
+  a:
+    tasks: ["a1", "a2"]
+    parallel: true
+  b:
+    tasks: ["b1", "b2"]
+    parallel: true
+  all:
+    tasks: ["a", "b"]
+    parallel: true
+"#
+        ),
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "all", "--jobs", "2", "--no-history"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn run_keep_going_continues_past_failures() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  "ci:a":
+    run: exit 1
+  "ci:b":
+    run: touch b-ran
+  all:
+    tasks: ["ci:a", "ci:b"]
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "ci:*"])
+        .assert()
+        .failure();
+    assert!(!dir.path().join("b-ran").exists());
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "ci:*", "--keep-going"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("1 of 2 tasks failed"));
+    assert!(dir.path().join("b-ran").exists());
+
+    fs::remove_file(dir.path().join("b-ran")).expect("remove marker");
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "all", "-k"])
+        .assert()
+        .failure();
+    assert!(dir.path().join("b-ran").exists());
+}
+
+#[test]
+fn run_clean_env_keeps_only_path_dotenv_and_task_env() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(dir.path().join(".env"), "FROM_DOTENV=yes\n").expect("write dotenv");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  check:
+    env:
+      FROM_TASK: "yes"
+    run: test -n "$$PATH" && test -z "$$HOST_ONLY" && test "$FROM_DOTENV" = yes && test "$FROM_TASK" = yes
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .env("HOST_ONLY", "leaked")
+        .args(["run", "check", "--no-inherit-env"])
+        .assert()
+        .success();
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .env("HOST_ONLY", "leaked")
+        .args(["run", "check"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn run_json_stream_emits_events() {
+    let dir = tempdir().expect("tempdir");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args([
+            "run",
+            "--json-stream",
+            "--no-history",
+            "--",
+            "echo",
+            "hello",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let events: Vec<Value> = String::from_utf8_lossy(&out)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("event json"))
+        .collect();
+    let kinds: Vec<&str> = events
+        .iter()
+        .map(|event| event["event"].as_str().expect("event kind"))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            "run_started",
+            "attempt_started",
+            "output_chunk",
+            "attempt_finished",
+            "run_finished"
+        ]
+    );
+    assert_eq!(events[2]["stream"], "stdout");
+    assert_eq!(events[2]["data"], "hello\n");
+    assert_eq!(events[4]["status"], "success");
+    assert_eq!(events[0]["id"], events[4]["id"]);
+}
+
+#[test]
+fn quiet_output_hides_command_output_until_failure() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+tasks:
+  ok:
+    run: echo visible-ok
+    output: quiet
+  broken:
+    run: echo visible-broken; exit 3
+    output: quiet
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["run", "ok", "--no-history"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    assert!(!String::from_utf8_lossy(&out.stdout).contains("visible-ok"));
+    assert!(!String::from_utf8_lossy(&out.stderr).contains("visible-ok"));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["run", "broken", "--no-history"])
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    assert!(String::from_utf8_lossy(&out.stderr).contains("visible-broken"));
+}
+
+#[cfg(unix)]
+#[test]
+fn container_task_runs_through_docker() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir().expect("tempdir");
+    let bin = dir.path().join("bin");
+    fs::create_dir(&bin).expect("create bin");
+    let docker = bin.join("docker");
+    fs::write(
+        &docker,
+        "#!/bin/sh\nprintf '%s|' \"$@\"\necho \"API=$API\"\n",
+    )
+    .expect("write docker");
+    fs::set_permissions(&docker, fs::Permissions::from_mode(0o755)).expect("chmod docker");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+tasks:
+  build:
+    run: cargo build
+    env:
+      API: token
+    container:
+      image: rust:1.80
+      volumes: ["./cache:/cache"]
+"#,
+    )
+    .expect("write config");
+
+    let path = format!(
+        "{}:{}",
+        bin.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .env("PATH", path)
+        .args(["run", "build", "--no-history"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let stdout = String::from_utf8_lossy(&out);
+    let project = dir.path().canonicalize().expect("canonical dir");
+    let project = project.display();
+    assert!(stdout.contains("run|--rm|--init|-i|--name|otto-"));
+    assert!(stdout.contains(&format!("|-v|{project}:{project}|")));
+    assert!(stdout.contains(&format!("-v|{project}/cache:/cache|-v|")));
+    assert!(stdout.contains(&format!("|-w|{project}|")));
+    assert!(stdout.contains(
+        "-e|API|-e|OTTO_ATTEMPT|-e|OTTO_CONFIG_DIR|-e|OTTO_MAX_ATTEMPTS|-e|OTTO_RUN_ID|\
+         -e|OTTO_STARTED_AT|-e|OTTO_TASK_NAME|-e|OTTO_TMPDIR|rust:1.80|sh|-c|cargo build|"
+    ));
+    let tmpdir = stdout
+        .split("|-v|")
+        .nth(3)
+        .and_then(|rest| rest.split('|').next())
+        .expect("temp dir volume");
+    let (host, guest) = tmpdir.split_once(':').expect("volume pair");
+    assert_eq!(host, guest);
+    assert!(host.contains("otto-"));
+    assert!(stdout.contains("API=token"));
+}
+
+#[cfg(unix)]
+#[test]
+fn timed_out_container_is_removed_by_name() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir().expect("tempdir");
+    let bin = dir.path().join("bin");
+    fs::create_dir(&bin).expect("create bin");
+    let docker = bin.join("docker");
+    fs::write(
+        &docker,
+        "#!/bin/sh
+if [ \"$1\" = rm ]; then echo \"$@\" > removed; exit 0; fi
+         while [ \"$1\" != --name ]; do shift; done
+echo \"$2\" > started
+exec sleep 5
+",
+    )
+    .expect("write docker");
+    fs::set_permissions(&docker, fs::Permissions::from_mode(0o755)).expect("chmod docker");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+tasks:
+  build:
+    run: cargo build
+    timeout: 200ms
+    container:
+      image: rust:1.80
+"#,
+    )
+    .expect("write config");
+
+    let path = format!(
+        "{}:{}",
+        bin.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .env("PATH", path)
+        .args(["run", "build", "--no-history"])
+        .assert()
+        .failure();
+
+    let started = fs::read_to_string(dir.path().join("started")).expect("read started");
+    let removed = fs::read_to_string(dir.path().join("removed")).expect("read removed");
+    assert!(started.starts_with("otto-"));
+    assert_eq!(removed.trim(), format!("rm -f {}", started.trim()));
+}
+
+#[test]
+fn fail_fast_group_cancels_running_siblings() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  broken:
+    run: sleep 0.2; exit 4
+  slow:
+    run: sleep 5; echo slow-finished
+  ci:
+    tasks: ["broken", "slow"]
+    parallel: true
+    fail_fast: true
+"#,
+    )
+    .expect("write config");
+
+    let started = std::time::Instant::now();
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "ci", "--no-history"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("slow-finished").not())
+        .stderr(predicate::str::contains(
+            "cancelled after a sibling task failed",
+        ));
+    assert!(started.elapsed() < std::time::Duration::from_secs(4));
+}
+
+#[test]
+fn group_timeout_cancels_remaining_children() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+  timeout: 1m
+
+notifications:
+  desktop: false
+
+tasks:
+  fast:
+    run: echo fast-done
+  slow:
+    run: sleep 5; echo slow-finished
+  after:
+    run: echo after-ran
+  ci:
+    tasks: ["fast", "slow", "after"]
+    timeout: 500ms
+"#,
+    )
+    .expect("write config");
+
+    let started = std::time::Instant::now();
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "ci"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("fast-done"))
+        .stdout(predicate::str::contains("slow-finished").not())
+        .stdout(predicate::str::contains("after-ran").not())
+        .stderr(predicate::str::contains("timed out after 500ms"));
+    assert!(started.elapsed() < std::time::Duration::from_secs(4));
+
+    let history = fs::read_to_string(dir.path().join(".otto/history.jsonl")).expect("history");
+    let records: Vec<Value> = history
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("record"))
+        .collect();
+    let group = records
+        .iter()
+        .find(|record| record["name"] == "ci")
+        .expect("group record");
+    assert_eq!(group["status"], "timedout");
+    assert_eq!(group["exit_code"], 124);
+    let slow = records
+        .iter()
+        .find(|record| record["name"] == "slow")
+        .expect("slow record");
+    assert_eq!(slow["status"], "timedout");
+    assert_eq!(slow["exit_code"], 124);
+}
+
+#[test]
+fn grouped_parallel_output_prints_contiguous_blocks() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  web:
+    run: echo web-1; sleep 0.3; echo web-2
+  api:
+    run: echo api-1; sleep 0.1; echo api-2 >&2
+  dev:
+    tasks: ["web", "api"]
+    parallel: true
+    output: grouped
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .env("NO_COLOR", "1")
+        .args(["run", "dev", "--no-history"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"\[web\] ok in \S+\nweb-1\nweb-2\n").expect("regex"))
+        .stdout(predicate::str::is_match(r"\[api\] ok in \S+\napi-1\napi-2\n").expect("regex"))
+        .stdout(predicate::str::contains("run \"web\" finished").not());
+}
+
+#[cfg(unix)]
+#[test]
+fn service_restarts_until_interrupted() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  worker:
+    run: echo tick >> ticks.txt; exit 1
+    service: true
+    restart: always
+    retry_backoff: 50ms
+    retry_strategy: fixed
+"#,
+    )
+    .expect("write config");
+
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin!("otto"))
+        .current_dir(dir.path())
+        .args(["run", "worker", "--no-history"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .expect("spawn otto");
+
+    let ticks = dir.path().join("ticks.txt");
+    let mut restarted = false;
+    for _ in 0..100 {
+        if fs::read_to_string(&ticks).is_ok_and(|text| text.lines().count() >= 3) {
+            restarted = true;
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    assert!(restarted);
+
+    let pid = libc::pid_t::try_from(child.id()).expect("pid");
+    unsafe {
+        libc::kill(pid, libc::SIGINT);
+    }
+    let status = child.wait().expect("wait otto");
+    assert_eq!(status.code(), Some(130));
+}
+
+#[test]
+fn wait_for_blocks_until_port_is_ready() {
+    let dir = tempdir().expect("tempdir");
+    let probe = std::net::TcpListener::bind("127.0.0.1:0").expect("bind probe");
+    let port = probe.local_addr().expect("probe addr").port();
+    drop(probe);
+    fs::write(
+        dir.path().join("otto.yml"),
+        format!(
+            r#"version: 1
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  seed:
+    run: echo seeded
+    wait_for:
+      tcp: 127.0.0.1:{port}
+      timeout: 10s
+  impatient:
+    run: echo never
+    wait_for:
+      tcp: 127.0.0.1:{port}
+      timeout: 300ms
+"#
+        ),
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "impatient", "--no-history"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("never").not())
+        .stderr(predicate::str::contains("was not ready after 300ms"));
+
+    let server = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        let listener = std::net::TcpListener::bind(("127.0.0.1", port)).expect("bind db");
+        let _ = listener.accept();
+    });
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "seed", "--no-history"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("seeded"));
+    server.join().expect("join server");
+}
+
+#[test]
+fn run_answers_prompts_from_var_flags() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 2
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  deploy:
+    prompts:
+      - name: DEPLOY_ENV
+        message: Deploy to
+        choices: [staging, production]
+      - name: REGION
+        default: eu-west-1
+    run: echo "$DEPLOY_ENV $REGION" > answers.txt
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "deploy", "--var", "DEPLOY_ENV=production"])
+        .assert()
+        .success();
+    let answers = fs::read_to_string(dir.path().join("answers.txt")).expect("answers");
+    assert_eq!(answers.trim(), "production eu-west-1");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "deploy"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "prompt \"DEPLOY_ENV\" needs an answer; pass --var DEPLOY_ENV=<value>",
+        ));
+}
+
+#[test]
+fn run_gives_each_run_a_temp_directory() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 2
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  pass:
+    run: touch "$OTTO_TMPDIR/scratch" && echo "$OTTO_TMPDIR" > pass.txt
+  fail:
+    keep_tmpdir: on-failure
+    run: touch "$OTTO_TMPDIR/scratch" && echo "$OTTO_TMPDIR" > fail.txt && exit 1
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "pass"])
+        .assert()
+        .success();
+    let pass_tmp = fs::read_to_string(dir.path().join("pass.txt")).expect("pass tmpdir");
+    assert!(!Path::new(pass_tmp.trim()).exists());
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "fail"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("kept temp directory"));
+    let fail_tmp = fs::read_to_string(dir.path().join("fail.txt")).expect("fail tmpdir");
+    let fail_tmp = Path::new(fail_tmp.trim());
+    assert_ne!(fail_tmp, Path::new(pass_tmp.trim()));
+    assert!(fail_tmp.join("scratch").exists());
+    fs::remove_dir_all(fail_tmp).expect("remove kept tmpdir");
+}
+
+#[test]
+fn run_no_retries_forces_a_single_attempt() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 2
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  flaky:
+    retries: 3
+    retry_backoff: 10ms
+    run: echo x >> attempts.txt && exit 1
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "flaky", "--no-retries"])
+        .assert()
+        .failure();
+    let attempts = fs::read_to_string(dir.path().join("attempts.txt")).expect("attempts");
+    assert_eq!(attempts.lines().count(), 1);
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .env("OTTO_NO_RETRY", "1")
+        .args(["run", "flaky"])
+        .assert()
+        .failure();
+    let attempts = fs::read_to_string(dir.path().join("attempts.txt")).expect("attempts");
+    assert_eq!(attempts.lines().count(), 2);
+}
+
+#[test]
+fn run_group_passes_named_outputs_to_later_tasks() {
+    let dir = tempdir().expect("tempdir");
+    fs::create_dir(dir.path().join("app")).expect("create app");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 2
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  build:
+    dir: app
+    outputs:
+      artifact: dist/app.txt
+    run: mkdir -p dist && echo built > dist/app.txt
+  package:
+    run: cp "${needs.build.outputs.artifact}" packaged.txt
+  release:
+    tasks: [build, package]
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "release"])
+        .assert()
+        .success();
+    let packaged = fs::read_to_string(dir.path().join("packaged.txt")).expect("packaged");
+    assert_eq!(packaged.trim(), "built");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "package"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "output \"artifact\" of task \"build\" is not available",
+        ));
+}
+
+#[test]
+fn run_expands_exec_globs_in_sorted_order() {
+    let dir = tempdir().expect("tempdir");
+    fs::create_dir_all(dir.path().join("proto/nested")).expect("create proto");
+    for file in ["proto/b.proto", "proto/a.proto", "proto/nested/c.proto"] {
+        fs::write(dir.path().join(file), "").expect("write proto");
+    }
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 2
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  gen:
+    glob: true
+    exec: ["echo", "proto/**/*.proto", "none/*.proto"]
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["run", "gen", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: Value = serde_json::from_slice(&out).expect("run json");
+    assert_eq!(
+        parsed["stdout_tail"],
+        "proto/a.proto proto/b.proto proto/nested/c.proto none/*.proto"
+    );
+}
+
+#[test]
+fn rerun_replays_inline_and_task_runs() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 2
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  flaky:
+    run: echo x >> flaky.txt && [ "$(wc -l < flaky.txt)" -gt 1 ]
+"#,
+    )
+    .expect("write config");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "--", "sh", "-c", r#"echo "$0" >> inline.txt"#, "a b"])
+        .assert()
+        .success();
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "flaky"])
+        .assert()
+        .failure();
+
+    let history = |dir: &Path| -> Vec<Value> {
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+        let out = cmd
+            .current_dir(dir)
+            .args(["history", "--json"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        serde_json::from_slice(&out).expect("history json")
+    };
+    let inline_id = history(dir.path())[1]["id"]
+        .as_str()
+        .expect("inline id")
+        .to_string();
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["rerun", "--last", "--failed"])
+        .assert()
+        .success();
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["rerun", &inline_id])
+        .assert()
+        .success();
+
+    let inline = fs::read_to_string(dir.path().join("inline.txt")).expect("inline output");
+    assert_eq!(inline, "a b\na b\n");
+    let rows = history(dir.path());
+    assert_eq!(rows[0]["name"], "inline");
+    assert_eq!(rows[0]["rerun_of"], inline_id.as_str());
+    assert_eq!(rows[1]["name"], "flaky");
+    assert_eq!(rows[1]["status"], "success");
+    assert_eq!(rows[1]["rerun_of"], rows[2]["id"]);
+}
+
+#[test]
+fn history_prune_removes_old_runs_and_their_logs() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 2
+
+defaults:
+  notify_on: never
+
+notifications:
+  desktop: false
+
+tasks:
+  build:
+    run: echo built
+"#,
+    )
+    .expect("write config");
+
+    for _ in 0..3 {
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+        cmd.current_dir(dir.path())
+            .args(["run", "build"])
+            .assert()
+            .success();
+    }
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["--no-color", "history", "prune", "--keep", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pruned 2 runs from history"));
+
+    let history = fs::read_to_string(dir.path().join(".otto/history.jsonl")).expect("read history");
+    assert_eq!(history.lines().count(), 1);
+    let logs = fs::read_dir(dir.path().join(".otto/logs")).expect("read logs");
+    assert_eq!(logs.count(), 1);
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["history", "prune"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn history_accepts_relative_and_absolute_time_bounds() {
+    let dir = tempdir().expect("tempdir");
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "--", "true"])
+        .assert()
+        .success();
+
+    let count = |args: &[&str]| -> usize {
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+        let out = cmd
+            .current_dir(dir.path())
+            .args(["history", "--json"])
+            .args(args)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        serde_json::from_slice::<Vec<Value>>(&out)
+            .expect("history json")
+            .len()
+    };
+    assert_eq!(count(&["--since", "1h"]), 1);
+    assert_eq!(
+        count(&["--since", "2000-01-01", "--until", "2000-01-02"]),
+        0
+    );
+    assert_eq!(count(&["--until", "2999-01-01T00:00:00Z"]), 1);
+    let today = time::OffsetDateTime::now_utc().date().to_string();
+    assert_eq!(count(&["--since", &today, "--until", &today]), 1);
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["history", "--since", "yesterday"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--since expects a duration"));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["history", "--since", "20000y"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "--since reaches too far into the past",
+        ));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["history", "prune", "--older-than", "20000y"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "--older-than reaches too far into the past",
+        ));
+}
+
+#[test]
+fn history_exports_csv_with_selected_columns() {
+    let dir = tempdir().expect("tempdir");
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "--", "echo", "a,b"])
+        .assert()
+        .success();
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args([
+            "history",
+            "--format",
+            "csv",
+            "--columns",
+            "status,exit_code,command",
+        ])
+        .output()
+        .expect("run history");
+    assert!(out.status.success());
+    let text = String::from_utf8(out.stdout).expect("utf8");
+    assert_eq!(text, "status,exit_code,command\nsuccess,0,\"echo a,b\"\n");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["history", "--format", "csv", "--columns", "bogus"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown column \"bogus\""));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args([
+            "--no-color",
+            "history",
+            "--format",
+            "csv",
+            "--columns",
+            "status,exit",
+        ])
+        .output()
+        .expect("run history");
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "status,exit_code\nsuccess,0\n"
+    );
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args([
+            "--no-color",
+            "history",
+            "--columns",
+            "status,exit_code,user",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("status   exit  user"));
+}
+
+#[test]
+fn history_before_pages_from_a_cursor() {
+    let dir = tempdir().expect("tempdir");
+    for word in ["first", "second", "third"] {
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+        cmd.current_dir(dir.path())
+            .args(["run", "--name", word, "--", "true"])
+            .assert()
+            .success();
+    }
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["history", "--json", "--limit", "1"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let page: Value = serde_json::from_slice(&out).expect("history json");
+    let cursor = page[0]["id"].as_str().expect("id").to_string();
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["history", "--json", "--before", &cursor])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let page: Value = serde_json::from_slice(&out).expect("history json");
+    let names: Vec<&str> = page
+        .as_array()
+        .expect("rows")
+        .iter()
+        .filter_map(|row| row["name"].as_str())
+        .collect();
+    assert_eq!(names, ["second", "first"]);
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["history", "--before", "missing"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "no run with id \"missing\" in history",
+        ));
+}
+
+#[test]
+fn history_records_host_user_and_cwd() {
+    let dir = tempdir().expect("tempdir");
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .env("USER", "builder")
+        .args(["run", "--", "true"])
+        .assert()
+        .success();
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["history", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: Value = serde_json::from_slice(&out).expect("history json");
+    let run = &parsed[0];
+    assert_eq!(run["user"], "builder");
+    assert!(run["host"].as_str().is_some_and(|host| !host.is_empty()));
+    let cwd = run["cwd"].as_str().expect("cwd");
+    assert_eq!(
+        fs::canonicalize(cwd).expect("canonical cwd"),
+        fs::canonicalize(dir.path()).expect("canonical dir")
+    );
+}
+
+#[test]
+fn history_records_git_state_from_before_the_run() {
+    let dir = tempdir().expect("tempdir");
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .args(["-c", "user.name=otto", "-c", "user.email=otto@example.com"])
+            .args(args)
+            .current_dir(dir.path())
+            .status()
+            .expect("run git");
+        assert!(status.success());
+    };
+    fs::create_dir(dir.path().join("app")).expect("create app");
+    fs::write(dir.path().join(".gitignore"), ".otto/\n").expect("write gitignore");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+tasks:
+  build:
+    dir: app
+    run: touch generated.txt
+"#,
+    )
+    .expect("write config");
+    git(&["init", "-q", "-b", "trunk"]);
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "init"]);
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "build"])
+        .assert()
+        .success();
+    assert!(dir.path().join("app/generated.txt").exists());
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["history", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: Value = serde_json::from_slice(&out).expect("history json");
+    assert_eq!(parsed[0]["git"]["branch"], "trunk");
+    assert_eq!(parsed[0]["git"]["dirty"], false);
+}
+
+#[test]
+fn history_diff_compares_two_runs() {
+    let dir = tempdir().expect("tempdir");
+    for word in ["alpha", "beta"] {
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+        cmd.current_dir(dir.path())
+            .args(["run", "--", "echo", word])
+            .assert()
+            .success();
+    }
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["history", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: Value = serde_json::from_slice(&out).expect("history json");
+    let newer = parsed[0]["id"].as_str().expect("id").to_string();
+    let older = parsed[1]["id"].as_str().expect("id").to_string();
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["--no-color", "history", "diff", &older, &newer])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("-alpha"))
+        .stdout(predicate::str::contains("+beta"))
+        .stdout(predicate::str::contains("duration:"));
+}
+
+#[test]
+fn history_location_follows_otto_history_env() {
+    let dir = tempdir().expect("tempdir");
+    let data = tempdir().expect("data dir");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .env("OTTO_HISTORY", "custom/runs.jsonl")
+        .args(["run", "--", "true"])
+        .assert()
+        .success();
+    assert!(dir.path().join("custom/runs.jsonl").exists());
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .env("OTTO_HISTORY", "global")
+        .env("XDG_DATA_HOME", data.path())
+        .args(["run", "--", "true"])
+        .assert()
+        .success();
+    let global = data.path().join("otto/history.jsonl");
+    let text = fs::read_to_string(&global).expect("global history");
+    assert_eq!(text.lines().count(), 1);
+    assert!(!dir.path().join(".otto/history.jsonl").exists());
+
+    let record: Value = serde_json::from_str(text.trim()).expect("record");
+    let log_path = Path::new(record["log_path"].as_str().expect("log path"));
+    assert!(log_path.is_absolute());
+    assert!(log_path.starts_with(data.path().join("otto/logs")));
+    assert!(log_path.exists());
+    assert!(dir.path().join("custom/logs").is_dir());
+    assert!(!dir.path().join(".otto/logs").exists());
+}
+
+#[test]
+fn history_grep_shows_matching_lines() {
+    let dir = tempdir().expect("tempdir");
+    for word in ["linker error here", "all good"] {
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+        cmd.current_dir(dir.path())
+            .args(["run", "--", "echo", word])
+            .assert()
+            .success();
+    }
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["--no-color", "history", "--grep", "LINKER"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("match: linker error here"))
+        .stdout(predicate::str::contains("all good").not());
+}
+
+#[test]
+fn history_last_prints_record_and_passes_status_through() {
+    let dir = tempdir().expect("tempdir");
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "--name", "deploy", "--", "false"])
+        .assert()
+        .failure();
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "--name", "build", "--", "true"])
+        .assert()
+        .success();
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["history", "last"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: Value = serde_json::from_slice(&out).expect("last json");
+    assert_eq!(parsed["name"], "build");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["history", "last", "--name", "deploy", "--exit-code"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("\"status\": \"failed\""));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["history", "last", "--name", "missing"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn run_tags_are_recorded_and_filterable() {
+    let dir = tempdir().expect("tempdir");
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args([
+            "run", "--tag", "release", "--tag", "hotfix", "--name", "ship", "--", "true",
+        ])
+        .assert()
+        .success();
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "--tag", "release", "--name", "plain", "--", "true"])
+        .assert()
+        .success();
+
+    let names = |tags: &[&str]| -> Vec<String> {
+        let mut args = vec!["history", "--json"];
+        for tag in tags {
+            args.extend(["--tag", tag]);
+        }
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+        let out = cmd
+            .current_dir(dir.path())
+            .args(&args)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let parsed: Value = serde_json::from_slice(&out).expect("history json");
+        parsed
+            .as_array()
+            .expect("array")
+            .iter()
+            .map(|run| run["name"].as_str().expect("name").to_string())
+            .collect()
+    };
+    assert_eq!(names(&["release"]), ["plain", "ship"]);
+    assert_eq!(names(&["release", "hotfix"]), ["ship"]);
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "--tag", "two words", "--", "true"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn replay_reuses_env_file_and_vars() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(dir.path().join(".env"), "GREETING=default\n").expect("write .env");
+    fs::write(dir.path().join("custom.env"), "GREETING=first\n").expect("write env");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args([
+            "run",
+            "--env-file",
+            "custom.env",
+            "--var",
+            "MODE=fast",
+            "--name",
+            "show",
+            "--",
+            "printenv",
+            "GREETING",
+        ])
+        .assert()
+        .success();
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["history", "last"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let record: Value = serde_json::from_slice(&out).expect("last json");
+    assert_eq!(record["env"], serde_json::json!(["GREETING"]));
+    assert_eq!(record["replay"]["env_file"], "custom.env");
+    assert_eq!(record["replay"]["vars"]["MODE"], "fast");
+    let id = record["id"].as_str().expect("id").to_string();
+
+    fs::write(dir.path().join("custom.env"), "GREETING=second\n").expect("rewrite env");
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["replay", &id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("second"))
+        .stdout(predicate::str::contains("default").not());
+}
+
+#[test]
+fn replay_reports_environment_drift() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        "version: 1\n\nhistory:\n  hash_env: true\n\ntasks:\n  noop:\n    run: \"true\"\n",
+    )
+    .expect("write config");
+    fs::write(dir.path().join(".env"), "TOKEN=one\n").expect("write .env");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["run", "--name", "show", "--", "true"])
+        .assert()
+        .success();
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["history", "last"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let record: Value = serde_json::from_slice(&out).expect("last json");
+    let id = record["id"].as_str().expect("id").to_string();
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["replay", &id])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("environment differs").not());
+
+    fs::write(dir.path().join(".env"), "TOKEN=two\nEXTRA=1\n").expect("rewrite .env");
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["replay", &id])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(format!(
+            "environment differs from run {id}: TOKEN changed, EXTRA is new"
+        )));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    cmd.current_dir(dir.path())
+        .args(["replay", &id, "--strict"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("TOKEN changed"));
+}
+
+#[test]
+fn schema_covers_run_and_history_json() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("otto.yml"),
+        r#"version: 1
+
+tasks:
+  hello:
+    run: "echo hi"
+"#,
+    )
+    .expect("write config");
+
+    let schema_of = |kind: &str| -> Value {
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+        let out = cmd
+            .current_dir(dir.path())
+            .args(["schema", kind])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        serde_json::from_slice(&out).expect("schema json")
+    };
+    let check = |object: &Value, schema: &Value| {
+        let properties = schema["properties"].as_object().expect("properties");
+        for key in object.as_object().expect("object").keys() {
+            assert!(properties.contains_key(key), "{key} missing from schema");
+        }
+        for key in schema["required"].as_array().expect("required") {
+            let key = key.as_str().expect("key");
+            assert!(object.get(key).is_some(), "{key} missing from output");
+        }
+    };
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["run", "hello", "--json", "--tag", "ci"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let run: Value = serde_json::from_slice(&out).expect("run json");
+    check(&run, &schema_of("run"));
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("otto");
+    let out = cmd
+        .current_dir(dir.path())
+        .args(["history", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let history: Value = serde_json::from_slice(&out).expect("history json");
+    let schema = schema_of("history");
+    assert_eq!(schema["type"], "array");
+    for record in history.as_array().expect("records") {
+        check(record, &schema["items"]);
+    }
+}
@@ -1,6 +1,8 @@
 use otto_cli::config::{
-    self, Config, Defaults, Notifications, Task, load, resolve_inline, validate,
+    self, Config, Defaults, Email, History, Notifications, Ntfy, Outputs, Prompt, Pushover,
+    ShellSetting, Slack, Task, load, migrate, parse_size, resolve_inline, validate,
 };
+use otto_cli::runner::RetryStrategy;
 use std::collections::HashMap;
 use std::fs;
 use std::time::Duration;
@@ -45,6 +47,7 @@ fn resolve_task_applies_defaults() {
             retries: Some(2),
             retry_backoff: "2s".to_string(),
             notify_on: "always".to_string(),
+            ..Defaults::default()
         },
         tasks: Some(tasks),
         ..Config::default()
@@ -64,6 +67,7 @@ fn resolve_inline_uses_defaults_and_overrides() {
         retries: Some(3),
         retry_backoff: "2s".to_string(),
         notify_on: "always".to_string(),
+        ..Defaults::default()
     };
 
     let args = vec!["cargo".to_string(), "test".to_string()];
@@ -115,6 +119,7 @@ fn resolve_notification_settings_defaults_and_override() {
             desktop: Some(false),
             webhook_url: "https://example.com".to_string(),
             webhook_timeout: "2s".to_string(),
+            ..Notifications::default()
         },
         ..Config::default()
     };
@@ -140,11 +145,7 @@ fn resolve_task_supports_composed_tasks() {
     tasks.insert(
         "ci".to_string(),
         Task {
-            tasks: vec![
-                "lint".to_string(),
-                "build".to_string(),
-                "clippy".to_string(),
-            ],
+            tasks: vec!["lint".into(), "build".into(), "clippy".into()],
             parallel: true,
             ..Task::default()
         },
@@ -193,7 +194,7 @@ fn validate_rejects_unknown_composed_task_reference() {
     tasks.insert(
         "ci".to_string(),
         Task {
-            tasks: vec!["lint".to_string(), "missing".to_string()],
+            tasks: vec!["lint".into(), "missing".into()],
             ..Task::default()
         },
     );
@@ -225,7 +226,7 @@ fn validate_rejects_composed_task_with_exec_or_run() {
     tasks.insert(
         "ci".to_string(),
         Task {
-            tasks: vec!["lint".to_string()],
+            tasks: vec!["lint".into()],
             run: "cargo test".to_string(),
             ..Task::default()
         },
@@ -260,3 +261,832 @@ fn validate_rejects_reserved_task_name_validate() {
     let err = validate(&cfg).expect_err("expected reserved task-name error");
     assert!(err.to_string().contains("name is reserved"));
 }
+
+#[test]
+fn validate_rejects_interactive_composed_task() {
+    let mut tasks = HashMap::new();
+    tasks.insert(
+        "ci".to_string(),
+        Task {
+            tasks: vec!["login".into()],
+            interactive: true,
+            ..Task::default()
+        },
+    );
+    tasks.insert(
+        "login".to_string(),
+        Task {
+            exec: vec!["npm".to_string(), "login".to_string()],
+            interactive: true,
+            ..Task::default()
+        },
+    );
+
+    let cfg = Config {
+        version: config::CURRENT_VERSION,
+        tasks: Some(tasks),
+        ..Config::default()
+    };
+
+    let err = validate(&cfg).expect_err("expected interactive composition error");
+    assert_eq!(err.issues.len(), 1);
+    assert_eq!(err.issues[0].field, "tasks.ci.interactive");
+}
+
+#[test]
+fn validate_rejects_redirected_stdin_for_interactive_task() {
+    let mut tasks = HashMap::new();
+    tasks.insert(
+        "login".to_string(),
+        Task {
+            exec: vec!["npm".to_string(), "login".to_string()],
+            interactive: true,
+            stdin: "null".to_string(),
+            ..Task::default()
+        },
+    );
+
+    let cfg = Config {
+        version: config::CURRENT_VERSION,
+        tasks: Some(tasks),
+        ..Config::default()
+    };
+
+    let err = validate(&cfg).expect_err("expected stdin error");
+    assert_eq!(err.issues.len(), 1);
+    assert_eq!(err.issues[0].field, "tasks.login.stdin");
+}
+
+#[test]
+fn validate_rejects_stdin_with_stdin_file() {
+    let mut tasks = HashMap::new();
+    tasks.insert(
+        "load".to_string(),
+        Task {
+            exec: vec!["psql".to_string()],
+            stdin: "null".to_string(),
+            stdin_file: "dump.sql".to_string(),
+            ..Task::default()
+        },
+    );
+
+    let cfg = Config {
+        version: config::CURRENT_VERSION,
+        tasks: Some(tasks),
+        ..Config::default()
+    };
+
+    let err = validate(&cfg).expect_err("expected stdin_file error");
+    assert_eq!(err.issues.len(), 1);
+    assert_eq!(err.issues[0].field, "tasks.load.stdin_file");
+    assert_eq!(err.issues[0].message, "cannot be combined with stdin");
+}
+
+#[test]
+fn validate_rejects_stdin_values_other_than_inherit_or_null() {
+    let mut tasks = HashMap::new();
+    for (name, stdin) in [("typo", "nul"), ("file", "input.txt"), ("quiet", "null")] {
+        tasks.insert(
+            name.to_string(),
+            Task {
+                exec: vec!["cat".to_string()],
+                stdin: stdin.to_string(),
+                ..Task::default()
+            },
+        );
+    }
+
+    let cfg = Config {
+        version: config::CURRENT_VERSION,
+        tasks: Some(tasks),
+        ..Config::default()
+    };
+
+    let err = validate(&cfg).expect_err("expected stdin errors");
+    let mut fields: Vec<&str> = err
+        .issues
+        .iter()
+        .map(|issue| issue.field.as_str())
+        .collect();
+    fields.sort();
+    assert_eq!(fields, vec!["tasks.file.stdin", "tasks.typo.stdin"]);
+    assert!(err.issues[0].message.contains("use stdin_file"));
+}
+
+#[test]
+fn validate_checks_shell_setting() {
+    let mut tasks = HashMap::new();
+    tasks.insert(
+        "build".to_string(),
+        Task {
+            run: "make".to_string(),
+            shell: ShellSetting::Named("fish".to_string()),
+            ..Task::default()
+        },
+    );
+    tasks.insert(
+        "lint".to_string(),
+        Task {
+            exec: vec!["cargo".to_string(), "clippy".to_string()],
+            shell: ShellSetting::Named("pwsh".to_string()),
+            ..Task::default()
+        },
+    );
+    tasks.insert(
+        "test".to_string(),
+        Task {
+            run: "Invoke-Pester".to_string(),
+            shell: ShellSetting::Named("pwsh".to_string()),
+            ..Task::default()
+        },
+    );
+
+    let cfg = Config {
+        version: config::CURRENT_VERSION,
+        tasks: Some(tasks),
+        ..Config::default()
+    };
+
+    let err = validate(&cfg).expect_err("expected shell errors");
+    let mut fields: Vec<&str> = err
+        .issues
+        .iter()
+        .map(|issue| issue.field.as_str())
+        .collect();
+    fields.sort();
+    assert_eq!(fields, vec!["tasks.build.shell", "tasks.lint.shell"]);
+}
+
+#[test]
+fn migrate_upgrades_v1_and_preserves_comments() {
+    let text = r#"version: 1 # schema version
+
+# shared defaults
+defaults:
+  timeout: "1m"
+
+tasks:
+  test:
+    exec: ["cargo", "test"] # direct argv
+"#;
+
+    let migrated = migrate(text).expect("migrate").expect("migrated text");
+    assert!(migrated.starts_with("version: 2 # schema version\n"));
+    assert!(migrated.contains("dir_relative_to: cwd"));
+    assert!(migrated.contains("# shared defaults"));
+    assert!(migrated.contains("# direct argv"));
+
+    assert!(migrate(&migrated).expect("migrate current").is_none());
+}
+
+#[test]
+fn lint_reports_unused_internal_and_shadowed_env() {
+    let mut tasks = HashMap::new();
+    tasks.insert(
+        "helper".to_string(),
+        Task {
+            internal: true,
+            run: "true".to_string(),
+            env: HashMap::from([("PATH".to_string(), "/opt/bin".to_string())]),
+            ..Task::default()
+        },
+    );
+
+    let cfg = Config {
+        version: config::CURRENT_VERSION,
+        tasks: Some(tasks),
+        ..Config::default()
+    };
+
+    let fields: Vec<String> = config::lint(&cfg)
+        .into_iter()
+        .map(|warning| warning.field)
+        .collect();
+    assert_eq!(fields, vec!["tasks.helper", "tasks.helper.env.PATH"]);
+}
+
+#[test]
+fn validate_accepts_namespaced_task_names() {
+    let mut tasks = HashMap::new();
+    for name in ["build:frontend", "build:backend:release", ":bad", "bad:"] {
+        tasks.insert(
+            name.to_string(),
+            Task {
+                exec: vec!["echo".to_string(), "ok".to_string()],
+                ..Task::default()
+            },
+        );
+    }
+
+    let cfg = Config {
+        version: config::CURRENT_VERSION,
+        tasks: Some(tasks),
+        ..Config::default()
+    };
+
+    let err = validate(&cfg).expect_err("expected invalid names");
+    let mut fields: Vec<&str> = err.issues.iter().map(|i| i.field.as_str()).collect();
+    fields.sort();
+    assert_eq!(fields, vec!["tasks.:bad", "tasks.bad:"]);
+}
+
+#[test]
+fn validate_rejects_max_parallel_without_parallel_group() {
+    let mut tasks = HashMap::new();
+    tasks.insert(
+        "build".to_string(),
+        Task {
+            exec: vec!["cargo".to_string(), "build".to_string()],
+            max_parallel: Some(2),
+            ..Task::default()
+        },
+    );
+
+    let cfg = Config {
+        version: config::CURRENT_VERSION,
+        tasks: Some(tasks),
+        ..Config::default()
+    };
+
+    let err = validate(&cfg).expect_err("expected max_parallel error");
+    assert_eq!(err.issues[0].field, "tasks.build.max_parallel");
+}
+
+#[test]
+fn retry_policy_resolves_and_validates() {
+    let mut tasks = HashMap::new();
+    tasks.insert(
+        "flaky".to_string(),
+        Task {
+            exec: vec!["true".to_string()],
+            retry_strategy: "linear".to_string(),
+            retry_jitter: "250ms".to_string(),
+            ..Task::default()
+        },
+    );
+
+    let cfg = Config {
+        version: 1,
+        defaults: Defaults {
+            retry_max_backoff: "10s".to_string(),
+            ..Defaults::default()
+        },
+        tasks: Some(tasks),
+        ..Config::default()
+    };
+
+    let resolved = cfg.resolve_task("flaky").expect("resolve task");
+    assert_eq!(resolved.retry_strategy, RetryStrategy::Linear);
+    assert_eq!(resolved.retry_jitter, Duration::from_millis(250));
+    assert_eq!(resolved.retry_max_backoff, Duration::from_secs(10));
+
+    let bad = Config {
+        defaults: Defaults {
+            retry_strategy: "random".to_string(),
+            ..Defaults::default()
+        },
+        ..cfg
+    };
+    let err = validate(&bad).expect_err("invalid strategy");
+    assert!(err.to_string().contains("defaults.retry_strategy"));
+}
+
+#[test]
+fn notify_min_duration_prefers_task_over_defaults() {
+    let mut tasks = HashMap::new();
+    tasks.insert(
+        "build".to_string(),
+        Task {
+            exec: vec!["true".to_string()],
+            notify_min_duration: "2m".to_string(),
+            ..Task::default()
+        },
+    );
+    tasks.insert(
+        "lint".to_string(),
+        Task {
+            exec: vec!["true".to_string()],
+            ..Task::default()
+        },
+    );
+
+    let cfg = Config {
+        version: 1,
+        defaults: Defaults {
+            notify_min_duration: "30s".to_string(),
+            ..Defaults::default()
+        },
+        tasks: Some(tasks),
+        ..Config::default()
+    };
+
+    assert!(validate(&cfg).is_ok());
+    let build = cfg.resolve_task("build").expect("resolve build");
+    assert_eq!(build.notify_min_duration, Duration::from_secs(120));
+    let lint = cfg.resolve_task("lint").expect("resolve lint");
+    assert_eq!(lint.notify_min_duration, Duration::from_secs(30));
+}
+
+#[test]
+fn load_merges_local_override_file() {
+    let dir = tempdir().expect("tempdir");
+    let path = dir.path().join("otto.yml");
+    fs::write(
+        &path,
+        r#"version: 2
+defaults:
+  timeout: "1m"
+  retries: 1
+tasks:
+  test:
+    run: "cargo test"
+    env:
+      A: "1"
+"#,
+    )
+    .expect("write config");
+    fs::write(
+        dir.path().join("otto.local.yml"),
+        r#"defaults:
+  timeout: "5m"
+tasks:
+  test:
+    env:
+      B: "2"
+"#,
+    )
+    .expect("write local override");
+
+    let cfg = load(&path).expect("load config");
+    assert_eq!(cfg.defaults.timeout, "5m");
+    assert_eq!(cfg.defaults.retries, Some(1));
+    let test = &cfg.tasks.as_ref().expect("tasks")["test"];
+    assert_eq!(test.run, "cargo test");
+    assert_eq!(test.env.get("A").map(String::as_str), Some("1"));
+    assert_eq!(test.env.get("B").map(String::as_str), Some("2"));
+}
+
+#[test]
+fn validate_checks_output_setting() {
+    let mut tasks = HashMap::new();
+    tasks.insert(
+        "build".to_string(),
+        Task {
+            run: "make".to_string(),
+            output: "silent".to_string(),
+            ..Task::default()
+        },
+    );
+    tasks.insert(
+        "shell".to_string(),
+        Task {
+            run: "bash".to_string(),
+            interactive: true,
+            output: "quiet".to_string(),
+            ..Task::default()
+        },
+    );
+    tasks.insert(
+        "test".to_string(),
+        Task {
+            run: "make test".to_string(),
+            output: "quiet".to_string(),
+            ..Task::default()
+        },
+    );
+    tasks.insert(
+        "ci".to_string(),
+        Task {
+            tasks: vec!["test".into()],
+            output: "grouped".to_string(),
+            ..Task::default()
+        },
+    );
+    tasks.insert(
+        "dev".to_string(),
+        Task {
+            tasks: vec!["test".into()],
+            parallel: true,
+            output: "grouped".to_string(),
+            ..Task::default()
+        },
+    );
+
+    let cfg = Config {
+        version: config::CURRENT_VERSION,
+        tasks: Some(tasks),
+        ..Config::default()
+    };
+
+    let err = validate(&cfg).expect_err("expected output errors");
+    let mut fields: Vec<&str> = err
+        .issues
+        .iter()
+        .map(|issue| issue.field.as_str())
+        .collect();
+    fields.sort();
+    assert_eq!(
+        fields,
+        vec![
+            "tasks.build.output",
+            "tasks.ci.output",
+            "tasks.shell.output"
+        ]
+    );
+}
+
+#[test]
+fn validate_checks_prompts() {
+    let mut tasks = HashMap::new();
+    tasks.insert(
+        "deploy".to_string(),
+        Task {
+            run: "./deploy.sh".to_string(),
+            prompts: vec![
+                Prompt {
+                    name: "DEPLOY_ENV".to_string(),
+                    default: "qa".to_string(),
+                    choices: vec!["staging".to_string(), "production".to_string()],
+                    ..Prompt::default()
+                },
+                Prompt {
+                    name: "DEPLOY_ENV".to_string(),
+                    ..Prompt::default()
+                },
+                Prompt {
+                    name: "1BAD".to_string(),
+                    ..Prompt::default()
+                },
+            ],
+            ..Task::default()
+        },
+    );
+
+    let cfg = Config {
+        version: config::CURRENT_VERSION,
+        tasks: Some(tasks),
+        ..Config::default()
+    };
+
+    let err = validate(&cfg).expect_err("expected prompt errors");
+    let mut fields: Vec<&str> = err
+        .issues
+        .iter()
+        .map(|issue| issue.field.as_str())
+        .collect();
+    fields.sort();
+    assert_eq!(
+        fields,
+        vec![
+            "tasks.deploy.prompts[0].default",
+            "tasks.deploy.prompts[1].name",
+            "tasks.deploy.prompts[2].name"
+        ]
+    );
+}
+
+#[test]
+fn validate_checks_named_outputs() {
+    let mut tasks = HashMap::new();
+    tasks.insert(
+        "build".to_string(),
+        Task {
+            run: "make".to_string(),
+            outputs: Outputs::Named(HashMap::from([
+                ("artifact".to_string(), "dist/app".to_string()),
+                ("bad-name".to_string(), "dist/other".to_string()),
+            ])),
+            ..Task::default()
+        },
+    );
+    tasks.insert(
+        "cached".to_string(),
+        Task {
+            run: "make".to_string(),
+            outputs: Outputs::Paths(vec!["dist/*".to_string()]),
+            ..Task::default()
+        },
+    );
+
+    let cfg = Config {
+        version: config::CURRENT_VERSION,
+        tasks: Some(tasks),
+        ..Config::default()
+    };
+
+    let err = validate(&cfg).expect_err("expected output errors");
+    let mut fields: Vec<&str> = err
+        .issues
+        .iter()
+        .map(|issue| issue.field.as_str())
+        .collect();
+    fields.sort();
+    assert_eq!(
+        fields,
+        vec!["tasks.build.outputs.bad-name", "tasks.cached.outputs"]
+    );
+}
+
+#[test]
+fn validate_requires_container_image() {
+    let mut tasks = HashMap::new();
+    tasks.insert(
+        "build".to_string(),
+        Task {
+            run: "cargo build".to_string(),
+            container: Some(config::Container {
+                image: " ".to_string(),
+                volumes: vec![String::new()],
+            }),
+            ..Task::default()
+        },
+    );
+
+    let cfg = Config {
+        version: config::CURRENT_VERSION,
+        tasks: Some(tasks),
+        ..Config::default()
+    };
+
+    let err = validate(&cfg).expect_err("expected container errors");
+    let mut fields: Vec<&str> = err
+        .issues
+        .iter()
+        .map(|issue| issue.field.as_str())
+        .collect();
+    fields.sort();
+    assert_eq!(
+        fields,
+        vec![
+            "tasks.build.container.image",
+            "tasks.build.container.volumes[0]"
+        ]
+    );
+}
+
+#[test]
+fn shell_false_splits_run_into_exec() {
+    let dir = tempdir().expect("tempdir");
+    let path = dir.path().join("otto.yml");
+
+    fs::write(
+        &path,
+        r#"version: 2
+tasks:
+  greet:
+    run: echo "hello  world" $HOME; rm -rf /
+    shell: false
+  broken:
+    run: echo "unterminated
+    shell: false
+  literal:
+    run: echo hi
+    shell: none
+"#,
+    )
+    .expect("write config");
+
+    let cfg = config::parse(&path).expect("parse config");
+    let tasks = cfg.tasks.as_ref().expect("tasks");
+    assert_eq!(tasks["greet"].shell, ShellSetting::Disabled);
+    assert_eq!(
+        tasks["literal"].shell,
+        ShellSetting::Named("none".to_string())
+    );
+
+    let err = validate(&cfg).expect_err("expected quoting error");
+    let mut fields: Vec<&str> = err
+        .issues
+        .iter()
+        .map(|issue| issue.field.as_str())
+        .collect();
+    fields.sort();
+    assert_eq!(fields, vec!["tasks.broken.run", "tasks.literal.shell"]);
+
+    let resolved = cfg.resolve_task("greet").expect("resolve task");
+    assert!(!resolved.use_shell);
+    assert_eq!(
+        resolved.exec,
+        vec!["echo", "hello  world", "$HOME;", "rm", "-rf", "/"]
+    );
+    assert_eq!(
+        resolved.command_preview,
+        r#"echo "hello  world" $HOME; rm -rf /"#
+    );
+}
+
+#[test]
+fn max_captured_output_parses_sizes() {
+    assert_eq!(parse_size("512"), Ok(512));
+    assert_eq!(parse_size("64KB"), Ok(64 * 1024));
+    assert_eq!(parse_size("2MB"), Ok(2 * 1024 * 1024));
+    assert_eq!(parse_size("1 gb"), Ok(1024 * 1024 * 1024));
+    assert!(parse_size("0").is_err());
+    assert!(parse_size("2TB").is_err());
+    assert!(parse_size("MB").is_err());
+
+    let mut tasks = HashMap::new();
+    tasks.insert(
+        "build".to_string(),
+        Task {
+            exec: vec!["true".to_string()],
+            max_captured_output: "lots".to_string(),
+            ..Task::default()
+        },
+    );
+    let mut cfg = Config {
+        version: 1,
+        defaults: Defaults {
+            max_captured_output: "1MB".to_string(),
+            ..Defaults::default()
+        },
+        tasks: Some(tasks),
+        ..Config::default()
+    };
+
+    let err = validate(&cfg).expect_err("invalid size");
+    assert_eq!(err.issues.len(), 1);
+    assert_eq!(err.issues[0].field, "tasks.build.max_captured_output");
+
+    if let Some(tasks) = cfg.tasks.as_mut() {
+        tasks.get_mut("build").expect("build").max_captured_output = String::new();
+    }
+    let build = cfg.resolve_task("build").expect("resolve build");
+    assert_eq!(build.max_captured_output, Some(1024 * 1024));
+}
+
+#[test]
+fn validate_rejects_bad_metrics_targets() {
+    let mut tasks = HashMap::new();
+    tasks.insert(
+        "build".to_string(),
+        Task {
+            run: "echo ok".to_string(),
+            ..Task::default()
+        },
+    );
+
+    let cfg = Config {
+        version: config::CURRENT_VERSION,
+        notifications: Notifications {
+            statsd: "localhost".to_string(),
+            pushgateway_url: "not a url".to_string(),
+            ..Notifications::default()
+        },
+        tasks: Some(tasks),
+        ..Config::default()
+    };
+
+    let err = validate(&cfg).expect_err("expected metrics errors");
+    let fields: Vec<&str> = err.issues.iter().map(|i| i.field.as_str()).collect();
+    assert_eq!(
+        fields,
+        ["notifications.statsd", "notifications.pushgateway_url"]
+    );
+}
+
+#[test]
+fn validate_rejects_global_history_scope_with_path() {
+    let mut tasks = HashMap::new();
+    tasks.insert(
+        "build".to_string(),
+        Task {
+            run: "echo ok".to_string(),
+            ..Task::default()
+        },
+    );
+
+    let cfg = Config {
+        version: config::CURRENT_VERSION,
+        history: History {
+            scope: "global".to_string(),
+            path: "runs.jsonl".to_string(),
+            ..History::default()
+        },
+        tasks: Some(tasks),
+        ..Config::default()
+    };
+
+    let err = validate(&cfg).expect_err("expected scope error");
+    assert_eq!(err.issues[0].field, "history.scope");
+}
+
+#[test]
+fn validate_requires_slack_webhook_url() {
+    let mut tasks = HashMap::new();
+    tasks.insert(
+        "build".to_string(),
+        Task {
+            run: "echo ok".to_string(),
+            ..Task::default()
+        },
+    );
+
+    let cfg = Config {
+        version: config::CURRENT_VERSION,
+        notifications: Notifications {
+            slack: Some(Slack::default()),
+            ..Notifications::default()
+        },
+        tasks: Some(tasks),
+        ..Config::default()
+    };
+
+    let err = validate(&cfg).expect_err("expected slack error");
+    assert_eq!(err.issues[0].field, "notifications.slack.webhook_url");
+}
+
+#[test]
+fn resolve_email_defaults_security_from_port() {
+    let email = |server: &str| Config {
+        notifications: Notifications {
+            email: Some(Email {
+                server: server.to_string(),
+                from: "otto@example.com".to_string(),
+                to: vec!["dev@example.com".to_string()],
+                ..Email::default()
+            }),
+            ..Notifications::default()
+        },
+        ..Config::default()
+    };
+
+    let settings = email("smtp.example.com:465")
+        .resolve_notification_settings()
+        .expect("settings");
+    let resolved = settings.email.expect("email");
+    assert_eq!(resolved.security, "tls");
+    assert_eq!(resolved.username_env, "OTTO_SMTP_USERNAME");
+    assert_eq!(resolved.password_env, "OTTO_SMTP_PASSWORD");
+
+    let settings = email("smtp.example.com:587")
+        .resolve_notification_settings()
+        .expect("settings");
+    assert_eq!(settings.email.expect("email").security, "starttls");
+}
+
+#[test]
+fn resolve_ntfy_priorities_over_defaults() {
+    let cfg = Config {
+        notifications: Notifications {
+            ntfy: Some(Ntfy {
+                url: "https://ntfy.sh/builds".to_string(),
+                priorities: [("failed".to_string(), "urgent".to_string())]
+                    .into_iter()
+                    .collect(),
+                ..Ntfy::default()
+            }),
+            ..Notifications::default()
+        },
+        ..Config::default()
+    };
+
+    let ntfy = cfg
+        .resolve_notification_settings()
+        .expect("settings")
+        .ntfy
+        .expect("ntfy");
+    assert_eq!(ntfy.token_env, "OTTO_NTFY_TOKEN");
+    assert_eq!(ntfy.priorities["failed"], 5);
+    assert_eq!(ntfy.priorities["timedout"], 4);
+    assert_eq!(ntfy.priorities["skipped"], 2);
+}
+
+#[test]
+fn validate_pushover_limits() {
+    let mut tasks = HashMap::new();
+    tasks.insert(
+        "build".to_string(),
+        Task {
+            run: "echo ok".to_string(),
+            ..Task::default()
+        },
+    );
+
+    let cfg = Config {
+        version: config::CURRENT_VERSION,
+        notifications: Notifications {
+            pushover: Some(Pushover {
+                user_key: "user-key".to_string(),
+                failure_priority: Some(3),
+                retry: "10s".to_string(),
+                expire: "1d".to_string(),
+                ..Pushover::default()
+            }),
+            ..Notifications::default()
+        },
+        tasks: Some(tasks),
+        ..Config::default()
+    };
+
+    let err = validate(&cfg).expect_err("expected pushover errors");
+    let fields: Vec<&str> = err.issues.iter().map(|i| i.field.as_str()).collect();
+    assert_eq!(
+        fields,
+        [
+            "notifications.pushover.failure_priority",
+            "notifications.pushover.retry",
+            "notifications.pushover.expire",
+        ]
+    );
+}
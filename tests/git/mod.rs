@@ -0,0 +1,56 @@
+use otto_cli::git::{context, parse_status};
+use otto_cli::model::GitContext;
+use std::fs;
+use std::process::Command;
+use tempfile::tempdir;
+
+#[test]
+fn parse_status_reads_sha_branch_and_dirty_flag() {
+    let text = "# branch.oid 0123456789abcdef\n# branch.head main\n1 .M N... 100644 100644 100644 aa bb src/lib.rs\n";
+    assert_eq!(
+        parse_status(text),
+        Some(GitContext {
+            sha: "0123456789abcdef".to_string(),
+            branch: Some("main".to_string()),
+            dirty: true,
+        })
+    );
+}
+
+#[test]
+fn parse_status_handles_detached_and_unborn_heads() {
+    let detached = parse_status("# branch.oid abc\n# branch.head (detached)\n").expect("context");
+    assert_eq!(detached.branch, None);
+    assert!(!detached.dirty);
+
+    assert_eq!(
+        parse_status("# branch.oid (initial)\n# branch.head main\n"),
+        None
+    );
+}
+
+#[test]
+fn context_reports_repository_state() {
+    let dir = tempdir().expect("tempdir");
+    assert_eq!(context(dir.path()), None);
+
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .args(["-c", "user.name=otto", "-c", "user.email=otto@example.com"])
+            .args(args)
+            .current_dir(dir.path())
+            .status()
+            .expect("run git");
+        assert!(status.success());
+    };
+    git(&["init", "-q", "-b", "trunk"]);
+    git(&["commit", "-q", "--allow-empty", "-m", "init"]);
+
+    let clean = context(dir.path()).expect("git context");
+    assert_eq!(clean.sha.len(), 40);
+    assert_eq!(clean.branch.as_deref(), Some("trunk"));
+    assert!(!clean.dirty);
+
+    fs::write(dir.path().join("new.txt"), "x").expect("write");
+    assert!(context(dir.path()).expect("git context").dirty);
+}
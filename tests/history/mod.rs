@@ -1,9 +1,9 @@
-use otto_cli::history::{Filter, Store};
+use otto_cli::history::{Filter, Retention, Store, matching_lines, stats};
 use otto_cli::model::{RunRecord, RunSource, RunStatus};
-use std::fs::OpenOptions;
+use std::fs::{self, OpenOptions};
 use std::io::Write;
 use tempfile::tempdir;
-use time::OffsetDateTime;
+use time::{Duration, OffsetDateTime};
 
 fn record(id: &str, source: RunSource, status: RunStatus) -> RunRecord {
     RunRecord {
@@ -15,7 +15,23 @@ fn record(id: &str, source: RunSource, status: RunStatus) -> RunRecord {
         duration_ms: 10,
         exit_code: if status == RunStatus::Success { 0 } else { 1 },
         status,
+        stdout_tail: None,
         stderr_tail: None,
+        signal: None,
+        skip_reason: None,
+        forced: false,
+        rerun_of: None,
+        args: Vec::new(),
+        tags: Vec::new(),
+        env: Vec::new(),
+        env_hashes: Default::default(),
+        replay: None,
+        git: None,
+        host: None,
+        user: None,
+        cwd: None,
+        log_path: None,
+        attempts: Vec::new(),
     }
 }
 
@@ -51,6 +67,210 @@ fn append_and_list() {
     assert_eq!(filtered[0].id, "2");
 }
 
+#[test]
+fn timed_out_runs_have_their_own_status() {
+    let dir = tempdir().expect("tempdir");
+    let path = dir.path().join("history.jsonl");
+    let store = Store::new(&path);
+
+    store
+        .append(&record("slow", RunSource::Task, RunStatus::TimedOut))
+        .expect("append timed out");
+    store
+        .append(&record("broken", RunSource::Task, RunStatus::Failed))
+        .expect("append failed");
+
+    let text = fs::read_to_string(&path).expect("read history");
+    assert!(text.contains("\"status\":\"timedout\""));
+
+    let filtered = store
+        .list(&Filter {
+            status: Some("timedout".to_string()),
+            ..Filter::default()
+        })
+        .expect("filter");
+    assert_eq!(filtered.len(), 1);
+    assert_eq!(filtered[0].id, "slow");
+}
+
+#[test]
+fn list_filters_by_name_pattern() {
+    let dir = tempdir().expect("tempdir");
+    let store = Store::new(dir.path().join("history.jsonl"));
+
+    for id in ["deploy", "deploy:staging", "build"] {
+        store
+            .append(&record(id, RunSource::Task, RunStatus::Success))
+            .expect("append");
+    }
+
+    let names = |pattern: &str| -> Vec<String> {
+        store
+            .list(&Filter {
+                name: Some(pattern.to_string()),
+                ..Filter::default()
+            })
+            .expect("list")
+            .into_iter()
+            .map(|row| row.name)
+            .collect()
+    };
+    assert_eq!(names("deploy"), ["deploy"]);
+    assert_eq!(names("deploy*"), ["deploy:staging", "deploy"]);
+    assert!(names("test").is_empty());
+}
+
+#[test]
+fn list_greps_command_stderr_and_logs() {
+    let dir = tempdir().expect("tempdir");
+    let store = Store::new(dir.path().join("history.jsonl"));
+    let log = dir.path().join("log.txt");
+    fs::write(&log, "compiling\nLinker Error: undefined symbol\n").expect("write log");
+
+    let mut from_log = record("from-log", RunSource::Task, RunStatus::Failed);
+    from_log.log_path = Some(log.display().to_string());
+    let mut from_stderr = record("from-stderr", RunSource::Task, RunStatus::Failed);
+    from_stderr.stderr_tail = Some("fatal: linker error".to_string());
+    for rec in [
+        from_log,
+        from_stderr,
+        record("clean", RunSource::Task, RunStatus::Success),
+    ] {
+        store.append(&rec).expect("append");
+    }
+
+    let rows = store
+        .list(&Filter {
+            grep: Some("linker error".to_string()),
+            ..Filter::default()
+        })
+        .expect("list");
+    let ids: Vec<&str> = rows.iter().map(|row| row.id.as_str()).collect();
+    assert_eq!(ids, ["from-stderr", "from-log"]);
+    assert_eq!(
+        matching_lines(&rows[1], "linker error"),
+        ["Linker Error: undefined symbol"]
+    );
+}
+
+#[test]
+fn import_merges_by_time_and_skips_duplicate_ids() {
+    let dir = tempdir().expect("tempdir");
+    let store = Store::new(dir.path().join("history.jsonl"));
+    let now = OffsetDateTime::now_utc();
+
+    let mut local = record("local", RunSource::Task, RunStatus::Success);
+    local.started_at = now - Duration::minutes(5);
+    store.append(&local).expect("append");
+
+    let other = Store::new(dir.path().join("agent.jsonl"));
+    let mut older = record("older", RunSource::Task, RunStatus::Failed);
+    older.started_at = now - Duration::minutes(10);
+    let mut newer = record("newer", RunSource::Task, RunStatus::Success);
+    newer.started_at = now;
+    for rec in [&newer, &local, &older] {
+        other.append(rec).expect("append");
+    }
+
+    let summary = store.import(other.path()).expect("import");
+    assert_eq!(summary.imported, 2);
+    assert_eq!(summary.duplicates, 1);
+
+    let rows = store.list(&Filter::default()).expect("list");
+    let ids: Vec<&str> = rows.iter().map(|row| row.id.as_str()).collect();
+    assert_eq!(ids, ["newer", "local", "older"]);
+
+    let again = store.import(other.path()).expect("import again");
+    assert_eq!(again.imported, 0);
+    assert_eq!(again.duplicates, 3);
+}
+
+#[test]
+fn import_merges_into_archives_and_applies_limits() {
+    let dir = tempdir().expect("tempdir");
+    let store = Store::new(dir.path().join("history.jsonl"))
+        .with_rotation(Some(1), 10)
+        .with_max_entries(3);
+    let now = OffsetDateTime::now_utc();
+
+    for (id, age) in [("a", 40), ("b", 20)] {
+        let mut rec = record(id, RunSource::Task, RunStatus::Success);
+        rec.started_at = now - Duration::minutes(age);
+        store.append(&rec).expect("append");
+    }
+    assert_eq!(store.archives().expect("archives").len(), 2);
+
+    let other = Store::new(dir.path().join("agent.jsonl"));
+    for (id, age) in [("old", 50), ("mid", 30), ("new", 10)] {
+        let mut rec = record(id, RunSource::Task, RunStatus::Success);
+        rec.started_at = now - Duration::minutes(age);
+        other.append(&rec).expect("append");
+    }
+
+    let summary = store.import(other.path()).expect("import");
+    assert_eq!(summary.imported, 3);
+
+    let rows = store.list(&Filter::default()).expect("list");
+    let ids: Vec<&str> = rows.iter().map(|row| row.id.as_str()).collect();
+    assert_eq!(ids, ["new", "b", "mid"]);
+    assert_eq!(
+        fs::metadata(store.path())
+            .map(|meta| meta.len())
+            .unwrap_or(0),
+        0
+    );
+}
+
+#[test]
+fn list_filters_by_time_range() {
+    let dir = tempdir().expect("tempdir");
+    let store = Store::new(dir.path().join("history.jsonl"));
+
+    let now = OffsetDateTime::now_utc();
+    for (id, age_hours) in [("old", 48), ("incident", 5), ("recent", 1)] {
+        let mut rec = record(id, RunSource::Task, RunStatus::Success);
+        rec.started_at = now - Duration::hours(age_hours);
+        store.append(&rec).expect("append");
+    }
+
+    let rows = store
+        .list(&Filter {
+            since: Some(now - Duration::hours(6)),
+            until: Some(now - Duration::hours(2)),
+            ..Filter::default()
+        })
+        .expect("list");
+    let ids: Vec<&str> = rows.iter().map(|row| row.id.as_str()).collect();
+    assert_eq!(ids, ["incident"]);
+}
+
+#[test]
+fn list_reads_large_files_from_the_end() {
+    let dir = tempdir().expect("tempdir");
+    let path = dir.path().join("history.jsonl");
+    let mut contents = String::new();
+    for idx in 0..3000 {
+        let rec = record(&idx.to_string(), RunSource::Task, RunStatus::Success);
+        contents.push_str(&serde_json::to_string(&rec).expect("encode"));
+        contents.push('\n');
+    }
+    fs::write(&path, contents).expect("write history");
+
+    let store = Store::new(&path);
+    let rows = store
+        .list(&Filter {
+            limit: Some(3),
+            ..Filter::default()
+        })
+        .expect("list");
+    let ids: Vec<&str> = rows.iter().map(|row| row.id.as_str()).collect();
+    assert_eq!(ids, ["2999", "2998", "2997"]);
+
+    let all = store.list(&Filter::default()).expect("list all");
+    assert_eq!(all.len(), 3000);
+    assert_eq!(all.last().map(|row| row.id.as_str()), Some("0"));
+}
+
 #[test]
 fn list_ignores_malformed_lines() {
     let dir = tempdir().expect("tempdir");
@@ -80,3 +300,415 @@ fn list_missing_file() {
     let rows = store.list(&Filter::default()).expect("list");
     assert!(rows.is_empty());
 }
+
+#[test]
+fn append_prunes_to_max_entries() {
+    let dir = tempdir().expect("tempdir");
+    let path = dir.path().join("history.jsonl");
+    let store = Store::new(&path).with_max_entries(2);
+
+    for id in ["1", "2", "3"] {
+        store
+            .append(&record(id, RunSource::Task, RunStatus::Success))
+            .expect("append");
+    }
+
+    let rows = store.list(&Filter::default()).expect("list");
+    let ids: Vec<&str> = rows.iter().map(|row| row.id.as_str()).collect();
+    assert_eq!(ids, ["3", "2"]);
+}
+
+#[test]
+fn concurrent_appends_and_prunes_keep_every_new_record() {
+    let dir = tempdir().expect("tempdir");
+    let path = dir.path().join("history.jsonl");
+    let cutoff = OffsetDateTime::now_utc() - Duration::hours(1);
+
+    std::thread::scope(|scope| {
+        for worker in 0..4 {
+            let path = &path;
+            scope.spawn(move || {
+                let store = Store::new(path);
+                for n in 0..25 {
+                    store
+                        .append(&record(
+                            &format!("{worker}-{n}"),
+                            RunSource::Task,
+                            RunStatus::Success,
+                        ))
+                        .expect("append");
+                }
+            });
+        }
+        let path = &path;
+        scope.spawn(move || {
+            let store = Store::new(path);
+            for n in 0..25 {
+                let mut old = record(&format!("old-{n}"), RunSource::Task, RunStatus::Success);
+                old.started_at = cutoff - Duration::days(1);
+                store.append(&old).expect("append old");
+                store
+                    .prune(&Retention {
+                        keep: None,
+                        newer_than: Some(cutoff),
+                    })
+                    .expect("prune");
+            }
+        });
+    });
+
+    let rows = Store::new(&path).list(&Filter::default()).expect("list");
+    assert_eq!(rows.len(), 100);
+    assert!(rows.iter().all(|row| !row.id.starts_with("old-")));
+    let leftovers: Vec<_> = fs::read_dir(dir.path())
+        .expect("read dir")
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+        .collect();
+    assert!(leftovers.is_empty());
+}
+
+#[test]
+fn append_rotates_by_size_and_lists_across_archives() {
+    let dir = tempdir().expect("tempdir");
+    let path = dir.path().join("history.jsonl");
+    let store = Store::new(&path).with_rotation(Some(1), 2);
+
+    for id in ["1", "2", "3", "4"] {
+        store
+            .append(&record(id, RunSource::Task, RunStatus::Success))
+            .expect("append");
+    }
+
+    assert!(!path.exists());
+    assert_eq!(store.archives().expect("archives").len(), 2);
+
+    let rows = Store::new(&path).list(&Filter::default()).expect("list");
+    let ids: Vec<&str> = rows.iter().map(|row| row.id.as_str()).collect();
+    assert_eq!(ids, ["4", "3"]);
+}
+
+#[test]
+fn compressed_archives_are_listed_transparently() {
+    let dir = tempdir().expect("tempdir");
+    let path = dir.path().join("history.jsonl");
+    let store = Store::new(&path)
+        .with_rotation(Some(1), 5)
+        .with_compression(true);
+
+    for id in ["1", "2", "3"] {
+        store
+            .append(&record(id, RunSource::Task, RunStatus::Success))
+            .expect("append");
+    }
+
+    let archives = store.archives().expect("archives");
+    assert_eq!(archives.len(), 3);
+    assert!(
+        archives
+            .iter()
+            .all(|archive| archive.to_string_lossy().ends_with(".jsonl.gz"))
+    );
+    let raw = fs::read(&archives[0]).expect("read archive");
+    assert_eq!(&raw[..2], [0x1f, 0x8b]);
+
+    let rows = store.list(&Filter::default()).expect("list");
+    let ids: Vec<&str> = rows.iter().map(|row| row.id.as_str()).collect();
+    assert_eq!(ids, ["3", "2", "1"]);
+}
+
+#[test]
+fn trimming_and_rotation_remove_logs_of_dropped_runs() {
+    let dir = tempdir().expect("tempdir");
+    let with_log = |id: &str| {
+        let log = dir.path().join(format!("{id}.log"));
+        fs::write(&log, id).expect("write log");
+        let mut rec = record(id, RunSource::Task, RunStatus::Success);
+        rec.log_path = Some(log.display().to_string());
+        rec
+    };
+
+    let trimmed = Store::new(dir.path().join("trimmed.jsonl"))
+        .with_max_entries(1)
+        .with_log_dir(dir.path());
+    trimmed.append(&with_log("a")).expect("append");
+    trimmed.append(&with_log("b")).expect("append");
+    assert!(!dir.path().join("a.log").exists());
+    assert!(dir.path().join("b.log").exists());
+
+    let rotated = Store::new(dir.path().join("rotated.jsonl"))
+        .with_rotation(Some(1), 1)
+        .with_log_dir(dir.path());
+    rotated.append(&with_log("c")).expect("append");
+    rotated.append(&with_log("d")).expect("append");
+    assert!(!dir.path().join("c.log").exists());
+    assert!(dir.path().join("d.log").exists());
+}
+
+#[test]
+fn prune_keeps_recent_records_and_returns_removed() {
+    let dir = tempdir().expect("tempdir");
+    let path = dir.path().join("history.jsonl");
+    let store = Store::new(&path);
+
+    let now = OffsetDateTime::now_utc();
+    for (id, age_days) in [("1", 40), ("2", 20), ("3", 10), ("4", 1)] {
+        let mut rec = record(id, RunSource::Task, RunStatus::Success);
+        rec.started_at = now - Duration::days(age_days);
+        store.append(&rec).expect("append");
+    }
+
+    let removed = store
+        .prune(&Retention {
+            keep: None,
+            newer_than: Some(now - Duration::days(30)),
+        })
+        .expect("prune by age");
+    assert_eq!(removed.len(), 1);
+    assert_eq!(removed[0].id, "1");
+
+    let removed = store
+        .prune(&Retention {
+            keep: Some(2),
+            newer_than: None,
+        })
+        .expect("prune by count");
+    let removed: Vec<&str> = removed.iter().map(|row| row.id.as_str()).collect();
+    assert_eq!(removed, ["2"]);
+
+    let rows = store.list(&Filter::default()).expect("list");
+    let ids: Vec<&str> = rows.iter().map(|row| row.id.as_str()).collect();
+    assert_eq!(ids, ["4", "3"]);
+    assert!(!dir.path().join("history.jsonl.tmp").exists());
+}
+
+#[test]
+fn prune_applies_across_rotated_archives() {
+    let dir = tempdir().expect("tempdir");
+    let path = dir.path().join("history.jsonl");
+    let line = serde_json::to_vec(&record("1", RunSource::Task, RunStatus::Success))
+        .expect("serialize")
+        .len() as u64;
+    let store = Store::new(&path)
+        .with_rotation(Some(line + 2), 10)
+        .with_compression(true);
+
+    let now = OffsetDateTime::now_utc();
+    for (id, age_days) in [("1", 6), ("2", 5), ("3", 4), ("4", 3), ("5", 2), ("6", 1)] {
+        let mut rec = record(id, RunSource::Task, RunStatus::Success);
+        rec.started_at = now - Duration::days(age_days);
+        store.append(&rec).expect("append");
+    }
+    assert_eq!(store.archives().expect("archives").len(), 3);
+
+    let removed = store
+        .prune(&Retention {
+            keep: Some(3),
+            newer_than: None,
+        })
+        .expect("prune by count");
+    let removed: Vec<&str> = removed.iter().map(|row| row.id.as_str()).collect();
+    assert_eq!(removed, ["3", "2", "1"]);
+    assert_eq!(store.archives().expect("archives").len(), 2);
+
+    let removed = store
+        .prune(&Retention {
+            keep: None,
+            newer_than: Some(now - Duration::days(2) - Duration::hours(1)),
+        })
+        .expect("prune by age");
+    assert_eq!(removed.len(), 1);
+    assert_eq!(removed[0].id, "4");
+
+    let archives = store.archives().expect("archives");
+    assert_eq!(archives.len(), 1);
+    assert_eq!(
+        &fs::read(&archives[0]).expect("read archive")[..2],
+        [0x1f, 0x8b]
+    );
+    let rows = store.list(&Filter::default()).expect("list");
+    let ids: Vec<&str> = rows.iter().map(|row| row.id.as_str()).collect();
+    assert_eq!(ids, ["6", "5"]);
+}
+
+#[test]
+fn max_entries_trims_rotated_archives() {
+    let dir = tempdir().expect("tempdir");
+    let path = dir.path().join("history.jsonl");
+    let store = Store::new(&path)
+        .with_max_entries(2)
+        .with_rotation(Some(1), 10);
+
+    for id in ["1", "2", "3", "4"] {
+        store
+            .append(&record(id, RunSource::Task, RunStatus::Success))
+            .expect("append");
+    }
+
+    let rows = store.list(&Filter::default()).expect("list");
+    let ids: Vec<&str> = rows.iter().map(|row| row.id.as_str()).collect();
+    assert_eq!(ids, ["4", "3"]);
+    assert_eq!(store.archives().expect("archives").len(), 2);
+}
+
+#[test]
+fn max_entries_leaves_archives_alone_until_rotation() {
+    let dir = tempdir().expect("tempdir");
+    let path = dir.path().join("history.jsonl");
+    let archive = dir.path().join("history.2000-01-01T00-00-00-000000.jsonl");
+    let archived: String = ["1", "2", "3"]
+        .iter()
+        .map(|id| {
+            let line = serde_json::to_string(&record(id, RunSource::Task, RunStatus::Success))
+                .expect("serialize");
+            format!("{line}\n")
+        })
+        .collect();
+    fs::write(&archive, &archived).expect("write archive");
+
+    let store = Store::new(&path).with_max_entries(2);
+    store
+        .append(&record("4", RunSource::Task, RunStatus::Success))
+        .expect("append");
+    assert_eq!(
+        fs::read_to_string(&archive).expect("read archive"),
+        archived
+    );
+
+    let rows = store.list(&Filter::default()).expect("list");
+    let ids: Vec<&str> = rows.iter().map(|row| row.id.as_str()).collect();
+    assert_eq!(ids, ["4", "3"]);
+
+    let rotating = Store::new(&path)
+        .with_max_entries(2)
+        .with_rotation(Some(1), 10);
+    rotating
+        .append(&record("5", RunSource::Task, RunStatus::Success))
+        .expect("append");
+    assert!(!archive.exists());
+    let rows = Store::new(&path).list(&Filter::default()).expect("list");
+    let ids: Vec<&str> = rows.iter().map(|row| row.id.as_str()).collect();
+    assert_eq!(ids, ["5", "4"]);
+}
+
+#[test]
+fn stats_summarise_runs_per_task() {
+    let now = OffsetDateTime::now_utc();
+    let mut records = Vec::new();
+    for (idx, duration_ms) in [100, 200, 300, 400, 1000].into_iter().enumerate() {
+        let status = if idx == 1 {
+            RunStatus::Failed
+        } else {
+            RunStatus::Success
+        };
+        let mut rec = record("build", RunSource::Task, status);
+        rec.duration_ms = duration_ms;
+        rec.started_at = now - Duration::minutes(10 - idx as i64);
+        records.push(rec);
+    }
+    records.push(record("build", RunSource::Task, RunStatus::Skipped));
+    records.push(record("lint", RunSource::Task, RunStatus::Success));
+
+    let stats = stats(&records);
+    assert_eq!(stats.len(), 2);
+    let build = &stats[0];
+    assert_eq!(build.name, "build");
+    assert_eq!((build.runs, build.successes, build.failures), (5, 4, 1));
+    assert!((build.success_rate - 0.8).abs() < f64::EPSILON);
+    assert_eq!((build.p50_ms, build.p95_ms), (300, 1000));
+    assert_eq!(build.last_failure, Some(now - Duration::minutes(9)));
+    assert_eq!(stats[1].last_failure, None);
+}
+
+#[test]
+fn list_pages_with_offset_and_before() {
+    let dir = tempdir().expect("tempdir");
+    let store = Store::new(dir.path().join("history.jsonl"));
+
+    for id in ["a", "b", "c", "d", "e"] {
+        store
+            .append(&record(id, RunSource::Task, RunStatus::Success))
+            .expect("append");
+    }
+
+    let ids = |filter: Filter| -> Vec<String> {
+        store
+            .list(&filter)
+            .expect("list")
+            .into_iter()
+            .map(|row| row.id)
+            .collect()
+    };
+    assert_eq!(
+        ids(Filter {
+            limit: Some(2),
+            offset: 2,
+            ..Filter::default()
+        }),
+        ["c", "b"]
+    );
+    assert_eq!(
+        ids(Filter {
+            limit: Some(2),
+            before: Some("d".to_string()),
+            ..Filter::default()
+        }),
+        ["c", "b"]
+    );
+    assert_eq!(
+        ids(Filter {
+            before: Some("a".to_string()),
+            ..Filter::default()
+        }),
+        Vec::<String>::new()
+    );
+
+    let err = store
+        .list(&Filter {
+            before: Some("zzz".to_string()),
+            ..Filter::default()
+        })
+        .expect_err("unknown cursor");
+    assert!(err.contains("zzz"));
+}
+
+#[test]
+fn remove_logs_only_deletes_owned_log_files() {
+    let dir = tempdir().expect("tempdir");
+    let logs = dir.path().join("logs");
+    fs::create_dir_all(&logs).expect("create logs");
+    let victim = dir.path().join("main.rs");
+    fs::write(&victim, "fn main() {}").expect("write victim");
+    let renamed = logs.join("other.log");
+    fs::write(&renamed, "x").expect("write renamed");
+    let owned = logs.join("owned.log");
+    fs::write(&owned, "x").expect("write owned");
+
+    let mut escape = record("escape", RunSource::Task, RunStatus::Success);
+    escape.log_path = Some(logs.join("../main.rs").display().to_string());
+    let mut mismatched = record("mismatched", RunSource::Task, RunStatus::Success);
+    mismatched.log_path = Some(renamed.display().to_string());
+    let mut mine = record("owned", RunSource::Task, RunStatus::Success);
+    mine.log_path = Some(owned.display().to_string());
+
+    let store = Store::new(dir.path().join("history.jsonl")).with_log_dir(&logs);
+    assert!(store.remove_logs(&[escape, mismatched, mine]).is_empty());
+    assert!(victim.exists());
+    assert!(renamed.exists());
+    assert!(!owned.exists());
+}
+
+#[test]
+fn import_drops_foreign_log_paths() {
+    let dir = tempdir().expect("tempdir");
+    let store = Store::new(dir.path().join("history.jsonl"));
+    let other = Store::new(dir.path().join("agent.jsonl"));
+    let mut foreign = record("foreign", RunSource::Task, RunStatus::Success);
+    foreign.log_path = Some("../../src/main.rs".to_string());
+    other.append(&foreign).expect("append");
+
+    store.import(other.path()).expect("import");
+    let rows = store.list(&Filter::default()).expect("list");
+    assert_eq!(rows[0].id, "foreign");
+    assert_eq!(rows[0].log_path, None);
+}
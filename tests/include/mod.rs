@@ -0,0 +1,48 @@
+use otto_cli::config::load;
+use otto_cli::include::{FetchMode, cache_path, fetch};
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn load_merges_local_include_without_overriding_tasks() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("shared.yml"),
+        r#"tasks:
+  lint:
+    run: "shared lint"
+  fmt:
+    run: "shared fmt"
+"#,
+    )
+    .expect("write include");
+    let path = dir.path().join("otto.yml");
+    fs::write(
+        &path,
+        r#"version: 2
+include: ["shared.yml"]
+tasks:
+  lint:
+    run: "local lint"
+"#,
+    )
+    .expect("write config");
+
+    let cfg = load(&path).expect("load config");
+    let tasks = cfg.tasks.expect("tasks");
+    assert_eq!(tasks["lint"].run, "local lint");
+    assert_eq!(tasks["fmt"].run, "shared fmt");
+}
+
+#[test]
+fn offline_fetch_uses_cache_only() {
+    let dir = tempdir().expect("tempdir");
+    let url = "https://example.invalid/otto/shared.yml";
+
+    let err = fetch(url, dir.path(), FetchMode::Offline).expect_err("not cached");
+    assert!(err.contains("--offline"));
+
+    fs::write(cache_path(dir.path(), url), "tasks: {}\n").expect("seed cache");
+    let text = fetch(url, dir.path(), FetchMode::Offline).expect("cached include");
+    assert_eq!(text, "tasks: {}\n");
+}
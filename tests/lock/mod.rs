@@ -0,0 +1,25 @@
+use otto_cli::lock::acquire;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+use tempfile::tempdir;
+
+#[test]
+fn acquire_waits_for_holder_to_release() {
+    let dir = tempdir().expect("tempdir");
+    let held = acquire(dir.path(), "db", || panic!("first acquire should not wait"))
+        .expect("first acquire");
+
+    let waited = Arc::new(AtomicBool::new(false));
+    let waited_flag = Arc::clone(&waited);
+    let path = dir.path().to_path_buf();
+    let handle = thread::spawn(move || {
+        acquire(&path, "db", || waited_flag.store(true, Ordering::SeqCst)).expect("second acquire")
+    });
+
+    thread::sleep(Duration::from_millis(100));
+    drop(held);
+    let _second = handle.join().expect("join");
+    assert!(waited.load(Ordering::SeqCst));
+}
@@ -0,0 +1,85 @@
+use otto_cli::metrics::{Emitter, Sample, prometheus_text, statsd_lines};
+use std::net::UdpSocket;
+use std::time::Duration;
+
+fn sample() -> Sample {
+    Sample {
+        name: "deploy:prod".to_string(),
+        status: "failed".to_string(),
+        exit_code: 2,
+        duration: Duration::from_millis(1500),
+    }
+}
+
+#[test]
+fn statsd_lines_use_sanitized_task_name() {
+    assert_eq!(
+        statsd_lines("otto", &sample()),
+        "otto.deploy_prod.duration:1500|ms\notto.deploy_prod.failed:1|c\notto.deploy_prod.exit_code:2|g"
+    );
+}
+
+#[test]
+fn prometheus_text_reports_run_values() {
+    let text = prometheus_text("otto", &sample());
+    assert!(text.contains("otto_run_duration_seconds 1.5\n"));
+    assert!(text.contains("otto_run_exit_code 2\n"));
+    assert!(text.contains("otto_run_success 0\n"));
+}
+
+#[test]
+fn emit_sends_statsd_packet() {
+    let socket = UdpSocket::bind("127.0.0.1:0").expect("bind");
+    socket
+        .set_read_timeout(Some(Duration::from_secs(2)))
+        .expect("timeout");
+    let emitter = Emitter {
+        statsd_addr: socket.local_addr().expect("addr").to_string(),
+        pushgateway_url: String::new(),
+        prefix: "otto".to_string(),
+        timeout: Duration::from_secs(1),
+    };
+
+    emitter.emit(&sample()).expect("emit");
+
+    let mut buf = [0_u8; 512];
+    let len = socket.recv(&mut buf).expect("recv");
+    let text = String::from_utf8_lossy(&buf[..len]);
+    assert!(text.starts_with("otto.deploy_prod.duration:1500|ms"));
+}
+
+#[test]
+fn emit_sends_statsd_packet_over_ipv6() {
+    let Ok(socket) = UdpSocket::bind("[::1]:0") else {
+        return;
+    };
+    socket
+        .set_read_timeout(Some(Duration::from_secs(2)))
+        .expect("timeout");
+    let emitter = Emitter {
+        statsd_addr: socket.local_addr().expect("addr").to_string(),
+        pushgateway_url: String::new(),
+        prefix: "otto".to_string(),
+        timeout: Duration::from_secs(1),
+    };
+
+    emitter.emit(&sample()).expect("emit");
+
+    let mut buf = [0_u8; 512];
+    let len = socket.recv(&mut buf).expect("recv");
+    let text = String::from_utf8_lossy(&buf[..len]);
+    assert!(text.starts_with("otto.deploy_prod.duration:1500|ms"));
+}
+
+#[test]
+fn emit_reports_pushgateway_failure() {
+    let emitter = Emitter {
+        statsd_addr: String::new(),
+        pushgateway_url: "http://127.0.0.1:1".to_string(),
+        prefix: "otto".to_string(),
+        timeout: Duration::from_secs(1),
+    };
+
+    let err = emitter.emit(&sample()).expect_err("should fail");
+    assert!(err.contains("pushgateway:"));
+}
@@ -1,4 +1,8 @@
-use otto_cli::notify::{Event, Manager};
+use otto_cli::notify::{
+    Discord, Email, Event, Manager, Ntfy, Pushover, Slack, Telegram, discord_payload,
+    email_message, ntfy_request, pushover_payload, slack_payload, teams_payload, telegram_payload,
+};
+use otto_cli::smtp::Security;
 use std::time::Duration;
 use time::OffsetDateTime;
 
@@ -11,7 +15,10 @@ fn test_event() -> Event {
         duration: Duration::from_millis(500),
         started_at: OffsetDateTime::now_utc(),
         command_preview: "echo ok".to_string(),
+        stdout_tail: None,
         stderr_tail: None,
+        signal: None,
+        git: None,
     }
 }
 
@@ -21,6 +28,13 @@ fn notify_webhook_failure_request() {
         desktop_enabled: false,
         webhook_url: "http://127.0.0.1:1/webhook".to_string(),
         webhook_timeout: Duration::from_secs(1),
+        slack: None,
+        discord: None,
+        teams_webhook_url: String::new(),
+        telegram: None,
+        email: None,
+        ntfy: None,
+        pushover: None,
     };
 
     let err = manager.notify(&test_event()).expect_err("expected failure");
@@ -33,7 +47,233 @@ fn notify_no_providers() {
         desktop_enabled: false,
         webhook_url: String::new(),
         webhook_timeout: Duration::from_secs(1),
+        slack: None,
+        discord: None,
+        teams_webhook_url: String::new(),
+        telegram: None,
+        email: None,
+        ntfy: None,
+        pushover: None,
     };
 
     manager.notify(&test_event()).expect("no-provider notify");
 }
+
+#[test]
+fn slack_payload_mentions_and_shows_stderr_on_failure() {
+    let slack = Slack {
+        webhook_url: "https://hooks.slack.com/services/x".to_string(),
+        channel: "#builds".to_string(),
+        mention_on_failure: "<!here>".to_string(),
+    };
+    let mut event = test_event();
+    event.status = "failed".to_string();
+    event.exit_code = 2;
+    event.stderr_tail = Some("boom\n".to_string());
+
+    let payload = slack_payload(&slack, &event);
+    assert_eq!(payload["channel"], "#builds");
+    let attachment = &payload["attachments"][0];
+    assert_eq!(attachment["color"], "#d00000");
+    let blocks = attachment["blocks"].as_array().expect("blocks");
+    assert_eq!(blocks[0]["text"]["text"], "<!here> *inline* failed");
+    assert!(
+        blocks[1]["elements"][0]["text"]
+            .as_str()
+            .is_some_and(|text| text.contains("exit 2") && text.contains("duration 500ms"))
+    );
+    assert_eq!(blocks[2]["text"]["text"], "```boom```");
+
+    event.status = "success".to_string();
+    let payload = slack_payload(&slack, &event);
+    let blocks = payload["attachments"][0]["blocks"]
+        .as_array()
+        .expect("blocks");
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[0]["text"]["text"], "*inline* success");
+}
+
+#[test]
+fn slack_payload_escapes_names_and_stderr() {
+    let slack = Slack {
+        webhook_url: "https://hooks.slack.com/services/x".to_string(),
+        channel: String::new(),
+        mention_on_failure: String::new(),
+    };
+    let mut event = test_event();
+    event.name = "<!channel> & co".to_string();
+    event.status = "failed".to_string();
+    event.stderr_tail = Some("a < b ``` <@U123>".to_string());
+
+    let payload = slack_payload(&slack, &event);
+    let blocks = payload["attachments"][0]["blocks"]
+        .as_array()
+        .expect("blocks");
+    assert_eq!(
+        blocks[0]["text"]["text"],
+        "*&lt;!channel&gt; &amp; co* failed"
+    );
+    assert_eq!(
+        blocks[2]["text"]["text"],
+        "```a &lt; b \u{2cb}\u{2cb}\u{2cb} &lt;@U123&gt;```"
+    );
+}
+
+#[test]
+fn discord_payload_builds_colored_embed() {
+    let discord = Discord {
+        webhook_url: "https://discord.com/api/webhooks/1/x".to_string(),
+        username: "otto".to_string(),
+    };
+    let mut event = test_event();
+    event.name = "build".to_string();
+    event.status = "failed".to_string();
+    event.exit_code = 1;
+    event.stderr_tail = Some("error: nope".to_string());
+
+    let payload = discord_payload(&discord, &event).expect("payload");
+    assert_eq!(payload["username"], "otto");
+    let embed = &payload["embeds"][0];
+    assert_eq!(embed["title"], "build failed");
+    assert_eq!(embed["color"], 0xd00000);
+    assert_eq!(embed["fields"][2]["value"], "500ms");
+    assert_eq!(embed["description"], "```\nerror: nope\n```");
+
+    event.status = "success".to_string();
+    let payload = discord_payload(&discord, &event).expect("payload");
+    assert_eq!(payload["embeds"][0]["color"], 0x2eb886);
+    assert!(payload["embeds"][0].get("description").is_none());
+}
+
+#[test]
+fn teams_payload_is_adaptive_card() {
+    let mut event = test_event();
+    event.status = "timedout".to_string();
+    event.exit_code = 124;
+
+    let payload = teams_payload(&event);
+    assert_eq!(payload["type"], "message");
+    let attachment = &payload["attachments"][0];
+    assert_eq!(
+        attachment["contentType"],
+        "application/vnd.microsoft.card.adaptive"
+    );
+    let card = &attachment["content"];
+    assert_eq!(card["type"], "AdaptiveCard");
+    assert_eq!(card["body"][0]["text"], "inline timedout");
+    assert_eq!(card["body"][0]["color"], "attention");
+    assert_eq!(card["body"][1]["facts"][1]["value"], "124");
+}
+
+#[test]
+fn telegram_payload_escapes_html() {
+    let mut event = test_event();
+    event.name = "a<b>".to_string();
+    event.status = "failed".to_string();
+    event.exit_code = 1;
+    event.stderr_tail = Some("x < y & z".to_string());
+
+    let payload = telegram_payload("-100123", &event);
+    assert_eq!(payload["chat_id"], "-100123");
+    assert_eq!(payload["parse_mode"], "HTML");
+    let text = payload["text"].as_str().expect("text");
+    assert!(text.starts_with("<b>a&lt;b&gt;</b> failed\nexit 1 | duration 500ms"));
+    assert!(text.ends_with("<pre>x &lt; y &amp; z</pre>"));
+}
+
+#[test]
+fn telegram_requires_token_env() {
+    let manager = Manager {
+        desktop_enabled: false,
+        webhook_url: String::new(),
+        webhook_timeout: Duration::from_secs(1),
+        slack: None,
+        discord: None,
+        teams_webhook_url: String::new(),
+        telegram: Some(Telegram {
+            bot_token_env: "OTTO_TEST_TELEGRAM_TOKEN_UNSET".to_string(),
+            chat_id: "1".to_string(),
+        }),
+        email: None,
+        ntfy: None,
+        pushover: None,
+    };
+
+    let err = manager.notify(&test_event()).expect_err("missing token");
+    assert_eq!(err, "telegram: OTTO_TEST_TELEGRAM_TOKEN_UNSET is not set");
+}
+
+#[test]
+fn email_message_has_headers_and_details() {
+    let email = Email {
+        host: "smtp.example.com".to_string(),
+        port: 587,
+        security: Security::StartTls,
+        username_env: "OTTO_SMTP_USERNAME".to_string(),
+        password_env: "OTTO_SMTP_PASSWORD".to_string(),
+        from: "otto@example.com".to_string(),
+        to: vec!["dev@example.com".to_string(), "ops@example.com".to_string()],
+    };
+    let mut event = test_event();
+    event.name = "deploy".to_string();
+    event.status = "failed".to_string();
+    event.exit_code = 3;
+    event.stderr_tail = Some("permission denied".to_string());
+
+    let text = email_message(&email, &event, OffsetDateTime::now_utc()).expect("message");
+    let (headers, body) = text.split_once("\r\n\r\n").expect("header break");
+    assert!(headers.contains("To: dev@example.com, ops@example.com\r\n"));
+    assert!(headers.contains("Subject: [otto] deploy failed\r\n"));
+    assert!(headers.contains("Content-Type: text/plain; charset=utf-8"));
+    assert!(body.contains("Exit:     3\n"));
+    assert!(body.ends_with("stderr:\npermission denied\n"));
+}
+
+#[test]
+fn ntfy_request_maps_priority_and_topic() {
+    let ntfy = Ntfy {
+        url: "https://ntfy.example.com/alerts/builds".to_string(),
+        token_env: "OTTO_NTFY_TOKEN".to_string(),
+        priorities: [("failed".to_string(), 5)].into_iter().collect(),
+    };
+    let mut event = test_event();
+    event.status = "failed".to_string();
+    event.exit_code = 1;
+
+    let (endpoint, payload) = ntfy_request(&ntfy, &event).expect("request");
+    assert_eq!(endpoint, "https://ntfy.example.com/alerts/");
+    assert_eq!(payload["topic"], "builds");
+    assert_eq!(payload["title"], "inline failed");
+    assert_eq!(payload["priority"], 5);
+    assert_eq!(payload["tags"][0], "x");
+
+    event.status = "success".to_string();
+    let (_, payload) = ntfy_request(&ntfy, &event).expect("request");
+    assert_eq!(payload["priority"], 3);
+}
+
+#[test]
+fn pushover_payload_escalates_failures() {
+    let pushover = Pushover {
+        user_key: "user-key".to_string(),
+        token_env: "OTTO_PUSHOVER_TOKEN".to_string(),
+        failure_priority: 2,
+        retry: Duration::from_secs(60),
+        expire: Duration::from_secs(3600),
+    };
+    let mut event = test_event();
+    event.status = "failed".to_string();
+    event.exit_code = 1;
+
+    let payload = pushover_payload(&pushover, "app-token", &event);
+    assert_eq!(payload["token"], "app-token");
+    assert_eq!(payload["user"], "user-key");
+    assert_eq!(payload["priority"], 2);
+    assert_eq!(payload["retry"], 60);
+    assert_eq!(payload["expire"], 3600);
+
+    event.status = "success".to_string();
+    let payload = pushover_payload(&pushover, "app-token", &event);
+    assert_eq!(payload["priority"], 0);
+    assert!(payload.get("retry").is_none());
+}
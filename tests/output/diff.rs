@@ -0,0 +1,26 @@
+use otto_cli::output::{Change, diff_lines};
+
+#[test]
+fn diff_lines_keeps_common_lines_and_marks_edits() {
+    let changes = diff_lines("a\nb\nc\nd\n", "a\nc\nd\ne\n");
+    assert_eq!(
+        changes,
+        [
+            Change::Same("a"),
+            Change::Removed("b"),
+            Change::Same("c"),
+            Change::Same("d"),
+            Change::Added("e"),
+        ]
+    );
+}
+
+#[test]
+fn diff_lines_of_equal_text_has_no_edits() {
+    let changes = diff_lines("x\ny", "x\ny");
+    assert!(
+        changes
+            .iter()
+            .all(|change| matches!(change, Change::Same(_)))
+    );
+}
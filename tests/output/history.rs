@@ -1,11 +1,13 @@
 use otto_cli::model::{RunSource, RunStatus};
-use otto_cli::output::{HistoryRow, print_history};
+use otto_cli::output::{
+    HistoryRow, HistoryView, csv_column, format_relative, print_history, text_column,
+};
 use time::OffsetDateTime;
 
 #[test]
 fn print_history_empty() {
     let mut out = Vec::new();
-    print_history(&mut out, &[]).expect("print history");
+    print_history(&mut out, &[], &HistoryView::default()).expect("print history");
     let text = String::from_utf8(out).expect("utf8");
     assert!(text.contains("No run history yet"));
 }
@@ -14,16 +16,139 @@ fn print_history_empty() {
 fn print_history_rows() {
     let mut out = Vec::new();
     let rows = vec![HistoryRow {
+        id: "1700000000000-abcd".to_string(),
         name: "inline".to_string(),
         source: RunSource::Inline,
         status: RunStatus::Success,
         exit_code: 0,
+        signal: None,
+        skip_reason: None,
+        forced: false,
+        rerun_of: None,
+        tags: Vec::new(),
+        git: None,
+        host: None,
+        user: None,
+        cwd: None,
         started_at: OffsetDateTime::now_utc(),
         duration_ms: 25,
+        log_path: None,
+        matches: Vec::new(),
     }];
-    print_history(&mut out, &rows).expect("print history");
+    print_history(&mut out, &rows, &HistoryView::default()).expect("print history");
     let text = String::from_utf8(out).expect("utf8");
     assert!(text.contains("inline"));
     assert!(text.contains("success"));
     assert!(text.contains("source: inline"));
+    assert!(text.contains("1700000000000-abcd"));
+}
+
+#[test]
+fn print_history_shows_terminating_signal() {
+    let mut out = Vec::new();
+    let rows = vec![HistoryRow {
+        id: "1700000000000-abcd".to_string(),
+        name: "build".to_string(),
+        source: RunSource::Task,
+        status: RunStatus::Failed,
+        exit_code: 137,
+        signal: Some("SIGKILL".to_string()),
+        skip_reason: None,
+        forced: false,
+        rerun_of: None,
+        tags: Vec::new(),
+        git: None,
+        host: None,
+        user: None,
+        cwd: None,
+        started_at: OffsetDateTime::now_utc(),
+        duration_ms: 25,
+        log_path: None,
+        matches: Vec::new(),
+    }];
+    print_history(&mut out, &rows, &HistoryView::default()).expect("print history");
+    let text = String::from_utf8(out).expect("utf8");
+    assert!(text.contains("(killed by SIGKILL)"));
+}
+
+#[test]
+fn print_history_selected_columns_with_relative_times() {
+    let now = OffsetDateTime::now_utc();
+    let mut out = Vec::new();
+    let rows = vec![HistoryRow {
+        id: "1700000000000-abcd".to_string(),
+        name: "build".to_string(),
+        source: RunSource::Task,
+        status: RunStatus::Failed,
+        exit_code: 1,
+        signal: None,
+        skip_reason: None,
+        forced: false,
+        rerun_of: None,
+        tags: Vec::new(),
+        git: None,
+        host: None,
+        user: None,
+        cwd: None,
+        started_at: now - time::Duration::minutes(3),
+        duration_ms: 1500,
+        log_path: None,
+        matches: Vec::new(),
+    }];
+    let view = HistoryView {
+        columns: vec![
+            "name".to_string(),
+            "status".to_string(),
+            "started".to_string(),
+            "duration".to_string(),
+        ],
+        relative_to: Some(now),
+    };
+    print_history(&mut out, &rows, &view).expect("print history");
+    let text = String::from_utf8(out).expect("utf8");
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains("name") && lines[0].contains("duration"));
+    assert!(lines[1].contains("build"));
+    assert!(lines[1].contains("failed"));
+    assert!(lines[1].contains("3m ago"));
+    assert!(lines[1].contains("1.500s"));
+    assert!(!text.contains("1700000000000-abcd"));
+}
+
+#[test]
+fn column_names_are_shared_between_text_and_csv() {
+    for (text, csv) in [
+        ("exit", "exit_code"),
+        ("started", "started_at"),
+        ("duration", "duration_ms"),
+        ("reason", "skip_reason"),
+        ("log", "log_path"),
+        ("user", "user"),
+    ] {
+        assert_eq!(text_column(text), Some(text));
+        assert_eq!(text_column(csv), Some(text));
+        assert_eq!(csv_column(text), Some(csv));
+        assert_eq!(csv_column(csv), Some(csv));
+    }
+    assert_eq!(text_column("bogus"), None);
+    assert_eq!(csv_column("git"), None);
+}
+
+#[test]
+fn format_relative_picks_largest_unit() {
+    let now = OffsetDateTime::now_utc();
+    assert_eq!(format_relative(now, now), "just now");
+    assert_eq!(
+        format_relative(now - time::Duration::seconds(42), now),
+        "42s ago"
+    );
+    assert_eq!(
+        format_relative(now - time::Duration::minutes(90), now),
+        "1h ago"
+    );
+    assert_eq!(
+        format_relative(now - time::Duration::days(3), now),
+        "3d ago"
+    );
 }
@@ -1,2 +1,4 @@
+mod diff;
 mod history;
+mod stats;
 mod tasks;
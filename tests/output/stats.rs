@@ -0,0 +1,41 @@
+use otto_cli::history::TaskStats;
+use otto_cli::output::{configure, print_stats};
+
+#[test]
+fn print_stats_aligns_columns() {
+    let mut out = Vec::new();
+    let rows = vec![
+        TaskStats {
+            name: "build".to_string(),
+            runs: 12,
+            successes: 9,
+            failures: 3,
+            success_rate: 0.75,
+            p50_ms: 1500,
+            p95_ms: 4000,
+            last_failure: None,
+        },
+        TaskStats {
+            name: "deploy:staging".to_string(),
+            runs: 2,
+            successes: 2,
+            failures: 0,
+            success_rate: 1.0,
+            p50_ms: 250,
+            p95_ms: 300,
+            last_failure: None,
+        },
+    ];
+    configure(true);
+    print_stats(&mut out, &rows).expect("print stats");
+    let text = String::from_utf8(out).expect("utf8");
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "task            runs  success  p50     p95    last failure",
+            "build           12    75%      1.500s  4s     -",
+            "deploy:staging  2     100%     250ms   300ms  -",
+        ]
+    );
+}
@@ -21,3 +21,24 @@ fn print_tasks_rows() {
     assert!(text.contains("test"));
     assert!(text.contains("command:"));
 }
+
+#[test]
+fn print_tasks_groups_namespaces() {
+    let mut out = Vec::new();
+    let rows = vec![
+        TaskRow {
+            name: "build:backend".to_string(),
+            description: String::new(),
+            command: "cargo build".to_string(),
+        },
+        TaskRow {
+            name: "build:frontend".to_string(),
+            description: String::new(),
+            command: "npm run build".to_string(),
+        },
+    ];
+    print_tasks(&mut out, &rows).expect("print tasks");
+    let text = String::from_utf8(out).expect("utf8");
+    assert_eq!(text.matches("build:*").count(), 1);
+    assert!(text.find("build:*") < text.find("build:backend"));
+}
@@ -0,0 +1,53 @@
+use otto_cli::config::Prompt;
+use otto_cli::prompt::{answer, ask};
+use std::collections::HashMap;
+use std::io::Cursor;
+
+fn env_prompt() -> Prompt {
+    Prompt {
+        name: "DEPLOY_ENV".to_string(),
+        message: "Deploy to".to_string(),
+        default: "staging".to_string(),
+        choices: vec!["staging".to_string(), "production".to_string()],
+    }
+}
+
+#[test]
+fn ask_reprompts_until_answer_is_a_choice() {
+    let mut input = Cursor::new("qa\nproduction\n");
+    let mut output = Vec::new();
+
+    let value = ask(&env_prompt(), &mut input, &mut output).expect("answer");
+
+    assert_eq!(value, "production");
+    let shown = String::from_utf8(output).expect("utf8");
+    assert_eq!(
+        shown
+            .matches("Deploy to (staging/production) [staging]: ")
+            .count(),
+        2
+    );
+    assert!(shown.contains("\"qa\" is not one of staging, production"));
+}
+
+#[test]
+fn ask_uses_default_for_empty_answer_and_fails_on_eof() {
+    let mut output = Vec::new();
+    let value = ask(&env_prompt(), &mut Cursor::new("\n"), &mut output).expect("answer");
+    assert_eq!(value, "staging");
+
+    let err = ask(&env_prompt(), &mut Cursor::new(""), &mut output).expect_err("eof");
+    assert!(err.contains("no answer"));
+}
+
+#[test]
+fn answer_prefers_vars_and_checks_choices() {
+    let mut vars = HashMap::new();
+    vars.insert("DEPLOY_ENV".to_string(), "production".to_string());
+    let answers = answer(&[env_prompt()], &vars).expect("answers");
+    assert_eq!(answers["DEPLOY_ENV"], "production");
+
+    vars.insert("DEPLOY_ENV".to_string(), "qa".to_string());
+    let err = answer(&[env_prompt()], &vars).expect_err("invalid choice");
+    assert!(err.contains("not one of"));
+}
@@ -1,6 +1,11 @@
 use otto_cli::model::RunStatus;
-use otto_cli::runner::{Request, execute, tail};
+use otto_cli::runner::{
+    CancelToken, IoPriority, Request, RetryStrategy, ShellKind, StdinMode, decode_utf8, execute,
+    retry_delay, tail,
+};
+use regex::Regex;
 use std::collections::HashMap;
+use std::fs;
 use std::time::Duration;
 use tempfile::tempdir;
 
@@ -15,12 +20,36 @@ fn base_request() -> Request {
             "echo ok".to_string(),
         ],
         shell: String::new(),
+        shell_kind: ShellKind::Default,
         dir: String::new(),
         env: HashMap::new(),
+        inherit_env: true,
+        interactive: false,
+        stdin: StdinMode::Inherit,
+        pty: false,
+        combine_output: false,
+        nice: None,
+        io_priority: IoPriority::Normal,
         timeout: Duration::ZERO,
+        deadline: Duration::ZERO,
+        grace_period: Duration::from_secs(1),
         retries: 0,
         retry_backoff: Duration::from_millis(10),
+        retry_strategy: RetryStrategy::Exponential,
+        retry_jitter: Duration::ZERO,
+        retry_max_backoff: Duration::ZERO,
+        retry_on_exit_codes: Vec::new(),
+        retry_on_timeout: true,
+        retry_when_output_matches: None,
+        output_prefix: None,
+        capture: None,
+        max_captured_output: None,
+        tee: None,
         stream_output: false,
+        log_path: None,
+        events: None,
+        cancel: None,
+        container: None,
     }
 }
 
@@ -45,6 +74,63 @@ fn execute_failure() {
     assert_eq!(err.result.status, RunStatus::Failed);
 }
 
+#[test]
+fn execute_captures_stdout_tail() {
+    let mut req = base_request();
+    req.exec = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "echo 'error: build broke'; echo warn >&2; exit 2".to_string(),
+    ];
+
+    let err = execute(&req).expect_err("expected failure");
+    assert_eq!(
+        err.result.stdout_tail.as_deref(),
+        Some("error: build broke")
+    );
+    assert_eq!(err.result.stderr_tail.as_deref(), Some("warn"));
+}
+
+#[test]
+fn execute_keeps_only_the_tail_of_long_output() {
+    let mut req = base_request();
+    req.exec = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "i=0; while [ $i -lt 20000 ]; do echo line-$i; i=$((i+1)); done".to_string(),
+    ];
+
+    let result = execute(&req).expect("run succeeds");
+    let tail = result.stdout_tail.expect("stdout tail");
+    assert_eq!(tail.lines().count(), 10);
+    assert!(tail.starts_with("line-19990\n"));
+    assert!(tail.ends_with("line-19999"));
+}
+
+#[test]
+fn execute_inherits_stdout_when_nothing_reads_it() {
+    let dir = tempdir().expect("tempdir");
+    let mut req = base_request();
+    req.exec = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "echo streamed".to_string(),
+    ];
+    req.stream_output = true;
+
+    let result = execute(&req).expect("run succeeds");
+    assert_eq!(result.stdout_tail, None);
+
+    req.exec = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "echo logged".to_string(),
+    ];
+    req.log_path = Some(dir.path().join("run.log"));
+    let result = execute(&req).expect("run succeeds");
+    assert_eq!(result.stdout_tail.as_deref(), Some("logged"));
+}
+
 #[test]
 fn execute_timeout() {
     let mut req = base_request();
@@ -57,6 +143,194 @@ fn execute_timeout() {
 
     let err = execute(&req).expect_err("expected timeout");
     assert_eq!(err.result.exit_code, 124);
+    assert_eq!(err.result.status, RunStatus::TimedOut);
+}
+
+#[test]
+fn execute_timeout_sends_sigterm_before_kill() {
+    let dir = tempdir().expect("tempdir");
+    let marker = dir.path().join("terminated");
+
+    let mut req = base_request();
+    req.exec = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        format!(
+            r#"trap 'touch "{}"; exit 0' TERM; sleep 5 >/dev/null 2>&1 & wait"#,
+            marker.display()
+        ),
+    ];
+    req.timeout = Duration::from_millis(200);
+    req.grace_period = Duration::from_secs(2);
+
+    let err = execute(&req).expect_err("expected timeout");
+    assert_eq!(err.result.exit_code, 124);
+    assert!(marker.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn execute_timeout_kills_grandchildren() {
+    let mut req = base_request();
+    req.exec = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "sleep 30 & wait".to_string(),
+    ];
+    req.timeout = Duration::from_millis(200);
+    req.grace_period = Duration::ZERO;
+
+    let started = std::time::Instant::now();
+    let err = execute(&req).expect_err("expected timeout");
+    assert_eq!(err.result.exit_code, 124);
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn execute_reads_stdin_from_file() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("input.txt");
+    fs::write(&input, "line one\nline two\n").expect("write input");
+
+    let mut req = base_request();
+    req.exec = vec!["/bin/sh".to_string(), "-c".to_string(), "wc -l".to_string()];
+    req.stdin = StdinMode::File(input);
+
+    let result = execute(&req).expect("run succeeds");
+    assert_eq!(result.stdout_tail.as_deref().map(str::trim), Some("2"));
+
+    req.stdin = StdinMode::File(dir.path().join("missing.txt"));
+    let err = execute(&req).expect_err("missing stdin file");
+    assert!(err.message.contains("open stdin file"));
+}
+
+#[test]
+fn execute_caps_captured_output_and_log() {
+    let dir = tempdir().expect("tempdir");
+    let log = dir.path().join("run.log");
+
+    let mut req = base_request();
+    req.exec = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "i=0; while [ $i -lt 2000 ]; do echo line-$i; i=$((i+1)); done".to_string(),
+    ];
+    req.max_captured_output = Some(1024);
+    req.log_path = Some(log.clone());
+
+    let result = execute(&req).expect("run succeeds");
+    assert!(
+        result
+            .stdout_tail
+            .as_deref()
+            .is_some_and(|tail| tail.ends_with("line-1999"))
+    );
+
+    let text = fs::read_to_string(&log).expect("read log");
+    assert!(text.starts_with("line-0\n"));
+    assert!(text.contains("output truncated"));
+    assert!(text.len() < 1200);
+}
+
+#[cfg(unix)]
+#[test]
+fn execute_records_terminating_signal() {
+    let mut req = base_request();
+    req.exec = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "kill -SEGV $$".to_string(),
+    ];
+
+    let err = execute(&req).expect_err("signalled run fails");
+    assert_eq!(err.result.exit_code, 139);
+    assert_eq!(err.result.signal.as_deref(), Some("SIGSEGV"));
+    assert_eq!(err.message, "command killed by SIGSEGV");
+}
+
+#[cfg(unix)]
+#[test]
+fn execute_pty_presents_terminal_output() {
+    let mut req = base_request();
+    req.exec = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "[ -t 1 ] && echo tty || echo pipe; echo err >&2".to_string(),
+    ];
+    req.pty = true;
+
+    let result = execute(&req).expect("run succeeds");
+    assert_eq!(result.stdout_tail.as_deref(), Some("tty\nerr"));
+    assert_eq!(result.stderr_tail, None);
+}
+
+#[cfg(unix)]
+#[test]
+fn execute_combine_output_merges_stderr_into_stdout() {
+    let mut req = base_request();
+    req.exec = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "echo out; echo err >&2; echo done".to_string(),
+    ];
+    req.combine_output = true;
+
+    let result = execute(&req).expect("run succeeds");
+    assert_eq!(result.stdout_tail.as_deref(), Some("out\nerr\ndone"));
+    assert_eq!(result.stderr_tail, None);
+}
+
+#[cfg(unix)]
+#[test]
+fn execute_applies_nice_level() {
+    let mut req = base_request();
+    req.exec = vec!["/bin/sh".to_string(), "-c".to_string(), "nice".to_string()];
+    req.nice = Some(19);
+
+    let result = execute(&req).expect("run succeeds");
+    assert_eq!(result.stdout_tail.as_deref(), Some("19"));
+}
+
+#[cfg(unix)]
+#[test]
+fn execute_uses_configured_shell() {
+    let mut req = base_request();
+    req.use_shell = true;
+    req.exec.clear();
+    req.shell = "echo ${BASH_VERSION:+bash}".to_string();
+    req.shell_kind = ShellKind::Bash;
+
+    let result = execute(&req).expect("run succeeds");
+    assert_eq!(result.stdout_tail.as_deref(), Some("bash"));
+}
+
+#[test]
+fn execute_deadline_caps_retries() {
+    let mut req = base_request();
+    req.exec = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "sleep 1".to_string(),
+    ];
+    req.timeout = Duration::from_secs(5);
+    req.deadline = Duration::from_millis(200);
+    req.grace_period = Duration::ZERO;
+    req.retries = 3;
+
+    let started = std::time::Instant::now();
+    let err = execute(&req).expect_err("expected deadline");
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert_eq!(err.result.exit_code, 124);
+    assert_eq!(err.message, "run exceeded deadline of 200ms");
+
+    req.exec[2] = "exit 3".to_string();
+    req.retry_backoff = Duration::from_millis(500);
+    let err = execute(&req).expect_err("expected failure");
+    assert_eq!(err.result.exit_code, 3);
+    assert!(
+        err.message
+            .contains("deadline of 200ms leaves no time to retry")
+    );
 }
 
 #[test]
@@ -79,6 +353,139 @@ fn execute_retry_then_success() {
     assert_eq!(result.exit_code, 0);
 }
 
+#[test]
+fn execute_records_attempts_and_exports_attempt_env() {
+    let mut req = base_request();
+    req.use_shell = true;
+    req.exec.clear();
+    req.shell = r#"echo "try $OTTO_ATTEMPT of $OTTO_MAX_ATTEMPTS" >&2; [ "$OTTO_ATTEMPT" = 3 ]"#
+        .to_string();
+    req.retries = 2;
+    req.retry_backoff = Duration::from_millis(10);
+    req.retry_strategy = RetryStrategy::Fixed;
+
+    let result = execute(&req).expect("third attempt succeeds");
+    let codes: Vec<(i32, i32)> = result
+        .attempts
+        .iter()
+        .map(|attempt| (attempt.attempt, attempt.exit_code))
+        .collect();
+    assert_eq!(codes, vec![(1, 1), (2, 1), (3, 0)]);
+    assert_eq!(
+        result.attempts[1].stderr_tail.as_deref(),
+        Some("try 2 of 3")
+    );
+}
+
+#[test]
+fn execute_stops_when_cancelled() {
+    let parent = CancelToken::new();
+    let token = parent.child();
+    let mut req = base_request();
+    req.exec = vec!["sleep".to_string(), "5".to_string()];
+    req.retries = 2;
+    req.cancel = Some(token);
+
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(200));
+        parent.cancel();
+    });
+    let started = std::time::Instant::now();
+    let err = execute(&req).expect_err("cancelled");
+    canceller.join().expect("join canceller");
+
+    assert!(started.elapsed() < Duration::from_secs(4));
+    assert_eq!(err.result.status, RunStatus::Interrupted);
+    assert_eq!(err.result.attempts.len(), 1);
+    assert!(err.message.contains("cancelled"));
+}
+
+#[test]
+fn execute_records_a_timed_out_cancellation_as_timed_out() {
+    let parent = CancelToken::new();
+    let mut req = base_request();
+    req.exec = vec!["sleep".to_string(), "5".to_string()];
+    req.retries = 2;
+    req.cancel = Some(parent.child());
+
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(200));
+        parent.time_out("after group \"ci\" timed out after 200ms");
+    });
+    let err = execute(&req).expect_err("timed out");
+    canceller.join().expect("join canceller");
+
+    assert_eq!(err.result.status, RunStatus::TimedOut);
+    assert_eq!(err.result.exit_code, 124);
+    assert_eq!(err.result.attempts.len(), 1);
+    assert!(err.message.contains("timed out after 200ms"));
+}
+
+#[test]
+fn execute_skips_retry_for_unlisted_exit_code() {
+    let dir = tempdir().expect("tempdir");
+    let counter = dir.path().join("count");
+    let script = format!(r#"echo x >> "{}"; exit 3"#, counter.display());
+
+    let mut req = base_request();
+    req.use_shell = true;
+    req.exec.clear();
+    req.shell = script;
+    req.retries = 2;
+    req.retry_on_exit_codes = vec![2, 137];
+
+    let failure = execute(&req).expect_err("should fail");
+    assert_eq!(failure.result.exit_code, 3);
+    let attempts = fs::read_to_string(&counter).expect("read counter");
+    assert_eq!(attempts.lines().count(), 1);
+}
+
+#[test]
+fn execute_retries_only_when_output_matches() {
+    let mut req = base_request();
+    req.use_shell = true;
+    req.exec.clear();
+    req.shell = r#"if [ "$OTTO_ATTEMPT" = 1 ]; then echo "429 Too Many Requests" >&2; else echo "bad config" >&2; fi; exit 1"#
+        .to_string();
+    req.retries = 3;
+    req.retry_backoff = Duration::from_millis(10);
+    req.retry_when_output_matches =
+        Some(Regex::new("connection reset|429 Too Many Requests").expect("regex"));
+
+    let failure = execute(&req).expect_err("should fail");
+    assert_eq!(failure.result.attempts.len(), 2);
+    assert_eq!(failure.result.stderr_tail.as_deref(), Some("bad config"));
+}
+
+#[test]
+fn execute_interactive_does_not_capture_stderr() {
+    let mut req = base_request();
+    req.exec = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "echo err >&2; exit 3".to_string(),
+    ];
+    req.interactive = true;
+
+    let err = execute(&req).expect_err("expected failure");
+    assert_eq!(err.result.exit_code, 3);
+    assert!(err.result.stderr_tail.is_none());
+}
+
+#[test]
+fn execute_clean_env_only_sees_declared_vars() {
+    let mut req = base_request();
+    req.exec = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        r#"test -z "$HOME" && test "$DECLARED" = yes"#.to_string(),
+    ];
+    req.env = HashMap::from([("DECLARED".to_string(), "yes".to_string())]);
+    req.inherit_env = false;
+
+    execute(&req).expect("clean env run succeeds");
+}
+
 #[test]
 fn validate_request_retries() {
     let mut req = base_request();
@@ -92,3 +499,113 @@ fn tail_limits_output() {
     let out = tail(input, 3, 10).expect("tail");
     assert_eq!(out, "d\ne\nf");
 }
+
+#[test]
+fn decode_utf8_carries_split_characters_to_the_next_chunk() {
+    let text = "héllo ✓".as_bytes();
+    let mut pending = text[..2].to_vec();
+    assert_eq!(decode_utf8(&mut pending), "h");
+    assert_eq!(pending, text[1..2]);
+
+    pending.extend_from_slice(&text[2..text.len() - 1]);
+    assert_eq!(decode_utf8(&mut pending), "éllo ");
+    assert_eq!(pending.len(), 2);
+
+    pending.extend_from_slice(&text[text.len() - 1..]);
+    assert_eq!(decode_utf8(&mut pending), "✓");
+    assert!(pending.is_empty());
+
+    let mut invalid = b"a\xffb\xe2".to_vec();
+    assert_eq!(decode_utf8(&mut invalid), "a\u{fffd}b");
+    assert_eq!(invalid, b"\xe2");
+}
+
+#[test]
+fn retry_delay_follows_strategy_and_cap() {
+    let base = Duration::from_secs(1);
+    let none = Duration::ZERO;
+
+    assert_eq!(retry_delay(RetryStrategy::Fixed, base, 3, none), base);
+    assert_eq!(
+        retry_delay(RetryStrategy::Linear, base, 2, none),
+        Duration::from_secs(3)
+    );
+    assert_eq!(
+        retry_delay(RetryStrategy::Exponential, base, 3, none),
+        Duration::from_secs(8)
+    );
+    assert_eq!(
+        retry_delay(
+            RetryStrategy::Exponential,
+            base,
+            10,
+            Duration::from_secs(30)
+        ),
+        Duration::from_secs(30)
+    );
+    assert_eq!(
+        retry_delay(
+            RetryStrategy::Exponential,
+            base,
+            40,
+            Duration::from_secs(300)
+        ),
+        Duration::from_secs(300)
+    );
+    assert_eq!(
+        retry_delay(RetryStrategy::Linear, Duration::MAX, 1, none),
+        Duration::MAX
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn execute_timeout_kills_grandchildren_with_inherited_stdin() {
+    let scripts = [
+        r#"sleep 100 & echo $! > "PID_FILE"; wait"#,
+        r#"/bin/sh -c 'sleep 100 & echo $! > "PID_FILE"'; sleep 100"#,
+    ];
+    for (script, interactive) in scripts
+        .into_iter()
+        .flat_map(|script| [(script, false), (script, true)])
+    {
+        let dir = tempdir().expect("tempdir");
+        let pid_file = dir.path().join("sleep.pid");
+
+        let mut req = base_request();
+        req.interactive = interactive;
+        req.stdin = StdinMode::Inherit;
+        req.exec = vec![
+            "/bin/sh".to_string(),
+            "-c".to_string(),
+            script.replace("PID_FILE", &pid_file.display().to_string()),
+        ];
+        req.timeout = Duration::from_millis(300);
+        req.grace_period = Duration::from_secs(1);
+
+        let err = execute(&req).expect_err("expected timeout");
+        assert_eq!(err.result.exit_code, 124);
+
+        let pid = fs::read_to_string(&pid_file).expect("read pid");
+        let pid = pid.trim();
+        let started = std::time::Instant::now();
+        let alive = loop {
+            let out = std::process::Command::new("ps")
+                .args(["-o", "stat=", "-p", pid])
+                .output()
+                .expect("run ps");
+            let stat = String::from_utf8_lossy(&out.stdout).trim().to_string();
+            if stat.is_empty() || stat.starts_with('Z') {
+                break false;
+            }
+            if started.elapsed() > Duration::from_secs(3) {
+                break true;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        };
+        assert!(
+            !alive,
+            "grandchild {pid} of {script:?} survived (interactive: {interactive})"
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use otto_cli::schedule::Schedule;
+use time::macros::datetime;
+
+#[test]
+fn schedule_matches_fields_and_steps() {
+    let schedule = Schedule::parse("*/15 9-17 * * 1-5").expect("parse");
+
+    assert!(schedule.matches(datetime!(2025-03-03 09:45 UTC)));
+    assert!(!schedule.matches(datetime!(2025-03-03 09:50 UTC)));
+    assert!(!schedule.matches(datetime!(2025-03-03 18:00 UTC)));
+    assert!(!schedule.matches(datetime!(2025-03-02 10:00 UTC)));
+}
+
+#[test]
+fn schedule_day_and_weekday_match_either() {
+    let schedule = Schedule::parse("0 0 1 * 7").expect("parse");
+
+    assert!(schedule.matches(datetime!(2025-04-01 00:00 UTC)));
+    assert!(schedule.matches(datetime!(2025-03-02 00:00 UTC)));
+    assert!(!schedule.matches(datetime!(2025-03-03 00:00 UTC)));
+
+    let daily = Schedule::parse("@daily").expect("parse macro");
+    assert!(daily.matches(datetime!(2025-03-03 00:00 UTC)));
+    assert!(!daily.matches(datetime!(2025-03-03 00:01 UTC)));
+}
+
+#[test]
+fn schedule_rejects_invalid_expressions() {
+    for expr in [
+        "* * * *",
+        "60 * * * *",
+        "*/0 * * * *",
+        "5-1 * * * *",
+        "a * * * *",
+    ] {
+        assert!(Schedule::parse(expr).is_err(), "{expr} should be rejected");
+    }
+}
@@ -0,0 +1,154 @@
+use otto_cli::smtp::{Message, Security, Server, address, recipient, send};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+fn fake_server(auth: bool) -> (u16, thread::JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+    let port = listener.local_addr().expect("addr").port();
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().expect("accept");
+        let mut reader = BufReader::new(stream.try_clone().expect("clone"));
+        let mut writer = stream;
+        let mut seen = Vec::new();
+        writer.write_all(b"220 fake ready\r\n").expect("greet");
+        let mut in_data = false;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).expect("read") == 0 {
+                break;
+            }
+            let line = line.trim_end_matches("\r\n").to_string();
+            seen.push(line.clone());
+            if in_data {
+                if line == "." {
+                    in_data = false;
+                    writer.write_all(b"250 queued\r\n").expect("reply");
+                }
+                continue;
+            }
+            let reply: &[u8] = match line.split_whitespace().next().unwrap_or_default() {
+                "EHLO" if auth => b"250-fake\r\n250 AUTH LOGIN PLAIN\r\n",
+                "EHLO" => b"250 fake\r\n",
+                "AUTH" => b"235 ok\r\n",
+                "DATA" => {
+                    in_data = true;
+                    b"354 go ahead\r\n"
+                }
+                "QUIT" => {
+                    writer.write_all(b"221 bye\r\n").expect("reply");
+                    break;
+                }
+                _ => b"250 ok\r\n",
+            };
+            writer.write_all(reply).expect("reply");
+        }
+        seen
+    });
+    (port, handle)
+}
+
+fn server(port: u16, credentials: Option<(String, String)>) -> Server {
+    Server {
+        host: "127.0.0.1".to_string(),
+        port,
+        security: Security::None,
+        credentials,
+        timeout: Duration::from_secs(5),
+    }
+}
+
+#[test]
+fn send_delivers_message_with_dot_stuffing() {
+    let (port, handle) = fake_server(true);
+    let message = Message {
+        from: "otto@example.com".to_string(),
+        to: vec!["a@example.com".to_string(), "b@example.com".to_string()],
+        body: "Subject: hi\r\n\r\nline one\n.hidden\n".to_string(),
+    };
+
+    send(
+        &server(port, Some(("user".to_string(), "pass".to_string()))),
+        &message,
+    )
+    .expect("send");
+    let seen = handle.join().expect("server");
+    assert!(seen.contains(&"AUTH PLAIN AHVzZXIAcGFzcw==".to_string()));
+    assert!(seen.contains(&"MAIL FROM:<otto@example.com>".to_string()));
+    assert!(seen.contains(&"RCPT TO:<b@example.com>".to_string()));
+    assert!(seen.contains(&"..hidden".to_string()));
+    assert_eq!(seen.last().map(String::as_str), Some("QUIT"));
+}
+
+#[test]
+fn send_requires_auth_support_for_credentials() {
+    let (port, _handle) = fake_server(false);
+    let message = Message {
+        from: "otto@example.com".to_string(),
+        to: vec!["a@example.com".to_string()],
+        body: "hi\n".to_string(),
+    };
+
+    let err = send(
+        &server(port, Some(("user".to_string(), "pass".to_string()))),
+        &message,
+    )
+    .expect_err("no auth");
+    assert!(
+        err.contains("No compatible authentication mechanism"),
+        "{err}"
+    );
+}
+
+#[test]
+fn send_uses_the_address_from_a_display_name() {
+    let (port, handle) = fake_server(false);
+    let message = Message {
+        from: "Otto <otto@example.com>".to_string(),
+        to: vec!["a@example.com".to_string()],
+        body: "hi\n".to_string(),
+    };
+
+    send(&server(port, None), &message).expect("send");
+    let seen = handle.join().expect("server");
+    assert!(seen.contains(&"MAIL FROM:<otto@example.com>".to_string()));
+}
+
+#[test]
+fn send_refuses_cleartext_credentials_to_remote_hosts() {
+    let message = Message {
+        from: "otto@example.com".to_string(),
+        to: vec!["a@example.com".to_string()],
+        body: "hi\n".to_string(),
+    };
+    let server = Server {
+        host: "mail.example.com".to_string(),
+        ..server(25, Some(("user".to_string(), "pass".to_string())))
+    };
+
+    let err = send(&server, &message).expect_err("cleartext auth");
+    assert_eq!(
+        err,
+        "refusing to send credentials to mail.example.com without TLS or STARTTLS"
+    );
+}
+
+#[test]
+fn addresses_reject_header_and_command_injection() {
+    assert_eq!(address("Otto <otto@x.dev>"), Ok("otto@x.dev"));
+    assert_eq!(address("otto@x.dev"), Ok("otto@x.dev"));
+    assert!(address("otto@x.dev\r\nBcc: evil@x.dev").is_err());
+    assert!(address("Otto <otto@x.dev> SIZE=1").is_err());
+    assert!(address("<a@x.dev>@x.dev>").is_err());
+    assert!(recipient("Ops <ops@x.dev>").is_err());
+    assert!(recipient("ops@x.dev").is_ok());
+}
+
+#[test]
+fn security_parses_known_modes() {
+    assert_eq!(Security::parse("starttls"), Some(Security::StartTls));
+    assert_eq!(Security::parse("tls"), Some(Security::Tls));
+    assert_eq!(Security::parse("none"), Some(Security::None));
+    assert_eq!(Security::parse("ssl"), None);
+}
@@ -1,7 +1,15 @@
+mod cache;
 mod cli;
 mod config;
 mod envfile;
+mod git;
 mod history;
+mod include;
+mod lock;
+mod metrics;
 mod notify;
 mod output;
+mod prompt;
 mod runner;
+mod schedule;
+mod smtp;